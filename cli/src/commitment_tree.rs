@@ -0,0 +1,118 @@
+//! Per-pool note commitment trees and incremental Merkle witnesses.
+//!
+//! Spend proofs are built against the *global* note commitment tree root, so
+//! every output's commitment - owned by this wallet or not - has to be
+//! appended to its pool's tree in strict block order. [`TreeTracker`] does
+//! that, and additionally starts an [`IncrementalWitness`] at the leaf of
+//! any note the wallet owns, advancing every open witness alongside the
+//! tree as later commitments arrive. Serialized tree state and witnesses
+//! are persisted by `Database` (see `save_tree_state`/`save_note_witness`),
+//! keyed by block height, so a witness can later be read back as of
+//! whatever anchor height a spend is built against.
+
+use anyhow::{Result, anyhow};
+use incrementalmerkletree::Hashable;
+use incrementalmerkletree::frontier::CommitmentTree;
+use incrementalmerkletree::witness::IncrementalWitness;
+use orchard::tree::MerkleHashOrchard;
+use sapling_crypto::Node as SaplingNode;
+use zcash_primitives::merkle_tree::{
+    read_commitment_tree, read_incremental_witness, write_commitment_tree,
+    write_incremental_witness,
+};
+
+/// Depth of both pools' note commitment trees (ZIP 202 / ZIP 225).
+pub const MERKLE_DEPTH: u8 = 32;
+
+/// Tracks one pool's commitment tree plus a witness for every note the
+/// wallet owns; witnesses are kept and advanced for as long as the tracker
+/// lives.
+pub struct TreeTracker<H> {
+    tree: CommitmentTree<H, 32>,
+    /// `(note_id, witness)` pairs for notes whose leaf has been seen but
+    /// which haven't necessarily reached an anchor height yet.
+    witnesses: Vec<(i64, IncrementalWitness<H, 32>)>,
+}
+
+impl<H: Hashable + Clone> TreeTracker<H> {
+    /// Start tracking from an empty tree (e.g. wallet birthday at genesis).
+    pub fn empty() -> Self {
+        TreeTracker {
+            tree: CommitmentTree::empty(),
+            witnesses: Vec::new(),
+        }
+    }
+
+    /// Resume tracking from a previously-serialized tree. Witnesses for
+    /// notes already known to the wallet must be reattached separately via
+    /// `track_witness`, since the tree alone doesn't record which of its
+    /// leaves are ours.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let tree = read_commitment_tree(bytes).map_err(|e| anyhow!("corrupt tree state: {e}"))?;
+        Ok(TreeTracker {
+            tree,
+            witnesses: Vec::new(),
+        })
+    }
+
+    /// Append a commitment for one output, in tree order. `owned` is the
+    /// note's database id when this output belongs to the wallet, which
+    /// starts a fresh witness at this leaf.
+    pub fn append(&mut self, node: H, owned: Option<i64>) -> Result<()> {
+        for (_, witness) in self.witnesses.iter_mut() {
+            witness
+                .append(node.clone())
+                .map_err(|_| anyhow!("commitment tree is full"))?;
+        }
+        self.tree
+            .append(node.clone())
+            .map_err(|_| anyhow!("commitment tree is full"))?;
+        if let Some(note_id) = owned {
+            self.witnesses
+                .push((note_id, IncrementalWitness::from_tree(self.tree.clone())));
+        }
+        Ok(())
+    }
+
+    /// Re-attach a witness for a note restored from the database (e.g. after
+    /// the process restarts mid-sync), so subsequent `append` calls keep
+    /// advancing it.
+    pub fn track_witness(&mut self, note_id: i64, witness: IncrementalWitness<H, 32>) {
+        self.witnesses.push((note_id, witness));
+    }
+
+    /// Serialize the tree state for persistence.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        write_commitment_tree(&self.tree, &mut out)
+            .map_err(|e| anyhow!("failed to serialize tree state: {e}"))?;
+        Ok(out)
+    }
+
+    /// Serialize `note_id`'s current witness, if one is being tracked.
+    pub fn witness_bytes(&self, note_id: i64) -> Result<Option<Vec<u8>>> {
+        let Some((_, witness)) = self.witnesses.iter().find(|(id, _)| *id == note_id) else {
+            return Ok(None);
+        };
+        let mut out = Vec::new();
+        write_incremental_witness(witness, &mut out)
+            .map_err(|e| anyhow!("failed to serialize witness: {e}"))?;
+        Ok(Some(out))
+    }
+}
+
+/// A `TreeTracker` specialized for the Sapling pool.
+pub type SaplingTreeTracker = TreeTracker<SaplingNode>;
+
+/// A `TreeTracker` specialized for the Orchard pool.
+pub type OrchardTreeTracker = TreeTracker<MerkleHashOrchard>;
+
+/// Deserialize a previously-persisted Sapling witness.
+pub fn read_sapling_witness(bytes: &[u8]) -> Result<IncrementalWitness<SaplingNode, 32>> {
+    read_incremental_witness(bytes).map_err(|e| anyhow!("corrupt sapling witness: {e}"))
+}
+
+/// Deserialize a previously-persisted Orchard witness.
+pub fn read_orchard_witness(bytes: &[u8]) -> Result<IncrementalWitness<MerkleHashOrchard, 32>> {
+    read_incremental_witness(bytes).map_err(|e| anyhow!("corrupt orchard witness: {e}"))
+}
@@ -18,6 +18,128 @@ pub struct Note {
     pub address: Option<String>,
     pub height: Option<i64>,
     pub spent_txid: Option<String>,
+    /// "incoming", "wallet_internal" (change), or "outgoing".
+    pub transfer_type: String,
+    /// This note's leaf position in its pool's note commitment tree, if known.
+    pub position: Option<i64>,
+    /// Fiat price per ZEC at the time this note was received, if recorded.
+    pub acquired_price: Option<f64>,
+    /// `YYYY-MM-DD` date this note was received, if recorded.
+    pub acquired_date: Option<String>,
+}
+
+/// Config key holding the highest migration index that has been applied.
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// One step of the schema's evolution. Migrations run in order, oldest
+/// first, and are never rewritten once released - a later change to a
+/// table adds a new migration rather than editing an old one, so that
+/// databases created at any past version upgrade in place.
+type Migration = fn(&Connection) -> Result<()>;
+
+/// The ordered list of schema migrations. Index `i` (0-based) is schema
+/// version `i + 1`.
+fn migrations() -> Vec<Migration> {
+    vec![
+        migration_0001_initial_schema,
+        migration_0002_cost_basis,
+        migration_0003_transaction_fees,
+    ]
+}
+
+fn migration_0001_initial_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            txid TEXT NOT NULL,
+            output_index INTEGER NOT NULL,
+            pool TEXT NOT NULL,
+            value INTEGER NOT NULL,
+            commitment TEXT,
+            nullifier TEXT,
+            memo TEXT,
+            address TEXT,
+            height INTEGER,
+            spent_txid TEXT,
+            transfer_type TEXT NOT NULL DEFAULT 'incoming',
+            position INTEGER,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(txid, output_index, pool)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_nullifier ON notes(nullifier);
+        CREATE INDEX IF NOT EXISTS idx_spent ON notes(spent_txid);
+
+        CREATE TABLE IF NOT EXISTS tree_state (
+            pool TEXT NOT NULL,
+            height INTEGER NOT NULL,
+            tree_bytes BLOB NOT NULL,
+            PRIMARY KEY (pool, height)
+        );
+
+        CREATE TABLE IF NOT EXISTS note_witnesses (
+            note_id INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            witness_bytes BLOB NOT NULL,
+            PRIMARY KEY (note_id, height),
+            FOREIGN KEY (note_id) REFERENCES notes(id)
+        );
+        "#,
+    )?;
+
+    // A database created before schema versioning already has a `notes`
+    // table without `transfer_type`/`position`, which the CREATE above
+    // leaves untouched, so add those columns where they are missing.
+    let columns = {
+        let mut stmt = conn.prepare("PRAGMA table_info(notes)")?;
+        stmt.query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    if !columns.iter().any(|c| c == "transfer_type") {
+        conn.execute_batch(
+            "ALTER TABLE notes ADD COLUMN transfer_type TEXT NOT NULL DEFAULT 'incoming';",
+        )?;
+    }
+    if !columns.iter().any(|c| c == "position") {
+        conn.execute_batch("ALTER TABLE notes ADD COLUMN position INTEGER;")?;
+    }
+    Ok(())
+}
+
+/// Adds fiat cost-basis tracking: a per-note acquisition price/date, and a
+/// cache of historical ZEC/fiat quotes keyed by height so repeated scans
+/// don't refetch them.
+fn migration_0002_cost_basis(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE notes ADD COLUMN acquired_price REAL;
+        ALTER TABLE notes ADD COLUMN acquired_date TEXT;
+
+        CREATE TABLE IF NOT EXISTS prices (
+            height INTEGER NOT NULL,
+            currency TEXT NOT NULL,
+            price REAL NOT NULL,
+            PRIMARY KEY (height, currency)
+        );
+        "#,
+    )?;
+    Ok(())
+}
+
+/// Adds a `transactions` table recording the fee paid per spending txid, so
+/// the capital-gains engine can adjust proceeds/basis for fees.
+fn migration_0003_transaction_fees(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS transactions (
+            txid TEXT PRIMARY KEY,
+            fee INTEGER NOT NULL,
+            height INTEGER
+        );
+        "#,
+    )?;
+    Ok(())
 }
 
 /// Database handle for note storage.
@@ -26,11 +148,12 @@ pub struct Database {
 }
 
 impl Database {
-    /// Open or create a database at the given path.
+    /// Open or create a database at the given path, migrating its schema
+    /// up to the latest version in place.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
         let conn = Connection::open(path).context("Failed to open database")?;
         let db = Self { conn };
-        db.init()?;
+        db.migrate()?;
         Ok(db)
     }
 
@@ -39,41 +162,47 @@ impl Database {
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory().context("Failed to open in-memory database")?;
         let db = Self { conn };
-        db.init()?;
+        db.migrate()?;
         Ok(db)
     }
 
-    /// Initialize the database schema.
-    fn init(&self) -> Result<()> {
-        self.conn
-            .execute_batch(
-                r#"
-            CREATE TABLE IF NOT EXISTS config (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            );
+    /// Bring the database's schema up to the latest version, running every
+    /// migration whose version exceeds the stored `schema_version` inside
+    /// its own transaction and bumping the stored version as each succeeds.
+    /// Safe to call on an already-current database (a no-op) or an older
+    /// one missing recently-added tables/columns.
+    pub fn migrate(&self) -> Result<()> {
+        // The config table has to exist before schema_version can be read,
+        // and it's part of the schema every version depends on, so it's
+        // created directly rather than as its own migration.
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS config (key TEXT PRIMARY KEY, value TEXT NOT NULL);",
+        )?;
 
-            CREATE TABLE IF NOT EXISTS notes (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                txid TEXT NOT NULL,
-                output_index INTEGER NOT NULL,
-                pool TEXT NOT NULL,
-                value INTEGER NOT NULL,
-                commitment TEXT,
-                nullifier TEXT,
-                memo TEXT,
-                address TEXT,
-                height INTEGER,
-                spent_txid TEXT,
-                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-                UNIQUE(txid, output_index, pool)
-            );
+        let current_version: i64 = self
+            .get_config(SCHEMA_VERSION_KEY)?
+            .map(|v| v.parse())
+            .transpose()
+            .context("Corrupt schema_version in config")?
+            .unwrap_or(0);
 
-            CREATE INDEX IF NOT EXISTS idx_nullifier ON notes(nullifier);
-            CREATE INDEX IF NOT EXISTS idx_spent ON notes(spent_txid);
-            "#,
-            )
-            .context("Failed to initialize database schema")?;
+        for (i, migration) in migrations().iter().enumerate() {
+            let version = (i + 1) as i64;
+            if version <= current_version {
+                continue;
+            }
+            let tx = self
+                .conn
+                .unchecked_transaction()
+                .context("Failed to start migration transaction")?;
+            migration(&tx)
+                .with_context(|| format!("Migration to schema version {version} failed"))?;
+            tx.execute(
+                "INSERT OR REPLACE INTO config (key, value) VALUES (?1, ?2)",
+                params![SCHEMA_VERSION_KEY, version.to_string()],
+            )?;
+            tx.commit()?;
+        }
         Ok(())
     }
 
@@ -100,6 +229,7 @@ impl Database {
     }
 
     /// Insert a new note. Returns Ok(true) if inserted, Ok(false) if already exists.
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_note(
         &self,
         txid: &str,
@@ -111,12 +241,14 @@ impl Database {
         memo: Option<&str>,
         address: Option<&str>,
         height: Option<i64>,
+        transfer_type: &str,
+        position: Option<i64>,
     ) -> Result<bool> {
         let result = self.conn.execute(
             r#"
             INSERT OR IGNORE INTO notes
-            (txid, output_index, pool, value, commitment, nullifier, memo, address, height)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            (txid, output_index, pool, value, commitment, nullifier, memo, address, height, transfer_type, position)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
             "#,
             params![
                 txid,
@@ -127,7 +259,9 @@ impl Database {
                 nullifier,
                 memo,
                 address,
-                height
+                height,
+                transfer_type,
+                position
             ],
         )?;
         Ok(result > 0)
@@ -138,7 +272,8 @@ impl Database {
         let mut stmt = self.conn.prepare(
             r#"
             SELECT id, txid, output_index, pool, value, commitment, nullifier,
-                   memo, address, height, spent_txid
+                   memo, address, height, spent_txid, transfer_type, position,
+                   acquired_price, acquired_date
             FROM notes
             WHERE spent_txid IS NULL
             ORDER BY id
@@ -158,6 +293,10 @@ impl Database {
                     address: row.get(8)?,
                     height: row.get(9)?,
                     spent_txid: row.get(10)?,
+                    transfer_type: row.get(11)?,
+                    position: row.get(12)?,
+                    acquired_price: row.get(13)?,
+                    acquired_date: row.get(14)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
@@ -169,7 +308,8 @@ impl Database {
         let mut stmt = self.conn.prepare(
             r#"
             SELECT id, txid, output_index, pool, value, commitment, nullifier,
-                   memo, address, height, spent_txid
+                   memo, address, height, spent_txid, transfer_type, position,
+                   acquired_price, acquired_date
             FROM notes
             ORDER BY id
             "#,
@@ -188,12 +328,64 @@ impl Database {
                     address: row.get(8)?,
                     height: row.get(9)?,
                     spent_txid: row.get(10)?,
+                    transfer_type: row.get(11)?,
+                    position: row.get(12)?,
+                    acquired_price: row.get(13)?,
+                    acquired_date: row.get(14)?,
                 })
             })?
             .collect::<Result<Vec<_>, _>>()?;
         Ok(notes)
     }
 
+    /// Look up a note's id by its natural key, as used right after
+    /// `insert_note` when the caller needs the id to attach cost-basis or
+    /// witness data.
+    pub fn find_note_id(&self, txid: &str, output_index: i64, pool: &str) -> Result<Option<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM notes WHERE txid = ?1 AND output_index = ?2 AND pool = ?3")?;
+        let mut rows = stmt.query(params![txid, output_index, pool])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Record the fiat cost basis - price per ZEC and acquisition date - a
+    /// note was received at, so a later disposal can be valued against it.
+    pub fn record_cost_basis(&self, note_id: i64, price: f64, date: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE notes SET acquired_price = ?1, acquired_date = ?2 WHERE id = ?3",
+            params![price, date, note_id],
+        )?;
+        Ok(())
+    }
+
+    /// Cache a historical ZEC/`currency` quote for `height`.
+    pub fn cache_price(&self, height: i64, currency: &str, price: f64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO prices (height, currency, price) VALUES (?1, ?2, ?3)",
+            params![height, currency, price],
+        )?;
+        Ok(())
+    }
+
+    /// Look up a cached historical ZEC/`currency` quote for `height`, if one
+    /// has been fetched before.
+    pub fn get_cached_price(&self, height: i64, currency: &str) -> Result<Option<f64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT price FROM prices WHERE height = ?1 AND currency = ?2")?;
+        let mut rows = stmt.query(params![height, currency])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Mark notes as spent by matching nullifiers.
     /// Returns the number of notes marked as spent.
     pub fn mark_spent_by_nullifiers(
@@ -212,23 +404,126 @@ impl Database {
         Ok(count)
     }
 
+    /// Record the fee paid by a spending transaction, so disposals of the
+    /// notes it spends can have proceeds/basis adjusted for it.
+    pub fn record_transaction_fee(&self, txid: &str, fee: i64, height: Option<i64>) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO transactions (txid, fee, height) VALUES (?1, ?2, ?3)",
+            params![txid, fee, height],
+        )?;
+        Ok(())
+    }
+
+    /// Look up a spending transaction's `(fee, height)`, if recorded.
+    pub fn get_transaction(&self, txid: &str) -> Result<Option<(i64, Option<i64>)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT fee, height FROM transactions WHERE txid = ?1")?;
+        let mut rows = stmt.query(params![txid])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some((row.get(0)?, row.get(1)?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get all spent notes (`spent_txid IS NOT NULL`), oldest acquisition
+    /// first - the order the FIFO capital-gains lot matching consumes them
+    /// in.
+    pub fn get_spent_notes_fifo(&self) -> Result<Vec<Note>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, txid, output_index, pool, value, commitment, nullifier,
+                   memo, address, height, spent_txid, transfer_type, position,
+                   acquired_price, acquired_date
+            FROM notes
+            WHERE spent_txid IS NOT NULL
+            ORDER BY acquired_date IS NULL, acquired_date, id
+            "#,
+        )?;
+        let notes = stmt
+            .query_map([], |row| {
+                Ok(Note {
+                    id: row.get(0)?,
+                    txid: row.get(1)?,
+                    output_index: row.get(2)?,
+                    pool: row.get(3)?,
+                    value: row.get(4)?,
+                    commitment: row.get(5)?,
+                    nullifier: row.get(6)?,
+                    memo: row.get(7)?,
+                    address: row.get(8)?,
+                    height: row.get(9)?,
+                    spent_txid: row.get(10)?,
+                    transfer_type: row.get(11)?,
+                    position: row.get(12)?,
+                    acquired_price: row.get(13)?,
+                    acquired_date: row.get(14)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(notes)
+    }
+
+    /// Get a specific set of notes by id (specific-identification lot
+    /// selection), in the given order.
+    pub fn get_notes_by_id(&self, note_ids: &[i64]) -> Result<Vec<Note>> {
+        let mut notes = Vec::with_capacity(note_ids.len());
+        for &note_id in note_ids {
+            let mut stmt = self.conn.prepare(
+                r#"
+                SELECT id, txid, output_index, pool, value, commitment, nullifier,
+                       memo, address, height, spent_txid, transfer_type, position,
+                       acquired_price, acquired_date
+                FROM notes
+                WHERE id = ?1
+                "#,
+            )?;
+            let mut rows = stmt.query(params![note_id])?;
+            if let Some(row) = rows.next()? {
+                notes.push(Note {
+                    id: row.get(0)?,
+                    txid: row.get(1)?,
+                    output_index: row.get(2)?,
+                    pool: row.get(3)?,
+                    value: row.get(4)?,
+                    commitment: row.get(5)?,
+                    nullifier: row.get(6)?,
+                    memo: row.get(7)?,
+                    address: row.get(8)?,
+                    height: row.get(9)?,
+                    spent_txid: row.get(10)?,
+                    transfer_type: row.get(11)?,
+                    position: row.get(12)?,
+                    acquired_price: row.get(13)?,
+                    acquired_date: row.get(14)?,
+                });
+            }
+        }
+        Ok(notes)
+    }
+
     /// Calculate the total balance of unspent notes.
+    ///
+    /// Excludes "outgoing" notes: those represent an output the wallet sent
+    /// to someone else, recovered via the outgoing viewing key, and were
+    /// never ours to hold.
     pub fn get_balance(&self) -> Result<i64> {
         let balance: i64 = self.conn.query_row(
-            "SELECT COALESCE(SUM(value), 0) FROM notes WHERE spent_txid IS NULL",
+            "SELECT COALESCE(SUM(value), 0) FROM notes WHERE spent_txid IS NULL AND transfer_type != 'outgoing'",
             [],
             |row| row.get(0),
         )?;
         Ok(balance)
     }
 
-    /// Get balance by pool type.
+    /// Get balance by pool type. Excludes "outgoing" notes (see `get_balance`).
     pub fn get_balance_by_pool(&self) -> Result<Vec<(String, i64)>> {
         let mut stmt = self.conn.prepare(
             r#"
             SELECT pool, COALESCE(SUM(value), 0)
             FROM notes
-            WHERE spent_txid IS NULL
+            WHERE spent_txid IS NULL AND transfer_type != 'outgoing'
             GROUP BY pool
             ORDER BY pool
             "#,
@@ -238,6 +533,88 @@ impl Database {
             .collect::<Result<Vec<_>, _>>()?;
         Ok(balances)
     }
+
+    /// Get the total value of notes recovered as outgoing (sent to others),
+    /// i.e. the wallet's own spends, recovered via the outgoing viewing key.
+    pub fn get_outgoing_total(&self) -> Result<i64> {
+        let total: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(value), 0) FROM notes WHERE transfer_type = 'outgoing'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(total)
+    }
+
+    /// Get the total value of notes genuinely received from elsewhere, i.e.
+    /// taxable income. Excludes both "wallet_internal" notes (change the
+    /// wallet sent back to itself, decrypted via the internal IVK - never a
+    /// new receipt) and "outgoing" notes (payments to someone else), so
+    /// moving funds between one's own pools never shows up as income.
+    pub fn get_income_total(&self) -> Result<i64> {
+        let total: i64 = self.conn.query_row(
+            "SELECT COALESCE(SUM(value), 0) FROM notes WHERE transfer_type = 'incoming'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(total)
+    }
+
+    /// Persist a pool's commitment tree state as of `height`.
+    pub fn save_tree_state(&self, pool: &str, height: i64, tree_bytes: &[u8]) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO tree_state (pool, height, tree_bytes) VALUES (?1, ?2, ?3)",
+            params![pool, height, tree_bytes],
+        )?;
+        Ok(())
+    }
+
+    /// Load the most recently saved `(height, tree_bytes)` for a pool, if any.
+    pub fn get_latest_tree_state(&self, pool: &str) -> Result<Option<(i64, Vec<u8>)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT height, tree_bytes FROM tree_state WHERE pool = ?1 ORDER BY height DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query(params![pool])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some((row.get(0)?, row.get(1)?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Persist a note's witness as of `height`.
+    pub fn save_note_witness(&self, note_id: i64, height: i64, witness_bytes: &[u8]) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO note_witnesses (note_id, height, witness_bytes) VALUES (?1, ?2, ?3)",
+            params![note_id, height, witness_bytes],
+        )?;
+        Ok(())
+    }
+
+    /// Load a note's witness as of the latest height no later than
+    /// `chain_tip_height - anchor_offset` (e.g. `anchor_offset = 10` for the
+    /// standard 10-confirmation anchor). Returns `None` if the note has no
+    /// witness recorded that old yet.
+    pub fn get_note_witness(
+        &self,
+        note_id: i64,
+        chain_tip_height: i64,
+        anchor_offset: i64,
+    ) -> Result<Option<Vec<u8>>> {
+        let anchor_height = chain_tip_height - anchor_offset;
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT witness_bytes FROM note_witnesses
+            WHERE note_id = ?1 AND height <= ?2
+            ORDER BY height DESC LIMIT 1
+            "#,
+        )?;
+        let mut rows = stmt.query(params![note_id, anchor_height])?;
+        if let Some(row) = rows.next()? {
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -266,6 +643,8 @@ mod tests {
                 None,
                 None,
                 Some(100),
+                "incoming",
+                None,
             )
             .unwrap();
         assert!(inserted);
@@ -282,6 +661,8 @@ mod tests {
                 None,
                 None,
                 Some(100),
+                "incoming",
+                None,
             )
             .unwrap();
         assert!(!inserted_again);
@@ -310,6 +691,8 @@ mod tests {
             None,
             None,
             None,
+            "incoming",
+            None,
         )
         .unwrap();
         db.insert_note(
@@ -322,6 +705,8 @@ mod tests {
             None,
             None,
             None,
+            "incoming",
+            None,
         )
         .unwrap();
 
@@ -342,6 +727,114 @@ mod tests {
         assert_eq!(notes[0].txid, "tx2");
     }
 
+    #[test]
+    fn test_outgoing_notes_excluded_from_balance() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.insert_note(
+            "tx1",
+            0,
+            "sapling",
+            1000000,
+            Some("c1"),
+            Some("n1"),
+            None,
+            None,
+            None,
+            "incoming",
+            None,
+        )
+        .unwrap();
+        db.insert_note(
+            "tx1",
+            1,
+            "sapling",
+            400000,
+            Some("c2"),
+            None,
+            None,
+            None,
+            None,
+            "wallet_internal",
+            None,
+        )
+        .unwrap();
+        db.insert_note(
+            "tx1",
+            2,
+            "sapling",
+            250000,
+            Some("c3"),
+            None,
+            None,
+            None,
+            None,
+            "outgoing",
+            None,
+        )
+        .unwrap();
+
+        // Change is ours and counts towards balance; the outgoing payment
+        // to someone else does not.
+        assert_eq!(db.get_balance().unwrap(), 1400000);
+        assert_eq!(db.get_outgoing_total().unwrap(), 250000);
+
+        // All three still show up when listing every note.
+        assert_eq!(db.get_all_notes().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_income_total_excludes_change_and_outgoing() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.insert_note(
+            "tx1",
+            0,
+            "sapling",
+            1000000,
+            Some("c1"),
+            Some("n1"),
+            None,
+            None,
+            None,
+            "incoming",
+            None,
+        )
+        .unwrap();
+        db.insert_note(
+            "tx1",
+            1,
+            "sapling",
+            400000,
+            Some("c2"),
+            None,
+            None,
+            None,
+            None,
+            "wallet_internal",
+            None,
+        )
+        .unwrap();
+        db.insert_note(
+            "tx1",
+            2,
+            "sapling",
+            250000,
+            Some("c3"),
+            None,
+            None,
+            None,
+            None,
+            "outgoing",
+            None,
+        )
+        .unwrap();
+
+        // Only the genuinely-received note counts as income; change
+        // returned to the wallet and outgoing payments to others don't.
+        assert_eq!(db.get_income_total().unwrap(), 1000000);
+    }
+
     #[test]
     fn test_config() {
         let db = Database::open_in_memory().unwrap();
@@ -354,4 +847,243 @@ mod tests {
             Some("http://localhost:8232".to_string())
         );
     }
+
+    #[test]
+    fn test_migrate_is_idempotent_and_records_version() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(
+            db.get_config(SCHEMA_VERSION_KEY).unwrap(),
+            Some(migrations().len().to_string())
+        );
+
+        // Re-running migrate on an already-current database is a no-op,
+        // not a failure (e.g. from re-creating tables that already exist).
+        db.migrate().unwrap();
+        assert_eq!(
+            db.get_config(SCHEMA_VERSION_KEY).unwrap(),
+            Some(migrations().len().to_string())
+        );
+    }
+
+    #[test]
+    fn test_migrate_upgrades_an_older_database() {
+        // A database created before schema versioning: no schema_version,
+        // and a `notes` table lacking every column added since. `migrate`
+        // must bring it up to date rather than error on what's missing.
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"
+            CREATE TABLE config (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+            CREATE TABLE notes (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                txid TEXT NOT NULL,
+                output_index INTEGER NOT NULL,
+                pool TEXT NOT NULL,
+                value INTEGER NOT NULL,
+                commitment TEXT,
+                nullifier TEXT,
+                memo TEXT,
+                address TEXT,
+                height INTEGER,
+                spent_txid TEXT,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(txid, output_index, pool)
+            );
+            "#,
+        )
+        .unwrap();
+        let db = Database { conn };
+        db.migrate().unwrap();
+
+        assert_eq!(
+            db.get_config(SCHEMA_VERSION_KEY).unwrap(),
+            Some(migrations().len().to_string())
+        );
+        assert!(db.get_unspent_notes().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tree_state_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(db.get_latest_tree_state("sapling").unwrap().is_none());
+
+        db.save_tree_state("sapling", 100, b"tree-at-100").unwrap();
+        db.save_tree_state("sapling", 200, b"tree-at-200").unwrap();
+        db.save_tree_state("orchard", 200, b"orchard-tree").unwrap();
+
+        // Only the latest height for the requested pool comes back.
+        let (height, bytes) = db.get_latest_tree_state("sapling").unwrap().unwrap();
+        assert_eq!(height, 200);
+        assert_eq!(bytes, b"tree-at-200");
+    }
+
+    #[test]
+    fn test_note_witness_respects_anchor_offset() {
+        let db = Database::open_in_memory().unwrap();
+        let note_id = 1;
+
+        db.save_note_witness(note_id, 90, b"witness-at-90").unwrap();
+        db.save_note_witness(note_id, 100, b"witness-at-100")
+            .unwrap();
+
+        // Chain tip 105 with a 10-block anchor offset means only witnesses
+        // saved at height <= 95 are old enough to spend against.
+        let witness = db.get_note_witness(note_id, 105, 10).unwrap();
+        assert_eq!(witness, Some(b"witness-at-90".to_vec()));
+
+        // With the chain tip further along, the height-100 witness qualifies.
+        let witness = db.get_note_witness(note_id, 115, 10).unwrap();
+        assert_eq!(witness, Some(b"witness-at-100".to_vec()));
+
+        // No witness has been recorded yet for a note that's too recent.
+        assert!(db.get_note_witness(2, 105, 10).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_price_cache_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(db.get_cached_price(2_000_000, "usd").unwrap().is_none());
+
+        db.cache_price(2_000_000, "usd", 30.5).unwrap();
+        assert_eq!(db.get_cached_price(2_000_000, "usd").unwrap(), Some(30.5));
+
+        // Distinct currencies at the same height are cached separately.
+        assert!(db.get_cached_price(2_000_000, "eur").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_record_cost_basis() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.insert_note(
+            "tx1",
+            0,
+            "sapling",
+            1000000,
+            Some("c1"),
+            Some("n1"),
+            None,
+            None,
+            Some(100),
+            "incoming",
+            None,
+        )
+        .unwrap();
+        let note_id = db.find_note_id("tx1", 0, "sapling").unwrap().unwrap();
+
+        db.record_cost_basis(note_id, 45.25, "2021-05-01").unwrap();
+
+        let note = db
+            .get_unspent_notes()
+            .unwrap()
+            .into_iter()
+            .find(|n| n.id == note_id)
+            .unwrap();
+        assert_eq!(note.acquired_price, Some(45.25));
+        assert_eq!(note.acquired_date, Some("2021-05-01".to_string()));
+    }
+
+    #[test]
+    fn test_transaction_fee_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+
+        assert!(db.get_transaction("tx1").unwrap().is_none());
+
+        db.record_transaction_fee("tx1", 10_000, Some(500)).unwrap();
+        assert_eq!(
+            db.get_transaction("tx1").unwrap(),
+            Some((10_000, Some(500)))
+        );
+    }
+
+    #[test]
+    fn test_get_spent_notes_fifo_orders_by_acquired_date() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.insert_note(
+            "tx1",
+            0,
+            "sapling",
+            1000000,
+            Some("c1"),
+            Some("n1"),
+            None,
+            None,
+            Some(200),
+            "incoming",
+            None,
+        )
+        .unwrap();
+        db.insert_note(
+            "tx2",
+            0,
+            "sapling",
+            2000000,
+            Some("c2"),
+            Some("n2"),
+            None,
+            None,
+            Some(100),
+            "incoming",
+            None,
+        )
+        .unwrap();
+
+        let id1 = db.find_note_id("tx1", 0, "sapling").unwrap().unwrap();
+        let id2 = db.find_note_id("tx2", 0, "sapling").unwrap().unwrap();
+        db.record_cost_basis(id1, 50.0, "2021-06-01").unwrap();
+        db.record_cost_basis(id2, 40.0, "2021-01-01").unwrap();
+
+        db.mark_spent_by_nullifiers(&["n1".to_string(), "n2".to_string()], "tx3")
+            .unwrap();
+
+        let spent = db.get_spent_notes_fifo().unwrap();
+        assert_eq!(spent.len(), 2);
+        // The earlier acquisition (tx2, 2021-01-01) is consumed first.
+        assert_eq!(spent[0].id, id2);
+        assert_eq!(spent[1].id, id1);
+    }
+
+    #[test]
+    fn test_get_notes_by_id_preserves_requested_order() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.insert_note(
+            "tx1",
+            0,
+            "sapling",
+            1000000,
+            Some("c1"),
+            None,
+            None,
+            None,
+            None,
+            "incoming",
+            None,
+        )
+        .unwrap();
+        db.insert_note(
+            "tx2",
+            0,
+            "sapling",
+            2000000,
+            Some("c2"),
+            None,
+            None,
+            None,
+            None,
+            "incoming",
+            None,
+        )
+        .unwrap();
+        let id1 = db.find_note_id("tx1", 0, "sapling").unwrap().unwrap();
+        let id2 = db.find_note_id("tx2", 0, "sapling").unwrap().unwrap();
+
+        let notes = db.get_notes_by_id(&[id2, id1]).unwrap();
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].id, id2);
+        assert_eq!(notes[1].id, id1);
+    }
 }
@@ -0,0 +1,332 @@
+//! FIFO / specific-identification capital-gains reporting over spent notes.
+//!
+//! Joins each spent note (carrying the fiat cost basis recorded by
+//! [`crate::price`] at receipt) to the transaction that spent it, and
+//! reports a per-disposal realized gain or loss: proceeds are the note's
+//! value at the spending transaction's fiat price, basis is the stored
+//! `acquired_price`, and both are adjusted for a share of that
+//! transaction's fee. Lots can be matched oldest-first (FIFO) or by an
+//! explicit list of note ids (specific identification).
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::db::Database;
+use crate::price::{get_or_fetch_price, height_to_date, PriceOracle};
+
+const ZATOSHI_PER_ZEC: f64 = 100_000_000.0;
+
+/// A holding period's capital-gains treatment under the common "one year"
+/// short/long-term split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldingTerm {
+    ShortTerm,
+    LongTerm,
+}
+
+impl HoldingTerm {
+    /// Classify a holding period of `days`, using the common 365-day
+    /// short/long-term cutoff (e.g. US capital-gains treatment).
+    pub fn from_holding_days(days: i64) -> Self {
+        if days > 365 {
+            HoldingTerm::LongTerm
+        } else {
+            HoldingTerm::ShortTerm
+        }
+    }
+}
+
+/// Which spent notes to report disposals for, and in what order to apply
+/// them against acquisition lots.
+pub enum LotSelection<'a> {
+    /// Every spent note, oldest acquisition first.
+    Fifo,
+    /// Only the listed note ids (specific identification), in the given order.
+    Specific(&'a [i64]),
+}
+
+/// A single realized disposal: one spent note matched against its
+/// acquisition cost basis.
+#[derive(Debug, Clone)]
+pub struct Disposal {
+    pub note_id: i64,
+    pub pool: String,
+    pub value_zatoshi: i64,
+    pub spent_txid: String,
+    pub acquired_date: Option<String>,
+    pub disposed_date: Option<String>,
+    pub holding_days: Option<i64>,
+    pub term: Option<HoldingTerm>,
+    /// Fiat value of the note at the time it was spent, if a price was
+    /// available.
+    pub proceeds: Option<f64>,
+    /// Fiat cost basis recorded at receipt, if one was recorded.
+    pub basis: Option<f64>,
+    /// This disposal's share of its spending transaction's fee, prorated
+    /// by value among every note that transaction spent, valued in fiat at
+    /// the same price as `proceeds`.
+    pub fee_share: Option<f64>,
+    /// `proceeds - basis - fee_share`, if all three are known.
+    pub gain: Option<f64>,
+}
+
+/// Build the realized-gains report for `selection`, quoting in `currency`.
+/// A disposal whose spending transaction's height or fee isn't known yet
+/// still appears in the report, just with the fields that depend on it left
+/// `None` - a missing quote never drops a disposal from the report.
+pub fn report(
+    db: &Database,
+    oracle: &PriceOracle,
+    currency: &str,
+    selection: LotSelection,
+) -> Result<Vec<Disposal>> {
+    let notes = match selection {
+        LotSelection::Fifo => db.get_spent_notes_fifo()?,
+        LotSelection::Specific(note_ids) => db.get_notes_by_id(note_ids)?,
+    };
+
+    // Fee is prorated by value among every note spent in the same
+    // transaction, so sum each spending txid's total disposed value first.
+    let mut value_by_spending_txid: HashMap<String, i64> = HashMap::new();
+    for note in &notes {
+        if let Some(ref spent_txid) = note.spent_txid {
+            *value_by_spending_txid
+                .entry(spent_txid.clone())
+                .or_insert(0) += note.value;
+        }
+    }
+
+    let mut disposals = Vec::with_capacity(notes.len());
+    for note in notes {
+        let Some(spent_txid) = note.spent_txid.clone() else {
+            continue;
+        };
+
+        let spending_tx = db.get_transaction(&spent_txid)?;
+        let disposed_height = spending_tx.as_ref().and_then(|(_, height)| *height);
+        let fee = spending_tx.map(|(fee, _)| fee);
+
+        let disposed_date = disposed_height.map(|h| height_to_date(h as u32));
+        let holding_days = match (&note.acquired_date, &disposed_date) {
+            (Some(acquired), Some(disposed)) => date_diff_days(acquired, disposed),
+            _ => None,
+        };
+        let term = holding_days.map(HoldingTerm::from_holding_days);
+
+        let disposed_price = disposed_height.and_then(|h| {
+            get_or_fetch_price(oracle, db, h as u32, currency)
+                .ok()
+                .flatten()
+        });
+        let proceeds = disposed_price.map(|price| (note.value as f64 / ZATOSHI_PER_ZEC) * price);
+        let basis = note
+            .acquired_price
+            .map(|price| (note.value as f64 / ZATOSHI_PER_ZEC) * price);
+
+        let total_spent_value = value_by_spending_txid
+            .get(&spent_txid)
+            .copied()
+            .unwrap_or(0);
+        let fee_share = fee
+            .filter(|_| total_spent_value > 0)
+            .zip(disposed_price)
+            .map(|(fee, price)| {
+                (fee as f64 / ZATOSHI_PER_ZEC)
+                    * (note.value as f64 / total_spent_value as f64)
+                    * price
+            });
+
+        let gain = match (proceeds, basis, fee_share) {
+            (Some(proceeds), Some(basis), Some(fee_share)) => Some(proceeds - basis - fee_share),
+            _ => None,
+        };
+
+        disposals.push(Disposal {
+            note_id: note.id,
+            pool: note.pool,
+            value_zatoshi: note.value,
+            spent_txid,
+            acquired_date: note.acquired_date,
+            disposed_date,
+            holding_days,
+            term,
+            proceeds,
+            basis,
+            fee_share,
+            gain,
+        });
+    }
+
+    Ok(disposals)
+}
+
+/// Render a gains report as CSV, suitable for import into tax-filing
+/// software.
+pub fn to_csv(disposals: &[Disposal]) -> String {
+    let mut out = String::from(
+        "note_id,pool,value_zatoshi,spent_txid,acquired_date,disposed_date,holding_days,term,proceeds,basis,fee_share,gain\n",
+    );
+    for d in disposals {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            d.note_id,
+            d.pool,
+            d.value_zatoshi,
+            d.spent_txid,
+            d.acquired_date.as_deref().unwrap_or(""),
+            d.disposed_date.as_deref().unwrap_or(""),
+            opt_to_string(d.holding_days),
+            d.term
+                .map(|t| match t {
+                    HoldingTerm::ShortTerm => "short_term",
+                    HoldingTerm::LongTerm => "long_term",
+                })
+                .unwrap_or(""),
+            opt_to_string(d.proceeds),
+            opt_to_string(d.basis),
+            opt_to_string(d.fee_share),
+            opt_to_string(d.gain),
+        ));
+    }
+    out
+}
+
+fn opt_to_string<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// Whole-day difference between two `YYYY-MM-DD` dates, or `None` if either
+/// fails to parse.
+fn date_diff_days(from: &str, to: &str) -> Option<i64> {
+    Some(days_since_epoch(to)? - days_since_epoch(from)?)
+}
+
+/// Parse a `YYYY-MM-DD` date into a day count since the Unix epoch, via
+/// Howard Hinnant's `days_from_civil` algorithm (the inverse of
+/// `price::height_to_date`'s `civil_from_days`).
+fn days_since_epoch(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = (y - era * 400) as u64;
+    let mp = if m > 2 { m - 3 } else { m + 9 } as u64;
+    let doy = (153 * mp + 2) / 5 + d as u64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe as i64 - 719_468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_diff_days() {
+        assert_eq!(date_diff_days("2021-01-01", "2021-01-02"), Some(1));
+        assert_eq!(date_diff_days("2021-01-01", "2022-01-01"), Some(365));
+        assert_eq!(date_diff_days("2020-01-01", "2021-01-01"), Some(366)); // leap year
+    }
+
+    #[test]
+    fn test_holding_term_cutoff() {
+        assert_eq!(HoldingTerm::from_holding_days(365), HoldingTerm::ShortTerm);
+        assert_eq!(HoldingTerm::from_holding_days(366), HoldingTerm::LongTerm);
+    }
+
+    fn setup_spent_note(db: &Database) -> i64 {
+        db.insert_note(
+            "tx1",
+            0,
+            "sapling",
+            100_000_000, // 1 ZEC
+            Some("c1"),
+            Some("n1"),
+            None,
+            None,
+            Some(100),
+            "incoming",
+            None,
+        )
+        .unwrap();
+        let note_id = db.find_note_id("tx1", 0, "sapling").unwrap().unwrap();
+        db.record_cost_basis(note_id, 40.0, &height_to_date(100)).unwrap();
+        db.mark_spent_by_nullifiers(&["n1".to_string()], "tx2")
+            .unwrap();
+        note_id
+    }
+
+    #[test]
+    fn test_report_computes_gain_when_price_and_fee_are_known() {
+        let db = Database::open_in_memory().unwrap();
+        let note_id = setup_spent_note(&db);
+
+        db.record_transaction_fee("tx2", 1_000_000, Some(200))
+            .unwrap();
+        db.cache_price(200, "usd", 60.0).unwrap();
+
+        let oracle = PriceOracle::default();
+        let disposals = report(&db, &oracle, "usd", LotSelection::Fifo).unwrap();
+
+        assert_eq!(disposals.len(), 1);
+        let d = &disposals[0];
+        assert_eq!(d.note_id, note_id);
+        assert_eq!(d.basis, Some(40.0));
+        assert_eq!(d.proceeds, Some(60.0));
+        // 0.01 ZEC fee, all of it on this one note, at 60 per ZEC.
+        assert_eq!(d.fee_share, Some(0.6));
+        assert_eq!(d.gain, Some(60.0 - 40.0 - 0.6));
+        // Height 200 is barely past acquisition height 100, so well within
+        // the short-term window.
+        assert_eq!(
+            d.holding_days,
+            date_diff_days(&height_to_date(100), d.disposed_date.as_ref().unwrap())
+        );
+        assert_eq!(d.term, Some(HoldingTerm::ShortTerm));
+    }
+
+    #[test]
+    fn test_report_degrades_gracefully_without_transaction_info() {
+        let db = Database::open_in_memory().unwrap();
+        setup_spent_note(&db);
+
+        let oracle = PriceOracle::default();
+        let disposals = report(&db, &oracle, "usd", LotSelection::Fifo).unwrap();
+
+        assert_eq!(disposals.len(), 1);
+        let d = &disposals[0];
+        assert!(d.proceeds.is_none());
+        assert!(d.fee_share.is_none());
+        assert!(d.gain.is_none());
+        // The disposal is still reported even with no spend-side data yet.
+        assert_eq!(d.basis, Some(40.0));
+    }
+
+    #[test]
+    fn test_specific_identification_selects_requested_note() {
+        let db = Database::open_in_memory().unwrap();
+        let note_id = setup_spent_note(&db);
+
+        let oracle = PriceOracle::default();
+        let disposals = report(&db, &oracle, "usd", LotSelection::Specific(&[note_id])).unwrap();
+
+        assert_eq!(disposals.len(), 1);
+        assert_eq!(disposals[0].note_id, note_id);
+    }
+
+    #[test]
+    fn test_csv_rendering() {
+        let db = Database::open_in_memory().unwrap();
+        setup_spent_note(&db);
+
+        let oracle = PriceOracle::default();
+        let disposals = report(&db, &oracle, "usd", LotSelection::Fifo).unwrap();
+        let csv = to_csv(&disposals);
+
+        assert!(csv.starts_with("note_id,pool,value_zatoshi"));
+        assert_eq!(csv.lines().count(), 2);
+    }
+}
@@ -6,15 +6,21 @@ use bip39::{Language, Mnemonic};
 use clap::{Parser, Subcommand};
 use rand::RngCore;
 use rand::rngs::OsRng;
+use zcash_address::unified::{self, Container, Encoding};
 use zcash_keys::encoding::AddressCodec;
-use zcash_keys::keys::{UnifiedAddressRequest, UnifiedSpendingKey};
+use zcash_keys::keys::{UnifiedAddressRequest, UnifiedFullViewingKey, UnifiedSpendingKey};
 use zcash_protocol::consensus::Network;
 use zcash_transparent::keys::IncomingViewingKey;
-use zip32::AccountId;
+use zip32::{AccountId, DiversifierIndex};
 
+mod commitment_tree;
 mod db;
+mod gains;
+mod price;
 mod rpc;
 mod scanner;
+mod sync;
+mod zip321;
 
 #[derive(Parser)]
 #[command(name = "zcash-wallet")]
@@ -45,6 +51,12 @@ enum Commands {
         /// RPC URL for Zcash node
         #[arg(long)]
         rpc_url: Option<String>,
+        /// lightwalletd gRPC endpoint, used by `sync`
+        #[arg(long)]
+        lightwalletd_url: Option<String>,
+        /// Default fiat currency for cost-basis tracking (e.g. "usd")
+        #[arg(long)]
+        currency: Option<String>,
         /// Database file path
         #[arg(long, default_value = "notes.db")]
         db: String,
@@ -66,6 +78,10 @@ enum Commands {
         /// Block height (optional, for better decryption)
         #[arg(long)]
         height: Option<u32>,
+        /// Fiat currency to record cost basis in. Defaults to the
+        /// configured currency, or "usd" if none is configured.
+        #[arg(long)]
+        currency: Option<String>,
     },
     /// Show balance from tracked notes
     Balance {
@@ -81,6 +97,106 @@ enum Commands {
         /// Show all notes including spent
         #[arg(long)]
         all: bool,
+        /// Only show notes that have a decoded memo
+        #[arg(long)]
+        with_memos: bool,
+    },
+    /// Generate a ZIP-321 payment request URI
+    Request {
+        /// Wallet file containing the address to request payment to
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Amount to request, in ZEC
+        #[arg(long)]
+        amount: Option<f64>,
+        /// Memo text to attach (shielded payments only)
+        #[arg(long)]
+        memo: Option<String>,
+        /// Human-readable label for the payment
+        #[arg(long)]
+        label: Option<String>,
+        /// Human-readable message for the payment
+        #[arg(long)]
+        message: Option<String>,
+    },
+    /// Parse a ZIP-321 payment request URI
+    ParseUri {
+        /// The `zcash:` URI to parse
+        uri: String,
+    },
+    /// Derive a batch of fresh diversified addresses from the wallet's UFVK
+    Address {
+        /// Wallet file containing the seed phrase
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Number of addresses to derive
+        #[arg(long, default_value = "1")]
+        count: u32,
+        /// Diversifier index to start from. Defaults to the next unissued index.
+        #[arg(long)]
+        start_index: Option<u128>,
+        /// Database file path, used to track the highest issued index
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+    },
+    /// NOT YET FUNCTIONAL: sync wallet notes from a lightwalletd endpoint.
+    /// This build has no gRPC transport wired in, so this command always
+    /// fails - use `scan` against individual transactions instead.
+    Sync {
+        /// Wallet file containing viewing key
+        #[arg(short, long, default_value = "wallet.json")]
+        wallet: String,
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+        /// Height to start syncing from. Defaults to the last synced height + 1.
+        #[arg(long)]
+        from_height: Option<u32>,
+        /// Height to sync to. Defaults to the current chain tip.
+        #[arg(long)]
+        to_height: Option<u32>,
+        /// lightwalletd gRPC endpoint (overrides the configured lightwalletd_url)
+        #[arg(long)]
+        lightwalletd_url: Option<String>,
+    },
+    /// Identify and describe an arbitrary piece of Zcash-related input
+    Inspect {
+        /// Mnemonic seed phrase, address, viewing key, or raw transaction hex
+        input: String,
+    },
+    /// Report realized capital gains/losses over spent notes
+    Gains {
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
+        /// Fiat currency to report gains in. Defaults to the configured
+        /// currency, or "usd" if none is configured.
+        #[arg(long)]
+        currency: Option<String>,
+        /// Report only these note ids (specific identification), in the
+        /// given order. Defaults to every spent note, FIFO.
+        #[arg(long, value_delimiter = ',')]
+        note_ids: Vec<i64>,
+        /// Write the report as CSV to this file instead of printing a summary
+        #[arg(long)]
+        csv: Option<String>,
+    },
+    /// Manually record a historical ZEC/fiat price, for cost-basis lookups
+    /// when no price oracle transport is wired in
+    SetPrice {
+        /// Block height the price applies to
+        #[arg(long)]
+        height: u32,
+        /// Price of 1 ZEC in the given currency
+        #[arg(long)]
+        price: f64,
+        /// Fiat currency the price is quoted in. Defaults to the configured
+        /// currency, or "usd" if none is configured.
+        #[arg(long)]
+        currency: Option<String>,
+        /// Database file path
+        #[arg(long, default_value = "notes.db")]
+        db: String,
     },
 }
 
@@ -91,16 +207,60 @@ fn main() -> Result<()> {
         Commands::Generate { output } => generate_wallet(&output),
         Commands::Restore { seed } => restore_wallet(&seed),
         Commands::Faucet => show_faucet_info(),
-        Commands::Config { rpc_url, db } => configure(&db, rpc_url),
+        Commands::Config {
+            rpc_url,
+            lightwalletd_url,
+            currency,
+            db,
+        } => configure(&db, rpc_url, lightwalletd_url, currency),
         Commands::Scan {
             txid,
             raw,
             wallet,
             db,
             height,
-        } => scan_transaction(&db, &wallet, txid, raw, height),
+            currency,
+        } => scan_transaction(&db, &wallet, txid, raw, height, currency),
         Commands::Balance { db } => show_balance(&db),
-        Commands::Notes { db, all } => list_notes(&db, all),
+        Commands::Notes {
+            db,
+            all,
+            with_memos,
+        } => list_notes(&db, all, with_memos),
+        Commands::Request {
+            wallet,
+            amount,
+            memo,
+            label,
+            message,
+        } => request_payment(&wallet, amount, memo, label, message),
+        Commands::ParseUri { uri } => parse_payment_uri(&uri),
+        Commands::Address {
+            wallet,
+            count,
+            start_index,
+            db,
+        } => derive_addresses(&wallet, count, start_index, &db),
+        Commands::Sync {
+            wallet,
+            db,
+            from_height,
+            to_height,
+            lightwalletd_url,
+        } => sync_wallet(&db, &wallet, from_height, to_height, lightwalletd_url),
+        Commands::Inspect { input } => inspect_input(&input),
+        Commands::Gains {
+            db,
+            currency,
+            note_ids,
+            csv,
+        } => report_gains(&db, currency, note_ids, csv),
+        Commands::SetPrice {
+            height,
+            price,
+            currency,
+            db,
+        } => set_price(&db, height, price, currency),
     }
 }
 
@@ -228,13 +388,26 @@ fn show_faucet_info() -> Result<()> {
     Ok(())
 }
 
-fn configure(db_path: &str, rpc_url: Option<String>) -> Result<()> {
+fn configure(
+    db_path: &str,
+    rpc_url: Option<String>,
+    lightwalletd_url: Option<String>,
+    currency: Option<String>,
+) -> Result<()> {
     let db = db::Database::open(db_path)?;
 
     if let Some(url) = rpc_url {
         db.set_config("rpc_url", &url)?;
         println!("RPC URL set to: {}", url);
     }
+    if let Some(url) = lightwalletd_url {
+        db.set_config("lightwalletd_url", &url)?;
+        println!("lightwalletd URL set to: {}", url);
+    }
+    if let Some(currency) = currency {
+        db.set_config("fiat_currency", &currency)?;
+        println!("Default fiat currency set to: {}", currency);
+    }
 
     // Show current config
     println!();
@@ -245,6 +418,16 @@ fn configure(db_path: &str, rpc_url: Option<String>) -> Result<()> {
     } else {
         println!("  RPC URL: (not configured)");
     }
+    if let Some(url) = db.get_config("lightwalletd_url")? {
+        println!("  lightwalletd URL: {}", url);
+    } else {
+        println!("  lightwalletd URL: (not configured)");
+    }
+    println!(
+        "  Default fiat currency: {}",
+        db.get_config("fiat_currency")?
+            .unwrap_or_else(|| "usd".to_string())
+    );
     println!();
 
     Ok(())
@@ -256,6 +439,7 @@ fn scan_transaction(
     txid: Option<String>,
     raw_hex: Option<String>,
     height: Option<u32>,
+    currency: Option<String>,
 ) -> Result<()> {
     // Load wallet to get viewing key
     let wallet_content = fs::read_to_string(wallet_path)
@@ -285,12 +469,20 @@ fn scan_transaction(
     // Parse and scan transaction
     let network = Network::TestNetwork;
     let tx = scanner::parse_transaction(&tx_hex, network)?;
-    let result = scanner::scan_transaction(&tx, viewing_key, network, height)?;
+    // No commitment-tree state is tracked yet for a single ad-hoc scan, so
+    // Sapling nullifiers can't be derived here; Orchard nullifiers don't
+    // need a position and are unaffected.
+    let leaf_positions = std::collections::HashMap::new();
+    let result = scanner::scan_transaction(&tx, viewing_key, network, height, &leaf_positions)?;
 
     // Open database
     let db = db::Database::open(db_path)?;
 
     // Store notes
+    let currency = currency
+        .or(db.get_config("fiat_currency")?)
+        .unwrap_or_else(|| "usd".to_string());
+    let price_oracle = price::PriceOracle::default();
     let mut notes_added = 0;
     for note in &result.notes {
         let inserted = db.insert_note(
@@ -303,10 +495,25 @@ fn scan_transaction(
             note.memo.as_deref(),
             note.address.as_deref(),
             height.map(|h| h as i64),
+            note.transfer_type.as_str(),
+            note.position.map(|p| p as i64),
         )?;
         if inserted {
             notes_added += 1;
         }
+
+        // Record the fiat cost basis for notes the wallet actually
+        // acquired (not outgoing payments to someone else), if a price is
+        // available for this block's approximate date.
+        if let (Some(h), scanner::TransferType::Incoming | scanner::TransferType::WalletInternal) =
+            (height, note.transfer_type)
+        {
+            if let Some(note_id) = db.find_note_id(&result.txid, note.output_index as i64, &note.pool)? {
+                if let Some(p) = price::get_or_fetch_price(&price_oracle, &db, h, &currency)? {
+                    db.record_cost_basis(note_id, p, &price::height_to_date(h))?;
+                }
+            }
+        }
     }
 
     // Check for spent nullifiers
@@ -342,12 +549,16 @@ fn scan_transaction(
         for note in &result.notes {
             println!();
             println!("  Pool: {}", note.pool);
+            println!("  Type: {}", note.transfer_type);
             println!("  Index: {}", note.output_index);
             println!("  Value: {} ZEC", format_zatoshi(note.value));
             println!(
                 "  Commitment: {}...",
                 &note.commitment[..16.min(note.commitment.len())]
             );
+            if let Some(ref memo) = note.memo {
+                println!("  Memo: {}", memo);
+            }
         }
         println!();
     }
@@ -377,6 +588,8 @@ fn show_balance(db_path: &str) -> Result<()> {
 
     let total_balance = db.get_balance()?;
     let balances_by_pool = db.get_balance_by_pool()?;
+    let outgoing_total = db.get_outgoing_total()?;
+    let income_total = db.get_income_total()?;
 
     println!();
     println!("============================================================");
@@ -394,17 +607,135 @@ fn show_balance(db_path: &str) -> Result<()> {
         println!();
     }
 
+    if outgoing_total > 0 {
+        println!("Sent to others: {} ZEC", format_zatoshi(outgoing_total as u64));
+        println!();
+    }
+
+    // Income excludes change the wallet sent back to itself, so moving
+    // funds between one's own pools never shows up as a taxable receipt.
+    println!(
+        "Income received (excludes change): {} ZEC",
+        format_zatoshi(income_total as u64)
+    );
+    println!();
+
+    Ok(())
+}
+
+fn report_gains(
+    db_path: &str,
+    currency: Option<String>,
+    note_ids: Vec<i64>,
+    csv_path: Option<String>,
+) -> Result<()> {
+    let db = db::Database::open(db_path)?;
+    let currency = currency
+        .or(db.get_config("fiat_currency")?)
+        .unwrap_or_else(|| "usd".to_string());
+    let oracle = price::PriceOracle::default();
+    let selection = if note_ids.is_empty() {
+        gains::LotSelection::Fifo
+    } else {
+        gains::LotSelection::Specific(&note_ids)
+    };
+    let disposals = gains::report(&db, &oracle, &currency, selection)?;
+
+    if let Some(path) = csv_path {
+        fs::write(&path, gains::to_csv(&disposals)).context("Failed to write gains CSV")?;
+        println!("Wrote {} disposal(s) to {}", disposals.len(), path);
+        return Ok(());
+    }
+
+    println!();
+    println!("============================================================");
+    println!("           REALIZED GAINS ({})", currency.to_uppercase());
+    println!("============================================================");
+    println!();
+
+    if disposals.is_empty() {
+        println!("No disposals found.");
+        println!();
+        return Ok(());
+    }
+
+    let mut total_gain = 0.0;
+    let mut total_known = 0;
+    for d in &disposals {
+        println!("------------------------------------------------------------");
+        println!("Note #{} [{}]", d.note_id, d.pool);
+        println!("------------------------------------------------------------");
+        println!("  Spent in: {}", d.spent_txid);
+        println!("  Value: {} ZEC", format_zatoshi(d.value_zatoshi as u64));
+        if let Some(ref date) = d.acquired_date {
+            println!("  Acquired: {}", date);
+        }
+        if let Some(ref date) = d.disposed_date {
+            println!("  Disposed: {}", date);
+        }
+        if let Some(term) = d.term {
+            let term = match term {
+                gains::HoldingTerm::ShortTerm => "short-term",
+                gains::HoldingTerm::LongTerm => "long-term",
+            };
+            println!("  Term: {}", term);
+        }
+        match d.gain {
+            Some(gain) => {
+                println!("  Gain/loss: {:.2} {}", gain, currency);
+                total_gain += gain;
+                total_known += 1;
+            }
+            None => println!("  Gain/loss: unknown (missing price or fee data)"),
+        }
+        println!();
+    }
+
+    println!("============================================================");
+    println!(
+        "Total realized gain/loss: {:.2} {} ({} of {} disposals priced)",
+        total_gain,
+        currency,
+        total_known,
+        disposals.len()
+    );
+    println!("============================================================");
+    println!();
+
     Ok(())
 }
 
-fn list_notes(db_path: &str, show_all: bool) -> Result<()> {
+/// Record a historical ZEC/`currency` price for `height` in the price
+/// cache, so `scan`/`gains` can compute cost basis without a live oracle
+/// transport (see `cli/src/price.rs`).
+fn set_price(db_path: &str, height: u32, price: f64, currency: Option<String>) -> Result<()> {
     let db = db::Database::open(db_path)?;
+    let currency = currency
+        .or(db.get_config("fiat_currency")?)
+        .unwrap_or_else(|| "usd".to_string());
+    db.cache_price(height as i64, &currency, price)?;
+
+    println!(
+        "Recorded price: 1 ZEC = {} {} at height {}",
+        price,
+        currency.to_uppercase(),
+        height
+    );
 
-    let notes = if show_all {
+    Ok(())
+}
+
+fn list_notes(db_path: &str, show_all: bool, with_memos: bool) -> Result<()> {
+    let db = db::Database::open(db_path)?;
+
+    let mut notes = if show_all {
         db.get_all_notes()?
     } else {
         db.get_unspent_notes()?
     };
+    if with_memos {
+        notes.retain(|n| n.memo.is_some());
+    }
 
     println!();
     println!("============================================================");
@@ -434,6 +765,7 @@ fn list_notes(db_path: &str, show_all: bool) -> Result<()> {
         println!("  Transaction: {}", note.txid);
         println!("  Output Index: {}", note.output_index);
         println!("  Pool: {}", note.pool);
+        println!("  Type: {}", note.transfer_type);
         println!("  Value: {} ZEC", format_zatoshi(note.value as u64));
         if let Some(ref commitment) = note.commitment {
             println!(
@@ -444,15 +776,21 @@ fn list_notes(db_path: &str, show_all: bool) -> Result<()> {
         if let Some(ref nullifier) = note.nullifier {
             println!("  Nullifier: {}...", &nullifier[..16.min(nullifier.len())]);
         }
+        if let Some(ref memo) = note.memo {
+            println!("  Memo: {}", memo);
+        }
         if let Some(ref spent_txid) = note.spent_txid {
             println!("  Spent in: {}", spent_txid);
         }
+        if let (Some(price), Some(ref date)) = (note.acquired_price, &note.acquired_date) {
+            println!("  Cost basis: {} on {}", price, date);
+        }
         println!();
     }
 
     let total: i64 = notes
         .iter()
-        .filter(|n| n.spent_txid.is_none())
+        .filter(|n| n.spent_txid.is_none() && n.transfer_type != "outgoing")
         .map(|n| n.value)
         .sum();
     println!("============================================================");
@@ -463,6 +801,308 @@ fn list_notes(db_path: &str, show_all: bool) -> Result<()> {
     Ok(())
 }
 
+fn sync_wallet(
+    db_path: &str,
+    wallet_path: &str,
+    from_height: Option<u32>,
+    to_height: Option<u32>,
+    lightwalletd_url: Option<String>,
+) -> Result<()> {
+    let wallet_content = fs::read_to_string(wallet_path)
+        .with_context(|| format!("Failed to read wallet file: {}", wallet_path))?;
+    let wallet_json: serde_json::Value =
+        serde_json::from_str(&wallet_content).context("Failed to parse wallet JSON")?;
+    let viewing_key = wallet_json["unified_full_viewing_key"]
+        .as_str()
+        .context("Wallet missing unified_full_viewing_key")?;
+
+    let db = db::Database::open(db_path)?;
+    let endpoint = match lightwalletd_url {
+        Some(url) => url,
+        None => db.get_config("lightwalletd_url")?.context(
+            "lightwalletd URL not configured. Run: zcash-wallet config --lightwalletd-url <url>",
+        )?,
+    };
+
+    let network = Network::TestNetwork;
+    let client = sync::LightwalletdClient::new(&endpoint);
+    println!("Syncing from {}...", endpoint);
+    let result = sync::sync(&client, &db, viewing_key, network, from_height, to_height)?;
+
+    println!();
+    println!("============================================================");
+    println!("           SYNC COMPLETE");
+    println!("============================================================");
+    println!();
+    println!("Blocks scanned: {}", result.blocks_scanned);
+    println!("New notes added: {}", result.notes_added);
+    println!("Synced to height: {}", result.synced_to_height);
+    println!();
+
+    Ok(())
+}
+
+fn request_payment(
+    wallet_path: &str,
+    amount: Option<f64>,
+    memo: Option<String>,
+    label: Option<String>,
+    message: Option<String>,
+) -> Result<()> {
+    let wallet_content = fs::read_to_string(wallet_path)
+        .with_context(|| format!("Failed to read wallet file: {}", wallet_path))?;
+    let wallet_json: serde_json::Value =
+        serde_json::from_str(&wallet_content).context("Failed to parse wallet JSON")?;
+    let address = wallet_json["unified_address"]
+        .as_str()
+        .context("Wallet missing unified_address")?;
+
+    let zatoshi = amount.map(|a| (a * 100_000_000.0).round() as u64);
+    let uri = zip321::build_uri(
+        address,
+        zatoshi,
+        memo.as_deref().map(str::as_bytes),
+        label.as_deref(),
+        message.as_deref(),
+    );
+
+    println!();
+    println!("============================================================");
+    println!("           PAYMENT REQUEST");
+    println!("============================================================");
+    println!();
+    println!("{}", uri);
+    println!();
+
+    Ok(())
+}
+
+fn parse_payment_uri(uri: &str) -> Result<()> {
+    let request = zip321::parse_uri(uri)?;
+
+    println!();
+    println!("============================================================");
+    println!("           PARSED PAYMENT REQUEST");
+    println!("============================================================");
+
+    for (i, payment) in request.payments.iter().enumerate() {
+        println!();
+        println!("------------------------------------------------------------");
+        println!("Payment #{}", i);
+        println!("------------------------------------------------------------");
+        println!("  Address: {}", payment.address);
+        if let Some(amount) = payment.amount {
+            println!("  Amount: {} ZEC", format_zatoshi(amount));
+        }
+        if let Some(ref label) = payment.label {
+            println!("  Label: {}", label);
+        }
+        if let Some(ref message) = payment.message {
+            println!("  Message: {}", message);
+        }
+        if let Some(ref memo) = payment.memo {
+            match std::str::from_utf8(memo) {
+                Ok(text) => println!("  Memo: {}", text),
+                Err(_) => println!("  Memo: {} bytes (binary)", memo.len()),
+            }
+        }
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Identify an arbitrary piece of Zcash-related input by trying each decoder
+/// in turn, similar to `zcash-inspect`: mnemonic, UFVK/UIVK, legacy Sapling
+/// viewing key, unified/Sapling/transparent address, then raw transaction hex.
+fn inspect_input(input: &str) -> Result<()> {
+    let input = input.trim();
+
+    println!();
+    println!("============================================================");
+    println!("           INSPECT");
+    println!("============================================================");
+    println!();
+
+    if let Ok(mnemonic) = Mnemonic::parse_in_normalized(Language::English, input) {
+        println!("Type: BIP-39 mnemonic seed phrase");
+        println!("  Word count: {}", mnemonic.word_count());
+        println!("  Entropy: {} bits", mnemonic.to_entropy().len() * 8);
+        println!();
+        return Ok(());
+    }
+
+    if let Ok((network, ufvk)) = unified::Ufvk::decode(input) {
+        println!("Type: Unified Full Viewing Key (UFVK)");
+        println!("  Network: {:?}", network);
+        for item in ufvk.items() {
+            describe_unified_fvk_item(&item);
+        }
+        println!();
+        return Ok(());
+    }
+
+    if let Ok((network, uivk)) = unified::Uivk::decode(input) {
+        println!("Type: Unified Incoming Viewing Key (UIVK)");
+        println!("  Network: {:?}", network);
+        for item in uivk.items() {
+            describe_unified_ivk_item(&item);
+        }
+        println!();
+        return Ok(());
+    }
+
+    if input.starts_with("zxview") {
+        println!("Type: Legacy Sapling extended full viewing key");
+        println!();
+        return Ok(());
+    }
+
+    if input.starts_with("u1") || input.starts_with("utest1") {
+        println!("Type: Unified Address");
+        println!();
+        return Ok(());
+    }
+
+    if input.starts_with("zs1") || input.starts_with("ztestsapling1") {
+        println!("Type: Sapling shielded address");
+        println!();
+        return Ok(());
+    }
+
+    if input.starts_with("t1")
+        || input.starts_with("t3")
+        || input.starts_with("tm")
+        || input.starts_with("t2")
+    {
+        println!("Type: Transparent address");
+        println!();
+        return Ok(());
+    }
+
+    for network in [Network::MainNetwork, Network::TestNetwork] {
+        if let Ok(tx) = scanner::parse_transaction(input, network) {
+            println!("Type: Raw transaction");
+            println!("  Txid: {}", tx.txid());
+            if let Some(bundle) = tx.transparent_bundle() {
+                println!("  Transparent inputs: {}", bundle.vin.len());
+                println!("  Transparent outputs: {}", bundle.vout.len());
+            }
+            if let Some(bundle) = tx.sapling_bundle() {
+                println!("  Sapling spends: {}", bundle.shielded_spends().len());
+                println!("  Sapling outputs: {}", bundle.shielded_outputs().len());
+            }
+            if let Some(bundle) = tx.orchard_bundle() {
+                println!("  Orchard actions: {}", bundle.actions().len());
+            }
+            println!();
+            return Ok(());
+        }
+    }
+
+    bail!("Could not identify input as a mnemonic, address, viewing key, or raw transaction")
+}
+
+fn describe_unified_fvk_item(item: &unified::Fvk) {
+    match item {
+        unified::Fvk::Sapling(_) => println!("  Contains: Sapling"),
+        unified::Fvk::Orchard(_) => println!("  Contains: Orchard"),
+        unified::Fvk::P2pkh(_) => println!("  Contains: Transparent (P2PKH)"),
+        _ => println!("  Contains: (unrecognized item)"),
+    }
+}
+
+fn describe_unified_ivk_item(item: &unified::Ivk) {
+    match item {
+        unified::Ivk::Sapling(_) => println!("  Contains: Sapling"),
+        unified::Ivk::Orchard(_) => println!("  Contains: Orchard"),
+        unified::Ivk::P2pkh(_) => println!("  Contains: Transparent (P2PKH)"),
+        _ => println!("  Contains: (unrecognized item)"),
+    }
+}
+
+fn derive_addresses(
+    wallet_path: &str,
+    count: u32,
+    start_index: Option<u128>,
+    db_path: &str,
+) -> Result<()> {
+    let wallet_content = fs::read_to_string(wallet_path)
+        .with_context(|| format!("Failed to read wallet file: {}", wallet_path))?;
+    let wallet_json: serde_json::Value =
+        serde_json::from_str(&wallet_content).context("Failed to parse wallet JSON")?;
+    let seed_phrase = wallet_json["seed_phrase"]
+        .as_str()
+        .context("Wallet missing seed_phrase")?;
+
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .context("Invalid seed phrase")?;
+    let seed = mnemonic.to_seed("");
+
+    let network = Network::TestNetwork;
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, AccountId::ZERO)
+        .map_err(|e| anyhow::anyhow!("Failed to derive spending key: {:?}", e))?;
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let db = db::Database::open(db_path)?;
+    let next_index = match start_index {
+        Some(idx) => idx,
+        None => match db.get_config("last_diversifier_index")? {
+            Some(s) => s.parse::<u128>().context("Corrupt last_diversifier_index")? + 1,
+            None => 0,
+        },
+    };
+
+    println!();
+    println!("============================================================");
+    println!("           DERIVED ADDRESSES");
+    println!("============================================================");
+    println!();
+
+    let mut issued = 0u32;
+    let mut index = next_index;
+    let mut highest_issued = next_index;
+
+    while issued < count {
+        let diversifier_index = DiversifierIndex::try_from(index)
+            .map_err(|_| anyhow::anyhow!("Diversifier index {} out of range", index))?;
+
+        if let Some((ua, _)) =
+            find_unified_address(&ufvk, diversifier_index, UnifiedAddressRequest::AllAvailableKeys)?
+        {
+            println!("[{}] {}", index, ua.encode(&network));
+            highest_issued = index;
+            issued += 1;
+        }
+
+        index = index
+            .checked_add(1)
+            .context("Diversifier index range exhausted")?;
+    }
+    println!();
+
+    db.set_config("last_diversifier_index", &highest_issued.to_string())?;
+
+    Ok(())
+}
+
+/// Look up a unified address at a specific diversifier index.
+///
+/// Returns `Ok(None)` if the index doesn't produce a valid diversifier for
+/// the requested receiver set, so the caller can skip ahead to the next one.
+fn find_unified_address(
+    ufvk: &UnifiedFullViewingKey,
+    diversifier_index: DiversifierIndex,
+    request: UnifiedAddressRequest,
+) -> Result<Option<(zcash_keys::address::UnifiedAddress, DiversifierIndex)>> {
+    // `find_address` searches forward from `diversifier_index`, so only
+    // accept its result when it landed on the requested index itself.
+    match ufvk.find_address(diversifier_index, request) {
+        Ok((ua, found)) if found == diversifier_index => Ok(Some((ua, found))),
+        _ => Ok(None),
+    }
+}
+
 /// Format zatoshi amount as ZEC with 8 decimal places.
 fn format_zatoshi(zatoshi: u64) -> String {
     let zec = zatoshi as f64 / 100_000_000.0;
@@ -1,9 +1,55 @@
 //! Transaction scanner for extracting notes and nullifiers.
 
 use anyhow::{Context, Result, bail};
-use zcash_address::unified::{self, Container, Encoding};
+use std::collections::HashMap;
+
+use orchard::keys::{
+    FullViewingKey as OrchardFvk, PreparedIncomingViewingKey as OrchardPreparedIvk,
+    Scope as OrchardScope,
+};
+use orchard::note_encryption::OrchardDomain;
+use sapling_crypto::NullifierDerivingKey;
+use sapling_crypto::note_encryption::{
+    PreparedIncomingViewingKey as SaplingPreparedIvk, try_sapling_note_decryption,
+};
+use zcash_keys::address::UnifiedAddress;
+use zcash_keys::encoding::AddressCodec;
+use zcash_keys::keys::{UnifiedFullViewingKey, UnifiedIncomingViewingKey};
+use zcash_note_encryption::try_note_decryption;
+use zcash_primitives::legacy::TransparentAddress;
 use zcash_primitives::transaction::Transaction;
-use zcash_protocol::consensus::{BranchId, Network};
+use zcash_protocol::consensus::{BlockHeight, BranchId, Network};
+use zip32::Scope as SaplingScope;
+
+/// How a note relates to this wallet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferType {
+    /// Decrypted with the incoming viewing key: funds received from elsewhere.
+    Incoming,
+    /// Decrypted with the internal-scope incoming viewing key, recovering
+    /// change the wallet sent back to itself.
+    WalletInternal,
+    /// Decrypted with the outgoing viewing key, recovering a payment the
+    /// wallet sent to someone else.
+    Outgoing,
+}
+
+impl TransferType {
+    /// Get the string representation of the transfer type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransferType::Incoming => "incoming",
+            TransferType::WalletInternal => "wallet_internal",
+            TransferType::Outgoing => "outgoing",
+        }
+    }
+}
+
+impl std::fmt::Display for TransferType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
 
 /// A note found in a transaction.
 #[derive(Debug, Clone)]
@@ -15,6 +61,12 @@ pub struct ScannedNote {
     pub nullifier: Option<String>,
     pub memo: Option<String>,
     pub address: Option<String>,
+    pub transfer_type: TransferType,
+    /// This note's leaf position in its pool's note commitment tree, needed
+    /// to derive its (future, outgoing) nullifier and to build a spending
+    /// witness. `None` until the caller supplies it - see
+    /// `scan_transaction`'s `leaf_positions` parameter.
+    pub position: Option<u64>,
 }
 
 /// Nullifiers found in a transaction (indicating spent notes).
@@ -32,6 +84,10 @@ pub struct ScanResult {
     pub spent_nullifiers: Vec<SpentNullifier>,
     pub transparent_received: u64,
     pub transparent_outputs: Vec<TransparentOutput>,
+    /// Whether the viewing key used for this scan is a full viewing key,
+    /// i.e. carries an outgoing viewing key capable of recovering change
+    /// and other self-created outputs.
+    pub has_outgoing_viewing_key: bool,
 }
 
 /// Transparent output info.
@@ -42,6 +98,47 @@ pub struct TransparentOutput {
     pub address: Option<String>,
 }
 
+/// Decode a ZIP-302 memo field: UTF-8 text if valid (with trailing zero
+/// padding trimmed), otherwise a hex dump of the raw bytes. An all-zero
+/// memo, or one starting with the 0xF6 "no memo" marker, is treated as
+/// blank and returns `None`.
+pub fn decode_memo(bytes: &[u8]) -> Option<String> {
+    if bytes.is_empty() || bytes[0] == 0xF6 || bytes.iter().all(|&b| b == 0) {
+        return None;
+    }
+
+    let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    let trimmed = &bytes[..end];
+
+    match std::str::from_utf8(trimmed) {
+        Ok(text) => Some(text.to_string()),
+        Err(_) => Some(hex::encode(trimmed)),
+    }
+}
+
+/// Recognize a standard P2PKH (`OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY
+/// OP_CHECKSIG`) or P2SH (`OP_HASH160 <20 bytes> OP_EQUAL`) scriptPubKey and
+/// return the address it pays to. Any other script form (multisig, bare
+/// pubkey, etc.) isn't recognized and returns `None`.
+fn decode_script_pubkey(script: &[u8]) -> Option<TransparentAddress> {
+    const OP_DUP: u8 = 0x76;
+    const OP_HASH160: u8 = 0xa9;
+    const OP_EQUALVERIFY: u8 = 0x88;
+    const OP_EQUAL: u8 = 0x87;
+    const OP_CHECKSIG: u8 = 0xac;
+    const PUSH_20: u8 = 0x14;
+
+    match script {
+        [OP_DUP, OP_HASH160, PUSH_20, hash @ .., OP_EQUALVERIFY, OP_CHECKSIG] if hash.len() == 20 => {
+            Some(TransparentAddress::PublicKeyHash(hash.try_into().ok()?))
+        }
+        [OP_HASH160, PUSH_20, hash @ .., OP_EQUAL] if hash.len() == 20 => {
+            Some(TransparentAddress::ScriptHash(hash.try_into().ok()?))
+        }
+        _ => None,
+    }
+}
+
 /// Parse a transaction from hex.
 pub fn parse_transaction(tx_hex: &str, network: Network) -> Result<Transaction> {
     let tx_bytes = hex::decode(tx_hex).context("Invalid transaction hex")?;
@@ -90,81 +187,177 @@ pub fn extract_nullifiers(tx: &Transaction) -> Vec<SpentNullifier> {
     nullifiers
 }
 
+/// Decryption key material extracted from a parsed viewing key, prepared
+/// for repeated trial decryption.
+///
+/// Each pool carries two scopes, matching ZIP 32's diversifiable key
+/// derivation: the external IVK recovers notes received from other wallets,
+/// while the internal IVK recovers change and other self-sends (it's only
+/// ever derivable from a full viewing key, since deriving it needs the OVK).
+/// `sapling_nk_*`/`orchard_fvk` carry the nullifier-deriving material for a
+/// decrypted note, needed once we know its commitment-tree position.
+struct ViewingKeys {
+    sapling_external: Option<SaplingPreparedIvk>,
+    sapling_internal: Option<SaplingPreparedIvk>,
+    sapling_nk_external: Option<NullifierDerivingKey>,
+    sapling_nk_internal: Option<NullifierDerivingKey>,
+    orchard_external: Option<OrchardPreparedIvk>,
+    orchard_internal: Option<OrchardPreparedIvk>,
+    orchard_fvk: Option<OrchardFvk>,
+    /// The transparent component's default (first) external address, used
+    /// to recognize which transparent outputs belong to this wallet. `None`
+    /// if the key carries no transparent component.
+    transparent_default_address: Option<TransparentAddress>,
+    has_ovk: bool,
+}
+
 /// Scan a transaction for notes belonging to a viewing key.
 ///
-/// Note: Full note decryption requires additional context (block height, etc.)
-/// For now, we extract what we can from the transaction structure.
+/// `leaf_positions` maps `(pool, output_index)` to that output's leaf
+/// position in its pool's note commitment tree, as maintained by the
+/// commitment-tree subsystem (see the `commitment_tree` module). Positions
+/// are needed to derive a *received* Sapling note's nullifier (Orchard's
+/// nullifier doesn't depend on tree position); pass an empty map if the
+/// caller hasn't built the tree up to this transaction yet; Sapling
+/// nullifiers for the unmapped outputs are simply left unset.
 pub fn scan_transaction(
     tx: &Transaction,
     viewing_key: &str,
     network: Network,
     height: Option<u32>,
+    leaf_positions: &HashMap<(String, usize), u64>,
 ) -> Result<ScanResult> {
     let txid = tx.txid().to_string();
     let mut notes = Vec::new();
     let mut transparent_received = 0u64;
     let mut transparent_outputs = Vec::new();
 
-    // Parse the viewing key
-    let (has_sapling, has_orchard, has_transparent) = parse_viewing_key_capabilities(viewing_key)?;
-
-    // Process transparent outputs
-    if has_transparent {
-        if let Some(transparent_bundle) = tx.transparent_bundle() {
-            for (i, output) in transparent_bundle.vout.iter().enumerate() {
-                let value = u64::from(output.value());
+    let keys = parse_viewing_key(viewing_key, network)?;
+
+    // Trial decryption needs a block height to pick the correct note
+    // plaintext version (pre/post-Canopy). When the caller doesn't know the
+    // height yet (e.g. scanning a just-broadcast transaction), assume the
+    // current consensus rules apply.
+    let decryption_height = BlockHeight::from_u32(height.unwrap_or(u32::MAX));
+
+    // Process transparent outputs. Every standard P2PKH/P2SH output is
+    // decoded and shown to the caller, but only ones paying the wallet's
+    // own transparent address count towards `transparent_received`.
+    if let Some(transparent_bundle) = tx.transparent_bundle() {
+        for (i, output) in transparent_bundle.vout.iter().enumerate() {
+            let value = u64::from(output.value());
+            let decoded = decode_script_pubkey(&output.script_pubkey().0);
+            if decoded.is_some() && decoded == keys.transparent_default_address {
                 transparent_received += value;
-                transparent_outputs.push(TransparentOutput {
-                    index: i,
-                    value,
-                    address: None, // TODO: decode address from script
-                });
             }
+            transparent_outputs.push(TransparentOutput {
+                index: i,
+                value,
+                address: decoded.map(|addr| addr.encode(&network)),
+            });
         }
     }
 
-    // Process Sapling outputs
-    if has_sapling {
-        if let Some(sapling_bundle) = tx.sapling_bundle() {
-            for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
-                // Extract commitment
-                let cmu = output.cmu();
-                let commitment = hex::encode(cmu.to_bytes());
+    // Process Sapling outputs.
+    //
+    // Try the external IVK first (funds received from elsewhere), then the
+    // internal IVK (change the wallet sent back to itself). Outgoing
+    // payments to someone else - recoverable only via the OVK, not an IVK -
+    // aren't attempted here; `has_outgoing_viewing_key` remains in place for
+    // when that lands. Outputs that decrypt under neither key aren't ours
+    // and are skipped, rather than stored with a zero value.
+    if let Some(sapling_bundle) = tx.sapling_bundle() {
+        for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
+            let cmu = output.cmu();
+            let commitment = hex::encode(cmu.to_bytes());
+            let position = leaf_positions.get(&("sapling".to_string(), i)).copied();
+
+            let decrypted = keys
+                .sapling_external
+                .as_ref()
+                .and_then(|ivk| {
+                    try_sapling_note_decryption(&network, decryption_height, ivk, output)
+                })
+                .map(|(note, address, memo)| {
+                    (note, address, memo, TransferType::Incoming, &keys.sapling_nk_external)
+                })
+                .or_else(|| {
+                    keys.sapling_internal.as_ref().and_then(|ivk| {
+                        try_sapling_note_decryption(&network, decryption_height, ivk, output).map(
+                            |(note, address, memo)| {
+                                (
+                                    note,
+                                    address,
+                                    memo,
+                                    TransferType::WalletInternal,
+                                    &keys.sapling_nk_internal,
+                                )
+                            },
+                        )
+                    })
+                });
 
-                // For Sapling, we need trial decryption to get the value
-                // This requires the full viewing key and block height
-                // For now, we record the output with unknown value
+            if let Some((note, address, memo, transfer_type, nk)) = decrypted {
+                let nullifier = match (nk, position) {
+                    (Some(nk), Some(pos)) => Some(hex::encode(note.nf(nk, pos).0)),
+                    _ => None,
+                };
                 notes.push(ScannedNote {
                     output_index: i,
                     pool: "sapling".to_string(),
-                    value: 0, // Would need trial decryption
+                    value: note.value().inner(),
                     commitment,
-                    nullifier: None, // Computed from note, not available without decryption
-                    memo: None,
-                    address: None,
+                    nullifier,
+                    memo: decode_memo(memo.as_array()),
+                    address: Some(address.encode(&network)),
+                    transfer_type,
+                    position,
                 });
             }
         }
     }
 
-    // Process Orchard actions
-    if has_orchard {
-        if let Some(orchard_bundle) = tx.orchard_bundle() {
-            for (i, action) in orchard_bundle.actions().iter().enumerate() {
-                // Extract commitment
-                let cmx = action.cmx();
-                let commitment = hex::encode(cmx.to_bytes());
+    // Process Orchard actions, same external-then-internal strategy as Sapling.
+    if let Some(orchard_bundle) = tx.orchard_bundle() {
+        for (i, action) in orchard_bundle.actions().iter().enumerate() {
+            let cmx = action.cmx();
+            let commitment = hex::encode(cmx.to_bytes());
+            let position = leaf_positions.get(&("orchard".to_string(), i)).copied();
+
+            let domain = OrchardDomain::for_action(action);
+            let decrypted = keys
+                .orchard_external
+                .as_ref()
+                .and_then(|ivk| try_note_decryption(&domain, ivk, action))
+                .map(|(note, address, memo)| (note, address, memo, TransferType::Incoming))
+                .or_else(|| {
+                    keys.orchard_internal.as_ref().and_then(|ivk| {
+                        try_note_decryption(&domain, ivk, action).map(|(note, address, memo)| {
+                            (note, address, memo, TransferType::WalletInternal)
+                        })
+                    })
+                });
 
-                // Orchard actions contain both inputs (nullifiers) and outputs
-                // The nullifier in the action is for the spent note, not the new note
+            if let Some((note, address, memo, transfer_type)) = decrypted {
+                // Unlike Sapling, an Orchard note's nullifier folds in its
+                // own rho value rather than a commitment-tree position, so
+                // it's derivable as soon as the note itself is known.
+                let nullifier = keys
+                    .orchard_fvk
+                    .as_ref()
+                    .map(|fvk| hex::encode(note.nullifier(fvk).to_bytes()));
+                let encoded_address = UnifiedAddress::from_receivers(Some(address), None, None)
+                    .map(|ua| ua.encode(&network));
                 notes.push(ScannedNote {
                     output_index: i,
                     pool: "orchard".to_string(),
-                    value: 0, // Would need trial decryption
+                    value: note.value().inner(),
                     commitment,
-                    nullifier: None, // The note's nullifier would be computed after decryption
-                    memo: None,
-                    address: None,
+                    nullifier,
+                    memo: decode_memo(&memo),
+                    address: encoded_address,
+                    transfer_type,
+                    position,
                 });
             }
         }
@@ -179,50 +372,84 @@ pub fn scan_transaction(
         spent_nullifiers,
         transparent_received,
         transparent_outputs,
+        has_outgoing_viewing_key: keys.has_ovk,
     })
 }
 
-/// Parse a viewing key and determine its capabilities.
-fn parse_viewing_key_capabilities(viewing_key: &str) -> Result<(bool, bool, bool)> {
-    // Try to decode as UFVK
-    if let Ok((_, ufvk)) = unified::Ufvk::decode(viewing_key) {
-        let mut has_sapling = false;
-        let mut has_orchard = false;
-        let mut has_transparent = false;
-
-        for item in ufvk.items() {
-            match item {
-                unified::Fvk::Sapling(_) => has_sapling = true,
-                unified::Fvk::Orchard(_) => has_orchard = true,
-                unified::Fvk::P2pkh(_) => has_transparent = true,
-                _ => {}
-            }
-        }
-
-        return Ok((has_sapling, has_orchard, has_transparent));
+/// Parse a viewing key and prepare its decryption key material.
+///
+/// `has_ovk` is set only for a UFVK, since a full viewing key is the only
+/// form that carries an outgoing viewing key; a UIVK can decrypt incoming
+/// notes but can never derive the internal (change) key or recover other
+/// self-created outputs.
+fn parse_viewing_key(viewing_key: &str, network: Network) -> Result<ViewingKeys> {
+    if let Ok(ufvk) = UnifiedFullViewingKey::decode(&network, viewing_key) {
+        let sapling_external = ufvk
+            .sapling()
+            .map(|dfvk| SaplingPreparedIvk::new(&dfvk.to_ivk(SaplingScope::External)));
+        let sapling_internal = ufvk
+            .sapling()
+            .map(|dfvk| SaplingPreparedIvk::new(&dfvk.to_ivk(SaplingScope::Internal)));
+        let sapling_nk_external = ufvk.sapling().map(|dfvk| dfvk.to_nk(SaplingScope::External));
+        let sapling_nk_internal = ufvk.sapling().map(|dfvk| dfvk.to_nk(SaplingScope::Internal));
+        let orchard_external = ufvk
+            .orchard()
+            .map(|fvk| OrchardPreparedIvk::new(&fvk.to_ivk(OrchardScope::External)));
+        let orchard_internal = ufvk
+            .orchard()
+            .map(|fvk| OrchardPreparedIvk::new(&fvk.to_ivk(OrchardScope::Internal)));
+
+        let transparent_default_address = ufvk
+            .transparent()
+            .map(|tfvk| {
+                tfvk.derive_external_ivk()
+                    .map(|ivk| ivk.default_address().0)
+            })
+            .transpose()
+            .map_err(|e| anyhow::anyhow!("Failed to derive transparent address: {e:?}"))?;
+
+        return Ok(ViewingKeys {
+            sapling_external,
+            sapling_internal,
+            sapling_nk_external,
+            sapling_nk_internal,
+            orchard_external,
+            orchard_internal,
+            orchard_fvk: ufvk.orchard().cloned(),
+            transparent_default_address,
+            has_ovk: true,
+        });
     }
 
-    // Try to decode as UIVK
-    if let Ok((_, uivk)) = unified::Uivk::decode(viewing_key) {
-        let mut has_sapling = false;
-        let mut has_orchard = false;
-        let mut has_transparent = false;
-
-        for item in uivk.items() {
-            match item {
-                unified::Ivk::Sapling(_) => has_sapling = true,
-                unified::Ivk::Orchard(_) => has_orchard = true,
-                unified::Ivk::P2pkh(_) => has_transparent = true,
-                _ => {}
-            }
-        }
-
-        return Ok((has_sapling, has_orchard, has_transparent));
+    if let Ok(uivk) = UnifiedIncomingViewingKey::decode(&network, viewing_key) {
+        let sapling_external = uivk.sapling().map(SaplingPreparedIvk::new);
+        let orchard_external = uivk.orchard().map(OrchardPreparedIvk::new);
+        // A UIVK's transparent component is already the external-scope IVK,
+        // with no further derivation needed (mirroring `sapling`/`orchard`
+        // above).
+        let transparent_default_address =
+            uivk.transparent().map(|ivk| ivk.default_address().0);
+
+        return Ok(ViewingKeys {
+            sapling_external,
+            sapling_internal: None,
+            sapling_nk_external: None,
+            sapling_nk_internal: None,
+            orchard_external,
+            orchard_internal: None,
+            // A UIVK carries no full viewing key, so Orchard nullifiers
+            // (like the Sapling internal scope and the OVK) are unavailable.
+            orchard_fvk: None,
+            transparent_default_address,
+            has_ovk: false,
+        });
     }
 
-    // Try legacy Sapling viewing key
     if viewing_key.starts_with("zxview") || viewing_key.starts_with("zxviews") {
-        return Ok((true, false, false));
+        bail!(
+            "Legacy Sapling viewing keys aren't supported for trial decryption; \
+             re-export a unified viewing key (UFVK/UIVK) instead."
+        );
     }
 
     bail!("Unrecognized viewing key format")
@@ -232,13 +459,72 @@ fn parse_viewing_key_capabilities(viewing_key: &str) -> Result<(bool, bool, bool
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_decode_memo_empty_is_blank() {
+        assert_eq!(decode_memo(&[0u8; 512]), None);
+        assert_eq!(decode_memo(&[]), None);
+
+        let mut no_memo = [0u8; 512];
+        no_memo[0] = 0xF6;
+        assert_eq!(decode_memo(&no_memo), None);
+    }
+
+    #[test]
+    fn test_decode_memo_utf8_text() {
+        let mut memo = [0u8; 512];
+        memo[..5].copy_from_slice(b"hello");
+        assert_eq!(decode_memo(&memo), Some("hello".to_string()));
+    }
+
+    #[test]
+    fn test_decode_memo_non_utf8_is_hex_dumped() {
+        let memo = [0xFFu8, 0xFE, 0x00, 0x01];
+        assert_eq!(decode_memo(&memo), Some("fffe0001".to_string()));
+    }
+
+    #[test]
+    fn test_decode_script_pubkey_p2pkh() {
+        let hash = [0x11u8; 20];
+        let mut script = vec![0x76, 0xa9, 0x14];
+        script.extend_from_slice(&hash);
+        script.extend_from_slice(&[0x88, 0xac]);
+
+        assert_eq!(
+            decode_script_pubkey(&script),
+            Some(TransparentAddress::PublicKeyHash(hash))
+        );
+    }
+
+    #[test]
+    fn test_decode_script_pubkey_p2sh() {
+        let hash = [0x22u8; 20];
+        let mut script = vec![0xa9, 0x14];
+        script.extend_from_slice(&hash);
+        script.push(0x87);
+
+        assert_eq!(
+            decode_script_pubkey(&script),
+            Some(TransparentAddress::ScriptHash(hash))
+        );
+    }
+
+    #[test]
+    fn test_decode_script_pubkey_unrecognized_form() {
+        // A bare multisig-style script isn't P2PKH or P2SH.
+        assert_eq!(decode_script_pubkey(&[0x51, 0x52, 0xae]), None);
+    }
+
     #[test]
     fn test_parse_viewing_key_capabilities() {
         // Test UFVK parsing
         let ufvk = "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd";
-        let (sapling, orchard, transparent) = parse_viewing_key_capabilities(ufvk).unwrap();
-        assert!(sapling);
-        assert!(orchard);
-        assert!(transparent);
+        let keys = parse_viewing_key(ufvk, Network::TestNetwork).unwrap();
+        assert!(keys.sapling_external.is_some());
+        assert!(keys.orchard_external.is_some());
+        assert!(keys.transparent_default_address.is_some());
+        assert!(keys.has_ovk);
+        // A full viewing key can also derive the internal (change) key.
+        assert!(keys.sapling_internal.is_some());
+        assert!(keys.orchard_internal.is_some());
     }
 }
@@ -0,0 +1,196 @@
+//! Sync wallet state from a lightwalletd `CompactTxStreamer` gRPC endpoint.
+//!
+//! Streams compact blocks over a height range and records how far it got.
+//! Compact Sapling/Orchard outputs are not trial-decrypted: no notes or
+//! nullifiers are stored and no commitment-tree state is built, so
+//! `SyncResult::notes_added` is always 0. The last synced height is tracked in
+//! `db`'s config table so a later `sync` resumes where the previous one left
+//! off, and the chain tip is fetched via `GetLatestBlock` when `to_height`
+//! isn't given.
+//!
+//! NOT YET FUNCTIONAL: this build has no gRPC transport or
+//! `CompactTxStreamer` protobuf bindings, so `LightwalletdClient` always
+//! errors and `sync` can't actually reach a lightwalletd endpoint. Scan
+//! individual transactions with `scan` in the meantime.
+
+use anyhow::{Context, Result, bail};
+use zcash_protocol::consensus::Network;
+
+use crate::db::Database;
+
+const CONFIG_KEY_LAST_SYNCED_HEIGHT: &str = "last_synced_height";
+
+/// A compact Sapling output, mirroring lightwalletd's `CompactSaplingOutput`.
+#[derive(Debug, Clone)]
+pub struct CompactSaplingOutput {
+    pub cmu: [u8; 32],
+    pub ephemeral_key: [u8; 32],
+    /// The first 52 bytes of the encrypted note ciphertext (enough to
+    /// trial-decrypt, per the compact block format).
+    pub enc_ciphertext: [u8; 52],
+}
+
+/// A compact Orchard action, mirroring lightwalletd's `CompactOrchardAction`.
+#[derive(Debug, Clone)]
+pub struct CompactOrchardAction {
+    pub nullifier: [u8; 32],
+    pub cmx: [u8; 32],
+    pub ephemeral_key: [u8; 32],
+    pub enc_ciphertext: [u8; 52],
+}
+
+/// A compact transaction within a compact block.
+#[derive(Debug, Clone, Default)]
+pub struct CompactTx {
+    pub index: u32,
+    pub txid: String,
+    pub sapling_outputs: Vec<CompactSaplingOutput>,
+    pub orchard_actions: Vec<CompactOrchardAction>,
+}
+
+/// A compact block, mirroring lightwalletd's `CompactBlock`.
+#[derive(Debug, Clone)]
+pub struct CompactBlock {
+    pub height: u32,
+    pub hash: String,
+    pub vtx: Vec<CompactTx>,
+}
+
+/// Summary of a completed sync.
+#[derive(Debug)]
+pub struct SyncResult {
+    pub blocks_scanned: u32,
+    pub notes_added: u32,
+    pub synced_to_height: u32,
+}
+
+/// A `CompactTxStreamer` gRPC endpoint (lightwalletd).
+pub struct LightwalletdClient {
+    endpoint: String,
+}
+
+impl LightwalletdClient {
+    /// Create a client for the given lightwalletd endpoint (e.g.
+    /// `https://testnet.lightwalletd.com:9067`).
+    pub fn new(endpoint: &str) -> Self {
+        LightwalletdClient {
+            endpoint: endpoint.to_string(),
+        }
+    }
+
+    /// Fetch the chain tip height via `GetLatestBlock`.
+    pub fn get_latest_height(&self) -> Result<u32> {
+        Err(self.transport_unavailable())
+    }
+
+    /// Stream compact blocks `[start, end]` via `GetBlockRange`.
+    pub fn get_block_range(&self, start: u32, end: u32) -> Result<Vec<CompactBlock>> {
+        let _ = (start, end);
+        Err(self.transport_unavailable())
+    }
+
+    /// Connecting to lightwalletd needs a gRPC transport and the
+    /// `CompactTxStreamer` protobuf bindings generated from its
+    /// `service.proto`/`compact_formats.proto`, neither of which is wired
+    /// into this build. `sync` is not usable until that transport lands;
+    /// see the module doc comment.
+    fn transport_unavailable(&self) -> anyhow::Error {
+        anyhow::anyhow!(
+            "Connecting to lightwalletd at {} requires a gRPC transport (tonic) and the \
+             CompactTxStreamer protobuf definitions, which are not wired into this build. \
+             `sync` is not usable yet - use `scan` against individual transactions instead.",
+            self.endpoint
+        )
+    }
+}
+
+/// Stream compact blocks over the requested range and advance the stored
+/// last-synced height. `viewing_key` is not used: no output is
+/// trial-decrypted and no note is stored.
+pub fn sync(
+    client: &LightwalletdClient,
+    db: &Database,
+    viewing_key: &str,
+    network: Network,
+    from_height: Option<u32>,
+    to_height: Option<u32>,
+) -> Result<SyncResult> {
+    let _ = (viewing_key, network);
+
+    let start = match from_height {
+        Some(h) => h,
+        None => db
+            .get_config(CONFIG_KEY_LAST_SYNCED_HEIGHT)?
+            .map(|s| s.parse::<u32>())
+            .transpose()
+            .context("Corrupt last_synced_height in config")?
+            .map(|h| h + 1)
+            .unwrap_or(0),
+    };
+    let end = match to_height {
+        Some(h) => h,
+        None => client.get_latest_height()?,
+    };
+    if start > end {
+        bail!(
+            "Nothing to sync: start height {} is past end height {}",
+            start,
+            end
+        );
+    }
+
+    let blocks = client.get_block_range(start, end)?;
+
+    let mut blocks_scanned = 0u32;
+    let mut notes_added = 0u32;
+    for block in &blocks {
+        for tx in &block.vtx {
+            // Compact outputs are not trial-decrypted here (unlike the full
+            // transactions `scanner::scan_transaction` decrypts), and no
+            // commitment is appended to a `commitment_tree::TreeTracker`.
+            let _ = tx;
+        }
+        db.set_config(CONFIG_KEY_LAST_SYNCED_HEIGHT, &block.height.to_string())?;
+        blocks_scanned += 1;
+    }
+
+    Ok(SyncResult {
+        blocks_scanned,
+        notes_added,
+        synced_to_height: end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resumes_from_last_synced_height() {
+        let db = Database::open_in_memory().unwrap();
+        db.set_config(CONFIG_KEY_LAST_SYNCED_HEIGHT, "999").unwrap();
+
+        let client = LightwalletdClient::new("https://example.invalid:9067");
+        // The transport isn't wired in yet, but we can still confirm the
+        // resume-height arithmetic runs before the client call fails.
+        let err = sync(&client, &db, "uviewtest", Network::TestNetwork, None, Some(999))
+            .unwrap_err();
+        assert!(err.to_string().contains("Nothing to sync"));
+    }
+
+    #[test]
+    fn test_start_past_end_is_rejected() {
+        let db = Database::open_in_memory().unwrap();
+        let client = LightwalletdClient::new("https://example.invalid:9067");
+        let err = sync(
+            &client,
+            &db,
+            "uviewtest",
+            Network::TestNetwork,
+            Some(500),
+            Some(100),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Nothing to sync"));
+    }
+}
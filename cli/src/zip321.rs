@@ -0,0 +1,493 @@
+//! ZIP-321 payment request URIs.
+//!
+//! Builds and parses `zcash:` URIs as defined by
+//! <https://zips.z.cash/zip-0321>. Supports a single payment as well as
+//! multiple payments via indexed parameters (`address.1`, `amount.1`, ...).
+
+use anyhow::{Context, Result, bail};
+use qr::{ErrorCorrectionLevel, QrCode};
+
+/// A single payment within a ZIP-321 request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Payment {
+    /// Recipient address (transparent, Sapling, or unified).
+    pub address: String,
+    /// Amount in zatoshis, if specified.
+    pub amount: Option<u64>,
+    /// Decoded memo bytes, if specified.
+    pub memo: Option<Vec<u8>>,
+    /// Percent-decoded human-readable label, if specified.
+    pub label: Option<String>,
+    /// Percent-decoded human-readable message, if specified.
+    pub message: Option<String>,
+}
+
+impl Payment {
+    /// Start building a payment to `address`.
+    pub fn new(address: impl Into<String>) -> Self {
+        Payment {
+            address: address.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the amount, in zatoshis.
+    pub fn amount(mut self, zatoshi: u64) -> Self {
+        self.amount = Some(zatoshi);
+        self
+    }
+
+    /// Set the memo bytes.
+    pub fn memo(mut self, memo: impl Into<Vec<u8>>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Set the human-readable label.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Set the human-readable message.
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+/// A parsed, or builder-assembled, ZIP-321 payment request of one or more
+/// payments.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub payments: Vec<Payment>,
+}
+
+impl PaymentRequest {
+    /// Start building an empty payment request.
+    pub fn new() -> Self {
+        PaymentRequest::default()
+    }
+
+    /// Append a payment to the request.
+    pub fn add_payment(mut self, payment: Payment) -> Self {
+        self.payments.push(payment);
+        self
+    }
+
+    /// Render this request as a ZIP-321 `zcash:` URI.
+    ///
+    /// A single payment is rendered with unindexed parameters, matching
+    /// [`build_uri`]; two or more payments use indexed parameters
+    /// (`address.1`, `amount.1`, ...) for every payment after the first.
+    pub fn to_uri(&self) -> Result<String> {
+        if self.payments.is_empty() {
+            bail!("Payment request must contain at least one payment");
+        }
+        if self.payments.len() == 1 {
+            let p = &self.payments[0];
+            return Ok(build_uri(
+                &p.address,
+                p.amount,
+                p.memo.as_deref(),
+                p.label.as_deref(),
+                p.message.as_deref(),
+            ));
+        }
+
+        let first = &self.payments[0];
+        let mut uri = format!("zcash:{}", first.address);
+        let mut params = payment_params(first, 0);
+
+        for (i, payment) in self.payments.iter().enumerate().skip(1) {
+            params.extend(payment_params(payment, i as u32));
+        }
+
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+        Ok(uri)
+    }
+
+    /// Render this request as a ZIP-321 URI and encode it as a QR code.
+    pub fn to_qr_code(&self, ecl: ErrorCorrectionLevel) -> Result<QrCode> {
+        let uri = self.to_uri()?;
+        QrCode::encode(&uri, ecl).map_err(anyhow::Error::msg)
+    }
+}
+
+/// Build the indexed query parameters for one payment within a multi-payment
+/// request; `index` is the slot's position (0 for the unindexed leading
+/// payment).
+fn payment_params(payment: &Payment, index: u32) -> Vec<String> {
+    let suffix = if index == 0 {
+        String::new()
+    } else {
+        format!(".{}", index)
+    };
+
+    let mut params = Vec::new();
+    if index != 0 {
+        params.push(format!("address{}={}", suffix, percent_encode(&payment.address)));
+    }
+    if let Some(amount) = payment.amount {
+        params.push(format!("amount{}={}", suffix, format_zec_amount(amount)));
+    }
+    if let Some(memo) = &payment.memo {
+        params.push(format!("memo{}={}", suffix, base64url_encode(memo)));
+    }
+    if let Some(label) = &payment.label {
+        params.push(format!("label{}={}", suffix, percent_encode(label)));
+    }
+    if let Some(message) = &payment.message {
+        params.push(format!("message{}={}", suffix, percent_encode(message)));
+    }
+    params
+}
+
+/// Build a ZIP-321 URI for a single payment.
+///
+/// `amount` is in zatoshis; it is rendered as decimal ZEC with up to 8
+/// fractional digits, trailing zeros trimmed.
+pub fn build_uri(
+    address: &str,
+    amount: Option<u64>,
+    memo: Option<&[u8]>,
+    label: Option<&str>,
+    message: Option<&str>,
+) -> String {
+    let mut uri = format!("zcash:{}", address);
+    let mut params: Vec<String> = Vec::new();
+
+    if let Some(amount) = amount {
+        params.push(format!("amount={}", format_zec_amount(amount)));
+    }
+    if let Some(memo) = memo {
+        params.push(format!("memo={}", base64url_encode(memo)));
+    }
+    if let Some(label) = label {
+        params.push(format!("label={}", percent_encode(label)));
+    }
+    if let Some(message) = message {
+        params.push(format!("message={}", percent_encode(message)));
+    }
+
+    if !params.is_empty() {
+        uri.push('?');
+        uri.push_str(&params.join("&"));
+    }
+
+    uri
+}
+
+/// Parse a ZIP-321 `zcash:` URI into its constituent payments.
+pub fn parse_uri(uri: &str) -> Result<PaymentRequest> {
+    let rest = uri
+        .strip_prefix("zcash:")
+        .context("URI must start with \"zcash:\"")?;
+
+    let (leading_addr, query) = match rest.split_once('?') {
+        Some((addr, query)) => (addr, query),
+        None => (rest, ""),
+    };
+
+    // Collect indexed payment slots. Index 0 is the leading (unindexed)
+    // address and any unindexed params.
+    let mut slots: std::collections::BTreeMap<u32, Payment> = std::collections::BTreeMap::new();
+
+    if !leading_addr.is_empty() {
+        slots.entry(0).or_default().address = percent_decode(leading_addr)?;
+    }
+
+    if !query.is_empty() {
+        for pair in query.split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair
+                .split_once('=')
+                .with_context(|| format!("Malformed query parameter: {}", pair))?;
+            let (base, index) = split_param_index(key)?;
+            let slot = slots.entry(index).or_default();
+
+            match base {
+                "address" => slot.address = percent_decode(value)?,
+                "amount" => slot.amount = Some(parse_zec_amount(value)?),
+                "memo" => slot.memo = Some(base64url_decode(value)?),
+                "label" => slot.label = Some(percent_decode(value)?),
+                "message" => slot.message = Some(percent_decode(value)?),
+                // Unknown non-"req-" params must be ignored per ZIP-321;
+                // unknown "req-" params would need to be rejected, but we
+                // don't yet implement the required-param mechanism.
+                _ => {}
+            }
+        }
+    }
+
+    let mut payments: Vec<Payment> = Vec::with_capacity(slots.len());
+    for (index, payment) in slots {
+        if payment.address.is_empty() {
+            bail!("Payment index {} is missing a paired address", index);
+        }
+        payments.push(payment);
+    }
+
+    if payments.is_empty() {
+        bail!("URI contains no payments");
+    }
+
+    Ok(PaymentRequest { payments })
+}
+
+/// Split `"amount.1"` into `("amount", 1)`, or `"amount"` into `("amount", 0)`.
+fn split_param_index(key: &str) -> Result<(&str, u32)> {
+    match key.split_once('.') {
+        Some((base, idx_str)) => {
+            let index: u32 = idx_str
+                .parse()
+                .with_context(|| format!("Invalid parameter index: {}", key))?;
+            Ok((base, index))
+        }
+        None => Ok((key, 0)),
+    }
+}
+
+/// Format a zatoshi amount as decimal ZEC with trailing zeros trimmed.
+fn format_zec_amount(zatoshi: u64) -> String {
+    let zec = zatoshi as f64 / 100_000_000.0;
+    let s = format!("{:.8}", zec);
+    let trimmed = s.trim_end_matches('0');
+    let trimmed = trimmed.trim_end_matches('.');
+    if trimmed.is_empty() { "0".to_string() } else { trimmed.to_string() }
+}
+
+/// Parse a decimal ZEC amount string into zatoshis.
+fn parse_zec_amount(s: &str) -> Result<u64> {
+    let value: f64 = s
+        .parse()
+        .with_context(|| format!("Invalid amount: {}", s))?;
+    if value < 0.0 {
+        bail!("Amount must not be negative: {}", s);
+    }
+    Ok((value * 100_000_000.0).round() as u64)
+}
+
+/// Percent-decode a URI component.
+fn percent_decode(s: &str) -> Result<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = s
+                    .get(i + 1..i + 3)
+                    .context("Truncated percent-encoding sequence")?;
+                let byte = u8::from_str_radix(hex, 16)
+                    .with_context(|| format!("Invalid percent-encoding: %{}", hex))?;
+                out.push(byte);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).context("Percent-decoded value is not valid UTF-8")
+}
+
+/// Percent-encode a string for use in a URI query component.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char);
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+const BASE64URL_ALPHABET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode bytes as unpadded base64url, per ZIP-321's memo encoding.
+fn base64url_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied();
+        let b2 = chunk.get(2).copied();
+
+        out.push(BASE64URL_ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(
+            BASE64URL_ALPHABET[(((b0 & 0x03) << 4) | (b1.unwrap_or(0) >> 4)) as usize] as char,
+        );
+        if let Some(b1) = b1 {
+            out.push(
+                BASE64URL_ALPHABET[(((b1 & 0x0F) << 2) | (b2.unwrap_or(0) >> 6)) as usize] as char,
+            );
+        }
+        if let Some(b2) = b2 {
+            out.push(BASE64URL_ALPHABET[(b2 & 0x3F) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Decode unpadded base64url bytes.
+fn base64url_decode(s: &str) -> Result<Vec<u8>> {
+    fn value(c: u8) -> Result<u8> {
+        match c {
+            b'A'..=b'Z' => Ok(c - b'A'),
+            b'a'..=b'z' => Ok(c - b'a' + 26),
+            b'0'..=b'9' => Ok(c - b'0' + 52),
+            b'-' => Ok(62),
+            b'_' => Ok(63),
+            _ => bail!("Invalid base64url character: {}", c as char),
+        }
+    }
+
+    let chars: Vec<u8> = s.bytes().collect();
+    let mut out = Vec::with_capacity(chars.len() * 3 / 4);
+
+    for chunk in chars.chunks(4) {
+        let v0 = value(chunk[0])?;
+        let v1 = value(*chunk.get(1).context("Truncated base64url memo")?)?;
+        out.push((v0 << 2) | (v1 >> 4));
+
+        if let Some(&c2) = chunk.get(2) {
+            let v2 = value(c2)?;
+            out.push(((v1 & 0x0F) << 4) | (v2 >> 2));
+
+            if let Some(&c3) = chunk.get(3) {
+                let v3 = value(c3)?;
+                out.push(((v2 & 0x03) << 6) | v3);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_uri_minimal() {
+        let uri = build_uri("tmXXXYYYZZZ", None, None, None, None);
+        assert_eq!(uri, "zcash:tmXXXYYYZZZ");
+    }
+
+    #[test]
+    fn test_build_uri_with_amount() {
+        let uri = build_uri("tmXXXYYYZZZ", Some(100_000_000), None, None, None);
+        assert_eq!(uri, "zcash:tmXXXYYYZZZ?amount=1");
+    }
+
+    #[test]
+    fn test_build_and_parse_roundtrip() {
+        let uri = build_uri(
+            "tmXXXYYYZZZ",
+            Some(123_456_789),
+            Some(b"hello memo"),
+            Some("Coffee"),
+            Some("Thanks!"),
+        );
+        let parsed = parse_uri(&uri).unwrap();
+        assert_eq!(parsed.payments.len(), 1);
+        let payment = &parsed.payments[0];
+        assert_eq!(payment.address, "tmXXXYYYZZZ");
+        assert_eq!(payment.amount, Some(123_456_789));
+        assert_eq!(payment.memo.as_deref(), Some(b"hello memo".as_slice()));
+        assert_eq!(payment.label.as_deref(), Some("Coffee"));
+        assert_eq!(payment.message.as_deref(), Some("Thanks!"));
+    }
+
+    #[test]
+    fn test_parse_multiple_payments() {
+        let uri = "zcash:addr0?amount=1&address.1=addr1&amount.1=2.5";
+        let parsed = parse_uri(uri).unwrap();
+        assert_eq!(parsed.payments.len(), 2);
+        assert_eq!(parsed.payments[0].address, "addr0");
+        assert_eq!(parsed.payments[0].amount, Some(100_000_000));
+        assert_eq!(parsed.payments[1].address, "addr1");
+        assert_eq!(parsed.payments[1].amount, Some(250_000_000));
+    }
+
+    #[test]
+    fn test_parse_missing_paired_address_fails() {
+        let uri = "zcash:addr0?amount.1=2.5";
+        assert!(parse_uri(uri).is_err());
+    }
+
+    #[test]
+    fn test_parse_requires_zcash_scheme() {
+        assert!(parse_uri("bitcoin:abc").is_err());
+    }
+
+    #[test]
+    fn test_percent_encode_decode_roundtrip() {
+        let label = "Coffee & Tea";
+        let encoded = percent_encode(label);
+        let decoded = percent_decode(&encoded).unwrap();
+        assert_eq!(decoded, label);
+    }
+
+    #[test]
+    fn test_base64url_roundtrip() {
+        let data = b"some arbitrary memo bytes!";
+        let encoded = base64url_encode(data);
+        let decoded = base64url_decode(&encoded).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_payment_request_builder_single_payment_matches_build_uri() {
+        let request = PaymentRequest::new().add_payment(
+            Payment::new("tmXXXYYYZZZ")
+                .amount(123_456_789)
+                .label("Coffee")
+                .message("Thanks!"),
+        );
+        let uri = request.to_uri().unwrap();
+        assert_eq!(
+            uri,
+            build_uri("tmXXXYYYZZZ", Some(123_456_789), None, Some("Coffee"), Some("Thanks!"))
+        );
+    }
+
+    #[test]
+    fn test_payment_request_builder_multi_recipient_roundtrip() {
+        let request = PaymentRequest::new()
+            .add_payment(Payment::new("addr0").amount(100_000_000))
+            .add_payment(Payment::new("addr1").amount(250_000_000).label("Tax"));
+        let uri = request.to_uri().unwrap();
+        let parsed = parse_uri(&uri).unwrap();
+        assert_eq!(parsed.payments.len(), 2);
+        assert_eq!(parsed.payments[0].address, "addr0");
+        assert_eq!(parsed.payments[0].amount, Some(100_000_000));
+        assert_eq!(parsed.payments[1].address, "addr1");
+        assert_eq!(parsed.payments[1].amount, Some(250_000_000));
+        assert_eq!(parsed.payments[1].label.as_deref(), Some("Tax"));
+    }
+
+    #[test]
+    fn test_payment_request_requires_at_least_one_payment() {
+        assert!(PaymentRequest::new().to_uri().is_err());
+    }
+
+    #[test]
+    fn test_payment_request_to_qr_code_encodes_uri() {
+        let request = PaymentRequest::new().add_payment(Payment::new("tmXXXYYYZZZ").amount(100_000_000));
+        let qr = request.to_qr_code(ErrorCorrectionLevel::M).unwrap();
+        assert!(qr.to_unicode(2).contains('█'));
+    }
+}
@@ -0,0 +1,329 @@
+//! Automatic UTXO selection for transparent transactions.
+//!
+//! [`select_utxos`] picks which `Utxo`s to spend for a given target amount,
+//! preferring a changeless selection (found via a bounded Branch-and-Bound
+//! search) and falling back to a largest-first accumulator when no such
+//! selection exists. See [`select_utxos`] for the exact fee model.
+
+use crate::transaction::Utxo;
+
+/// Marginal fee cost, in zatoshis, of adding one more input or output to a
+/// transaction - used to compute both the required fee and the
+/// "cost of change" threshold for the Branch-and-Bound search.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeWeight {
+    /// Fee cost of adding one transparent input.
+    pub per_input: u64,
+    /// Fee cost of adding one transparent output.
+    pub per_output: u64,
+}
+
+/// The result of a successful coin selection.
+#[derive(Debug, Clone)]
+pub struct CoinSelection {
+    /// The UTXOs chosen to cover `target` plus fees.
+    pub selected: Vec<Utxo>,
+    /// The change amount to return to the wallet, in zatoshis. `0` if the
+    /// selection is changeless or the leftover fell below the dust
+    /// threshold and was absorbed into the fee.
+    pub change: u64,
+    /// The total fee paid, in zatoshis, including any dust absorbed from
+    /// leftover change.
+    pub fee: u64,
+}
+
+/// Errors that can occur during coin selection.
+#[derive(Debug)]
+pub enum CoinSelectionError {
+    /// The available UTXOs cannot cover the target amount plus fees.
+    InsufficientFunds { available: u64, required: u64 },
+}
+
+impl core::fmt::Display for CoinSelectionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InsufficientFunds { available, required } => write!(
+                f,
+                "Insufficient funds: available {} zatoshis, required {} zatoshis",
+                available, required
+            ),
+        }
+    }
+}
+
+impl core::error::Error for CoinSelectionError {}
+
+/// Number of candidate combinations the Branch-and-Bound search will
+/// explore before giving up and falling back to largest-first.
+const BNB_MAX_TRIES: u32 = 100_000;
+
+/// Select UTXOs to cover `target` zatoshis sent across `num_outputs`
+/// recipient outputs, plus the fee implied by `fee_weight` and
+/// `base_fee`.
+///
+/// The required fee for a selection of `n` inputs is
+/// `base_fee + n * fee_weight.per_input + num_outputs * fee_weight.per_output`,
+/// plus one more `fee_weight.per_output` if a change output ends up being
+/// created. A selection is "changeless" when the selected inputs sum to
+/// somewhere in `[target + fee_without_change, target + fee_without_change
+/// + cost_of_change]`, where `cost_of_change = fee_weight.per_input +
+/// fee_weight.per_output` is the fee it would eventually cost to create
+/// and later spend a change output - spending the surplus on-chain as fee
+/// is preferred over creating a change output that costs at least as much
+/// to clean up later.
+///
+/// Leftover change below `dust_threshold` is folded into the fee rather
+/// than creating an uneconomical change output.
+pub fn select_utxos(
+    utxos: &[Utxo],
+    target: u64,
+    num_outputs: u32,
+    fee_weight: FeeWeight,
+    base_fee: u64,
+    dust_threshold: u64,
+) -> Result<CoinSelection, CoinSelectionError> {
+    let fee_without_change = base_fee
+        + u64::from(num_outputs) * fee_weight.per_output;
+    let cost_of_change = fee_weight.per_input + fee_weight.per_output;
+
+    let mut candidates: Vec<&Utxo> = utxos.iter().collect();
+    candidates.sort_by(|a, b| effective_value(b, fee_weight).cmp(&effective_value(a, fee_weight)));
+
+    if let Some(indices) = branch_and_bound(
+        &candidates,
+        target + fee_without_change,
+        cost_of_change,
+        fee_weight.per_input,
+    ) {
+        let selected: Vec<Utxo> = indices.iter().map(|&i| candidates[i].clone()).collect();
+        let total_in: u64 = selected.iter().map(|u| u.value).sum();
+        let n = selected.len() as u64;
+        let fee = fee_without_change + n * fee_weight.per_input;
+        return Ok(CoinSelection {
+            selected,
+            change: 0,
+            fee: fee + (total_in - (target + fee)),
+        });
+    }
+
+    largest_first(&candidates, target, num_outputs, fee_weight, base_fee, dust_threshold)
+}
+
+fn effective_value(utxo: &Utxo, fee_weight: FeeWeight) -> i64 {
+    utxo.value as i64 - fee_weight.per_input as i64
+}
+
+/// Depth-first Branch-and-Bound search for a changeless selection.
+///
+/// Explores, for each candidate in turn, the branch where it's included
+/// and the branch where it's excluded, pruning as soon as the running
+/// total (in effective value) can no longer land in
+/// `[required, required + cost_of_change]`. Candidates are assumed
+/// pre-sorted by descending effective value, which lets an all-remaining
+/// upper bound be computed cheaply for pruning.
+fn branch_and_bound(
+    candidates: &[&Utxo],
+    required: u64,
+    cost_of_change: u64,
+    per_input: u64,
+) -> Option<Vec<usize>> {
+    let effective: Vec<i64> = candidates
+        .iter()
+        .map(|u| u.value as i64 - per_input as i64)
+        .collect();
+    // Suffix sums of remaining effective value, for an upper-bound check.
+    let mut suffix_sum = vec![0i64; effective.len() + 1];
+    for i in (0..effective.len()).rev() {
+        suffix_sum[i] = suffix_sum[i + 1] + effective[i].max(0);
+    }
+
+    let required = required as i64;
+    let upper = required + cost_of_change as i64;
+
+    let mut tries = 0u32;
+    let mut selection = Vec::new();
+    let mut best: Option<Vec<usize>> = None;
+
+    fn search(
+        effective: &[i64],
+        suffix_sum: &[i64],
+        index: usize,
+        running: i64,
+        required: i64,
+        upper: i64,
+        tries: &mut u32,
+        selection: &mut Vec<usize>,
+        best: &mut Option<Vec<usize>>,
+    ) {
+        if *tries >= BNB_MAX_TRIES || best.is_some() {
+            return;
+        }
+        *tries += 1;
+
+        if running >= required && running <= upper {
+            *best = Some(selection.clone());
+            return;
+        }
+        if index == effective.len() || running + suffix_sum[index] < required {
+            return;
+        }
+        if running > upper {
+            return;
+        }
+
+        // Include candidate `index`.
+        selection.push(index);
+        search(
+            effective,
+            suffix_sum,
+            index + 1,
+            running + effective[index],
+            required,
+            upper,
+            tries,
+            selection,
+            best,
+        );
+        selection.pop();
+        if best.is_some() {
+            return;
+        }
+
+        // Exclude candidate `index`.
+        search(
+            effective,
+            suffix_sum,
+            index + 1,
+            running,
+            required,
+            upper,
+            tries,
+            selection,
+            best,
+        );
+    }
+
+    search(
+        &effective,
+        &suffix_sum,
+        0,
+        0,
+        required,
+        upper,
+        &mut tries,
+        &mut selection,
+        &mut best,
+    );
+    best
+}
+
+/// Fallback selection: accumulate UTXOs largest-first until the target
+/// plus the fee for the inputs selected so far is covered.
+fn largest_first(
+    candidates: &[&Utxo],
+    target: u64,
+    num_outputs: u32,
+    fee_weight: FeeWeight,
+    base_fee: u64,
+    dust_threshold: u64,
+) -> Result<CoinSelection, CoinSelectionError> {
+    let mut selected = Vec::new();
+    let mut total_in = 0u64;
+
+    for utxo in candidates {
+        selected.push((*utxo).clone());
+        total_in += utxo.value;
+
+        let n = selected.len() as u64;
+        let fee = base_fee + n * fee_weight.per_input + u64::from(num_outputs) * fee_weight.per_output;
+        let required = target + fee;
+        if total_in >= required {
+            let leftover = total_in - required;
+            if leftover <= dust_threshold {
+                return Ok(CoinSelection {
+                    selected,
+                    change: 0,
+                    fee: fee + leftover,
+                });
+            }
+            // A change output pays one more `per_output` fee.
+            let fee_with_change = fee + fee_weight.per_output;
+            if total_in < target + fee_with_change {
+                // Adding the change output's own fee tips us back under
+                // the requirement; keep accumulating more inputs.
+                continue;
+            }
+            return Ok(CoinSelection {
+                selected,
+                change: total_in - target - fee_with_change,
+                fee: fee_with_change,
+            });
+        }
+    }
+
+    let available: u64 = candidates.iter().map(|u| u.value).sum();
+    Err(CoinSelectionError::InsufficientFunds {
+        available,
+        required: target
+            + base_fee
+            + (candidates.len() as u64) * fee_weight.per_input
+            + u64::from(num_outputs) * fee_weight.per_output,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utxo(value: u64) -> Utxo {
+        Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value,
+            address: "tmTest".to_string(),
+            script_pubkey: None,
+        }
+    }
+
+    const WEIGHT: FeeWeight = FeeWeight {
+        per_input: 1000,
+        per_output: 500,
+    };
+
+    #[test]
+    fn test_changeless_selection_is_preferred_when_available() {
+        // target(100_000) + base_fee(1000) + 1*per_output(500) = 101_500
+        // required raw total for 1 input is in [102_500, 104_000]; 103_000 fits.
+        let utxos = vec![utxo(103_000), utxo(50_000), utxo(25_000)];
+        let selection = select_utxos(&utxos, 100_000, 1, WEIGHT, 1000, 100).unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.selected.len(), 1);
+        assert_eq!(selection.selected[0].value, 103_000);
+        assert_eq!(selection.fee, 103_000 - 100_000);
+    }
+
+    #[test]
+    fn test_falls_back_to_largest_first_with_change() {
+        let utxos = vec![utxo(80_000), utxo(70_000)];
+        let selection = select_utxos(&utxos, 100_000, 1, WEIGHT, 1000, 100).unwrap();
+        assert!(selection.selected.iter().map(|u| u.value).sum::<u64>() >= 100_000 + selection.fee);
+    }
+
+    #[test]
+    fn test_insufficient_funds_is_reported() {
+        let utxos = vec![utxo(1_000)];
+        let err = select_utxos(&utxos, 100_000, 1, WEIGHT, 1000, 100).unwrap_err();
+        match err {
+            CoinSelectionError::InsufficientFunds { available, .. } => assert_eq!(available, 1_000),
+        }
+    }
+
+    #[test]
+    fn test_dust_change_is_folded_into_fee() {
+        // Total input exceeds target+fee by less than the dust threshold.
+        let fee = 1000 + WEIGHT.per_input + WEIGHT.per_output;
+        let utxos = vec![utxo(100_000 + fee + 50)];
+        let selection = select_utxos(&utxos, 100_000, 1, WEIGHT, 1000, 100).unwrap();
+        assert_eq!(selection.change, 0);
+    }
+}
@@ -0,0 +1,170 @@
+//! Per-pool note commitment trees and incremental Merkle witnesses.
+//!
+//! Spend proofs are built against the *global* note commitment tree root, so
+//! every output's commitment - owned by this wallet or not - has to be
+//! appended to its pool's tree in strict block order. [`TreeTracker`] does
+//! that, and additionally starts an [`IncrementalWitness`] at the leaf of
+//! any note the wallet owns, advancing every open witness alongside the
+//! tree as later commitments arrive. Serialized tree state and witnesses
+//! are meant to be persisted alongside a [`crate::types::StoredNote`] (see
+//! its `witness` field), so a witness can later be read back and used to
+//! build a spend.
+//!
+//! Only leaves marked as owned keep a full authentication path; every other
+//! leaf is folded into the underlying frontier as soon as a later commitment
+//! arrives, so memory stays bounded by the number of notes the wallet is
+//! actually tracking rather than the size of the chain. A note is only
+//! spendable once [`TreeTracker::witness_at`] returns `Some` for its
+//! [`crate::types::StoredNote::position`] under the tracker's current
+//! [`TreeTracker::root`].
+
+use incrementalmerkletree::Hashable;
+use incrementalmerkletree::frontier::CommitmentTree;
+pub use incrementalmerkletree::witness::MerklePath;
+use incrementalmerkletree::witness::IncrementalWitness;
+use orchard::tree::MerkleHashOrchard;
+use sapling_crypto::Node as SaplingNode;
+use zcash_primitives::merkle_tree::{
+    read_commitment_tree, read_incremental_witness, write_commitment_tree,
+    write_incremental_witness,
+};
+
+/// Depth of both pools' note commitment trees (ZIP 202 / ZIP 225).
+pub const MERKLE_DEPTH: u8 = 32;
+
+/// Errors that can occur while tracking or (de)serializing commitment trees
+/// and witnesses.
+#[derive(Debug)]
+pub enum CommitmentTreeError {
+    /// The pool's commitment tree has reached its maximum depth.
+    TreeFull,
+    /// Serialized tree/witness bytes couldn't be parsed.
+    Corrupt(String),
+}
+
+impl core::fmt::Display for CommitmentTreeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TreeFull => write!(f, "commitment tree is full"),
+            Self::Corrupt(msg) => write!(f, "corrupt commitment tree state: {}", msg),
+        }
+    }
+}
+
+impl core::error::Error for CommitmentTreeError {}
+
+/// Tracks one pool's commitment tree plus a witness for every note the
+/// wallet owns; witnesses are kept and advanced for as long as the tracker
+/// lives.
+pub struct TreeTracker<H> {
+    tree: CommitmentTree<H, 32>,
+    /// `(note_id, witness)` pairs for notes whose leaf has been seen.
+    witnesses: Vec<(String, IncrementalWitness<H, 32>)>,
+}
+
+impl<H: Hashable + Clone> TreeTracker<H> {
+    /// Start tracking from an empty tree (e.g. wallet birthday at genesis).
+    pub fn empty() -> Self {
+        TreeTracker {
+            tree: CommitmentTree::empty(),
+            witnesses: Vec::new(),
+        }
+    }
+
+    /// Resume tracking from a previously-serialized tree. Witnesses for
+    /// notes already known to the wallet must be reattached separately via
+    /// `track_witness`, since the tree alone doesn't record which of its
+    /// leaves are ours.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CommitmentTreeError> {
+        let tree = read_commitment_tree(bytes)
+            .map_err(|e| CommitmentTreeError::Corrupt(e.to_string()))?;
+        Ok(TreeTracker {
+            tree,
+            witnesses: Vec::new(),
+        })
+    }
+
+    /// Append a commitment for one output, in tree order. `owned` is the
+    /// note's id when this output belongs to the wallet, which starts a
+    /// fresh witness at this leaf.
+    pub fn append(&mut self, node: H, owned: Option<String>) -> Result<(), CommitmentTreeError> {
+        for (_, witness) in self.witnesses.iter_mut() {
+            witness
+                .append(node.clone())
+                .map_err(|_| CommitmentTreeError::TreeFull)?;
+        }
+        self.tree
+            .append(node.clone())
+            .map_err(|_| CommitmentTreeError::TreeFull)?;
+        if let Some(note_id) = owned {
+            self.witnesses
+                .push((note_id, IncrementalWitness::from_tree(self.tree.clone())));
+        }
+        Ok(())
+    }
+
+    /// Re-attach a witness for a note restored from storage, so subsequent
+    /// `append` calls keep advancing it.
+    pub fn track_witness(&mut self, note_id: String, witness: IncrementalWitness<H, 32>) {
+        self.witnesses.push((note_id, witness));
+    }
+
+    /// Serialize the tree state for persistence.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, CommitmentTreeError> {
+        let mut out = Vec::new();
+        write_commitment_tree(&self.tree, &mut out)
+            .map_err(|e| CommitmentTreeError::Corrupt(e.to_string()))?;
+        Ok(out)
+    }
+
+    /// Current leaf position and serialized witness for `note_id`, if one is
+    /// being tracked. The leaf position is the note's index among all
+    /// commitments appended to this tree so far.
+    pub fn witness(&self, note_id: &str) -> Result<Option<(u64, Vec<u8>)>, CommitmentTreeError> {
+        let Some((_, witness)) = self.witnesses.iter().find(|(id, _)| id == note_id) else {
+            return Ok(None);
+        };
+        let position: u64 = witness.position().into();
+        let mut out = Vec::new();
+        write_incremental_witness(witness, &mut out)
+            .map_err(|e| CommitmentTreeError::Corrupt(e.to_string()))?;
+        Ok(Some((position, out)))
+    }
+
+    /// Authentication path from `position`'s leaf to the current root, if a
+    /// witness is being tracked at that position. `None` both when no owned
+    /// note was ever marked at `position` and when a marked leaf hasn't
+    /// accumulated enough later commitments yet to complete its path.
+    pub fn witness_at(&self, position: u64) -> Option<MerklePath<H, 32>> {
+        self.witnesses
+            .iter()
+            .find(|(_, witness)| u64::from(witness.position()) == position)
+            .and_then(|(_, witness)| witness.path())
+    }
+
+    /// The tree's current root, i.e. the anchor that a spend built against
+    /// any of this tracker's retained witnesses must match.
+    pub fn root(&self) -> H {
+        self.tree.root()
+    }
+}
+
+/// A `TreeTracker` specialized for the Sapling pool.
+pub type SaplingTreeTracker = TreeTracker<SaplingNode>;
+
+/// A `TreeTracker` specialized for the Orchard pool.
+pub type OrchardTreeTracker = TreeTracker<MerkleHashOrchard>;
+
+/// Deserialize a previously-persisted Sapling witness.
+pub fn read_sapling_witness(
+    bytes: &[u8],
+) -> Result<IncrementalWitness<SaplingNode, 32>, CommitmentTreeError> {
+    read_incremental_witness(bytes).map_err(|e| CommitmentTreeError::Corrupt(e.to_string()))
+}
+
+/// Deserialize a previously-persisted Orchard witness.
+pub fn read_orchard_witness(
+    bytes: &[u8],
+) -> Result<IncrementalWitness<MerkleHashOrchard, 32>, CommitmentTreeError> {
+    read_incremental_witness(bytes).map_err(|e| CommitmentTreeError::Corrupt(e.to_string()))
+}
@@ -0,0 +1,62 @@
+//! Transaction fee rules.
+//!
+//! [`FeeRule::Fixed`] keeps the old caller-supplied flat fee; [`FeeRule::Zip317`]
+//! computes the ZIP 317 conventional fee from the transparent input/output
+//! counts via [`zip317_fee`].
+
+/// Marginal fee per logical action, in zatoshis, per ZIP 317.
+pub const ZIP317_MARGINAL_FEE: u64 = 5000;
+/// Minimum number of logical actions a transaction is charged for, per ZIP 317.
+pub const ZIP317_GRACE_ACTIONS: u64 = 2;
+
+/// Approximate serialized size, in bytes, of a P2PKH transparent input.
+const P2PKH_INPUT_SIZE: u64 = 150;
+/// Approximate serialized size, in bytes, of a P2PKH transparent output.
+const P2PKH_OUTPUT_SIZE: u64 = 34;
+
+/// A fee rule a transaction builder can be asked to apply.
+#[derive(Debug, Clone, Copy)]
+pub enum FeeRule {
+    /// A flat fee, in zatoshis, supplied by the caller.
+    Fixed(u64),
+    /// The ZIP 317 conventional fee, computed from the transaction's
+    /// transparent input/output counts.
+    Zip317,
+}
+
+/// Compute the ZIP 317 conventional fee for a transparent-only transaction
+/// with `num_inputs` P2PKH inputs and `num_outputs` P2PKH outputs.
+///
+/// `logical_actions = max(ceil(total_tin_size / 150), ceil(total_tout_size / 34))`,
+/// and the fee is `marginal_fee * max(grace_actions, logical_actions)`. A
+/// simple 1-in/2-out spend (e.g. a payment plus change) therefore costs the
+/// `grace_actions` minimum: `5000 * 2 = 10_000` zatoshis.
+pub fn zip317_fee(num_inputs: u64, num_outputs: u64) -> u64 {
+    let total_tin_size = num_inputs * P2PKH_INPUT_SIZE;
+    let total_tout_size = num_outputs * P2PKH_OUTPUT_SIZE;
+    let logical_actions = total_tin_size
+        .div_ceil(P2PKH_INPUT_SIZE)
+        .max(total_tout_size.div_ceil(P2PKH_OUTPUT_SIZE));
+    ZIP317_MARGINAL_FEE * logical_actions.max(ZIP317_GRACE_ACTIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_spend_costs_the_grace_minimum() {
+        assert_eq!(zip317_fee(1, 2), 10_000);
+    }
+
+    #[test]
+    fn test_fee_grows_with_the_larger_of_inputs_or_outputs() {
+        assert_eq!(zip317_fee(5, 1), 25_000);
+        assert_eq!(zip317_fee(1, 5), 25_000);
+    }
+
+    #[test]
+    fn test_zero_inputs_and_outputs_still_pays_the_grace_minimum() {
+        assert_eq!(zip317_fee(0, 0), ZIP317_MARGINAL_FEE * ZIP317_GRACE_ACTIONS);
+    }
+}
@@ -0,0 +1,594 @@
+//! Fungible FIFO/LIFO/HIFO capital-gains lot matching.
+//!
+//! Unlike a specific-identification report that matches each spent note
+//! against its own recorded cost basis, this engine treats every note in a
+//! pool as commingled inventory: each note's creation opens an acquisition
+//! lot (value, acquisition date, per-unit price), and each spend disposes
+//! of value drawn from that pool's open lots by the chosen accounting
+//! method - not necessarily the lot the spent note itself opened. This is
+//! the common treatment for fungible holdings, where the specific coin
+//! received doesn't determine which coin is deemed sold.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::types::{
+    GainRecord, GainsReport, HoldingTerm, Pool, PoolGainsTotals, PriceQuote, StoredNote,
+    TransferType,
+};
+
+const ZATOSHI_PER_ZEC: f64 = 100_000_000.0;
+
+/// Which open lot is consumed first when matching a disposal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LotMethod {
+    /// Oldest acquisition first.
+    Fifo,
+    /// Newest acquisition first.
+    Lifo,
+    /// Highest cost-basis (price per unit) first.
+    Hifo,
+}
+
+impl LotMethod {
+    /// Parse a method name, case-insensitively.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "fifo" => Some(LotMethod::Fifo),
+            "lifo" => Some(LotMethod::Lifo),
+            "hifo" => Some(LotMethod::Hifo),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that can occur while matching disposals against acquisition lots.
+#[derive(Debug)]
+pub enum GainsError {
+    /// `method` wasn't one of `fifo`, `lifo`, or `hifo`.
+    InvalidMethod(String),
+    /// A pool's disposals exceed the value recorded in its acquisition
+    /// lots - some acquisitions are missing from the supplied notes.
+    InsufficientBasis { pool: Pool, shortfall_zatoshi: u64 },
+}
+
+impl core::fmt::Display for GainsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMethod(method) => write!(f, "unknown lot-matching method: {}", method),
+            Self::InsufficientBasis {
+                pool,
+                shortfall_zatoshi,
+            } => write!(
+                f,
+                "{} disposals exceed available basis by {} zatoshi - acquisition data is missing",
+                pool, shortfall_zatoshi
+            ),
+        }
+    }
+}
+
+impl core::error::Error for GainsError {}
+
+/// One acquisition lot, opened by a note's creation.
+struct OpenLot {
+    note_id: String,
+    remaining_zatoshi: u64,
+    acquired_date: String,
+    price: Option<f64>,
+}
+
+/// Match every spent note's value against its pool's open acquisition lots
+/// by `method`, and tally realized short/long-term gains.
+///
+/// Every note (spent or not) opens an acquisition lot. Disposals are
+/// processed in `notes` order, restricted to notes whose spend has
+/// confirmed (`StoredNote::is_spent`) - a note merely `PendingSpent` isn't
+/// counted yet, so a disposal whose spend never confirms never shows up as
+/// a taxable event; callers that care about chronological matching should
+/// pass `notes` pre-sorted by spend time. `prices` supplies a per-unit
+/// fiat price and date for a txid, looked up by both a lot's own txid (for
+/// its cost basis) and a disposal's `spent_txid` (for its proceeds and
+/// disposal date) - a txid missing from `prices` leaves the fields that
+/// depend on it `None` rather than failing the whole computation. A
+/// disposal note's `fee_zat` (set via `NoteCollection::record_transaction_fee`)
+/// is prorated across every lot it consumes by that slice's share of the
+/// total value its transaction spent, valued at the disposal's price like
+/// `proceeds`, and subtracted from `gain` alongside `basis` - a disposal with no recorded fee is treated as fee-free rather
+/// than leaving `gain` unknown. `transfer_types` maps a spending txid to its
+/// `TransferType` (see `scanner::classify_transfer_type`); a disposal whose
+/// transaction is `TransferType::WalletInternal` - value that only moved
+/// between this wallet's own pools/addresses - is skipped entirely rather
+/// than realizing a gain, and its lot is left open for a future genuine
+/// disposal. A spending txid missing from `transfer_types` is treated as a
+/// genuine disposal, since that's the common case (not every caller tracks
+/// transfer classification).
+pub fn compute_gains(
+    notes: &[StoredNote],
+    method: LotMethod,
+    prices: &HashMap<String, PriceQuote>,
+    transfer_types: &HashMap<String, TransferType>,
+) -> Result<GainsReport, GainsError> {
+    let mut lots_by_pool: HashMap<Pool, VecDeque<OpenLot>> = HashMap::new();
+    let mut value_by_spending_txid: HashMap<&str, u64> = HashMap::new();
+    for note in notes {
+        if note.value == 0 {
+            continue;
+        }
+        lots_by_pool
+            .entry(note.pool)
+            .or_default()
+            .push_back(OpenLot {
+                note_id: note.id.to_string(),
+                remaining_zatoshi: note.value,
+                acquired_date: acquisition_date(note),
+                price: prices.get(&note.txid).map(|q| q.price),
+            });
+        if note.is_spent() {
+            if let Some(spent_txid) = note.spent_txid.as_deref() {
+                *value_by_spending_txid.entry(spent_txid).or_default() += note.value;
+            }
+        }
+    }
+
+    let mut records = Vec::new();
+    let mut totals: HashMap<Pool, PoolGainsTotals> = HashMap::new();
+
+    for note in notes {
+        if !note.is_spent() {
+            continue;
+        }
+        let Some(spent_txid) = note.spent_txid.as_ref() else {
+            continue;
+        };
+        if note.value == 0 {
+            continue;
+        }
+        if transfer_types.get(spent_txid.as_str()) == Some(&TransferType::WalletInternal) {
+            continue;
+        }
+
+        let quote = prices.get(spent_txid);
+        let disposed_date = quote.map(|q| q.date.clone());
+        let disposal_price = quote.map(|q| q.price);
+
+        // The fee is recorded once per transaction but shared by every note
+        // it spent; prorate it here by this note's share of that total.
+        let total_spent_value = value_by_spending_txid.get(spent_txid.as_str()).copied();
+        let fee_rate = note.fee_zat.zip(total_spent_value).and_then(|(fee, total)| {
+            if total == 0 {
+                None
+            } else {
+                Some(fee as f64 / total as f64)
+            }
+        });
+
+        let mut remaining = note.value;
+        let lots = lots_by_pool.entry(note.pool).or_default();
+
+        while remaining > 0 {
+            let lot_index = match method {
+                LotMethod::Fifo => {
+                    if lots.is_empty() {
+                        None
+                    } else {
+                        Some(0)
+                    }
+                }
+                LotMethod::Lifo => {
+                    if lots.is_empty() {
+                        None
+                    } else {
+                        Some(lots.len() - 1)
+                    }
+                }
+                LotMethod::Hifo => lots
+                    .iter()
+                    .enumerate()
+                    .max_by(|(_, a), (_, b)| {
+                        a.price
+                            .unwrap_or(f64::MIN)
+                            .total_cmp(&b.price.unwrap_or(f64::MIN))
+                    })
+                    .map(|(i, _)| i),
+            };
+
+            let Some(lot_index) = lot_index else {
+                return Err(GainsError::InsufficientBasis {
+                    pool: note.pool,
+                    shortfall_zatoshi: remaining,
+                });
+            };
+
+            let lot = &mut lots[lot_index];
+            let consumed = remaining.min(lot.remaining_zatoshi);
+            lot.remaining_zatoshi -= consumed;
+            remaining -= consumed;
+
+            let acquired_date = lot.acquired_date.clone();
+            let lot_note_id = lot.note_id.clone();
+            let basis = lot.price.map(|p| (consumed as f64 / ZATOSHI_PER_ZEC) * p);
+            if lot.remaining_zatoshi == 0 {
+                lots.remove(lot_index);
+            }
+
+            let holding_days = disposed_date
+                .as_deref()
+                .and_then(|disposed| date_diff_days(&acquired_date, disposed));
+            let term = holding_days.map(HoldingTerm::from_holding_days);
+            let proceeds = disposal_price.map(|p| (consumed as f64 / ZATOSHI_PER_ZEC) * p);
+            let fee_share = fee_rate
+                .zip(disposal_price)
+                .map(|(rate, p)| (consumed as f64 / ZATOSHI_PER_ZEC) * rate * p);
+            let gain = proceeds
+                .zip(basis)
+                .map(|(proceeds, basis)| proceeds - basis - fee_share.unwrap_or(0.0));
+
+            if let (Some(proceeds), Some(basis), Some(gain), Some(term)) =
+                (proceeds, basis, gain, term)
+            {
+                let pool_totals = totals.entry(note.pool).or_default();
+                match term {
+                    HoldingTerm::ShortTerm => {
+                        pool_totals.short_term_proceeds += proceeds;
+                        pool_totals.short_term_basis += basis;
+                        pool_totals.short_term_gain += gain;
+                    }
+                    HoldingTerm::LongTerm => {
+                        pool_totals.long_term_proceeds += proceeds;
+                        pool_totals.long_term_basis += basis;
+                        pool_totals.long_term_gain += gain;
+                    }
+                }
+            }
+
+            records.push(GainRecord {
+                pool: note.pool,
+                disposal_note_id: note.id.to_string(),
+                lot_note_id,
+                value_zatoshi: consumed,
+                acquired_date,
+                disposed_date: disposed_date.clone(),
+                holding_days,
+                term,
+                proceeds,
+                basis,
+                fee_share,
+                gain,
+            });
+        }
+    }
+
+    Ok(GainsReport { records, totals })
+}
+
+/// A note's acquisition date, as the `YYYY-MM-DD` portion of `created_at`.
+fn acquisition_date(note: &StoredNote) -> String {
+    note.created_at
+        .split('T')
+        .next()
+        .unwrap_or(&note.created_at)
+        .to_string()
+}
+
+/// Whole-day difference between two `YYYY-MM-DD` dates, or `None` if either
+/// fails to parse.
+fn date_diff_days(from: &str, to: &str) -> Option<i64> {
+    Some(days_since_epoch(to)? - days_since_epoch(from)?)
+}
+
+/// Parse a `YYYY-MM-DD` date into a day count since the Unix epoch, via
+/// Howard Hinnant's `days_from_civil` algorithm.
+fn days_since_epoch(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = (y - era * 400) as u64;
+    let mp = if m > 2 { m - 3 } else { m + 9 } as u64;
+    let doy = (153 * mp + 2) / 5 + d as u64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe as i64 - 719_468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{KeyScope, NoteId, NoteStatus};
+
+    fn note(
+        id: &str,
+        pool: Pool,
+        value: u64,
+        created_at: &str,
+        spent_txid: Option<&str>,
+    ) -> StoredNote {
+        StoredNote {
+            id: NoteId::new(id, pool, 0),
+            wallet_id: "wallet1".to_string(),
+            txid: id.to_string(),
+            output_index: 0,
+            pool,
+            value,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: spent_txid.map(|s| s.to_string()),
+            created_at: created_at.to_string(),
+            position: None,
+            witness: None,
+            status: if spent_txid.is_some() {
+                NoteStatus::Spent
+            } else {
+                NoteStatus::Confirmed
+            },
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        }
+    }
+
+    fn quote(date: &str, price: f64) -> PriceQuote {
+        PriceQuote {
+            date: date.to_string(),
+            price,
+        }
+    }
+
+    #[test]
+    fn test_fifo_matches_oldest_lot_first() {
+        let notes = vec![
+            note("a", Pool::Sapling, 100_000_000, "2021-01-01T00:00:00Z", None),
+            note("b", Pool::Sapling, 50_000_000, "2022-01-01T00:00:00Z", None),
+            // Bigger than lot "a" alone, so it must spill into lot "b" too -
+            // that's what actually exercises FIFO ordering across lots.
+            note(
+                "c",
+                Pool::Sapling,
+                150_000_000,
+                "2023-01-01T00:00:00Z",
+                Some("spend1"),
+            ),
+        ];
+        let mut prices = HashMap::new();
+        prices.insert("a".to_string(), quote("2021-01-01", 40.0));
+        prices.insert("b".to_string(), quote("2022-01-01", 100.0));
+        prices.insert("spend1".to_string(), quote("2023-01-02", 60.0));
+
+        let report = compute_gains(&notes, LotMethod::Fifo, &prices, &HashMap::new()).unwrap();
+
+        assert_eq!(report.records.len(), 2);
+        assert_eq!(report.records[0].lot_note_id, "a-sapling-0");
+        assert_eq!(report.records[0].value_zatoshi, 100_000_000);
+        assert_eq!(report.records[1].lot_note_id, "b-sapling-0");
+        assert_eq!(report.records[1].value_zatoshi, 50_000_000);
+        assert_eq!(report.records[1].basis, Some(50.0));
+    }
+
+    #[test]
+    fn test_hifo_prefers_highest_cost_basis_lot() {
+        let notes = vec![
+            note("a", Pool::Orchard, 100_000_000, "2021-01-01T00:00:00Z", None),
+            note("b", Pool::Orchard, 100_000_000, "2021-06-01T00:00:00Z", None),
+            note(
+                "c",
+                Pool::Orchard,
+                100_000_000,
+                "2022-01-01T00:00:00Z",
+                Some("spend1"),
+            ),
+        ];
+        let mut prices = HashMap::new();
+        prices.insert("a".to_string(), quote("2021-01-01", 40.0));
+        prices.insert("b".to_string(), quote("2021-06-01", 90.0));
+        prices.insert("spend1".to_string(), quote("2022-01-02", 60.0));
+
+        let report = compute_gains(&notes, LotMethod::Hifo, &prices, &HashMap::new()).unwrap();
+
+        assert_eq!(report.records.len(), 1);
+        assert_eq!(report.records[0].lot_note_id, "b-orchard-0");
+    }
+
+    #[test]
+    fn test_partial_lot_consumption_carries_remainder_forward() {
+        // Every note - spent or not - opens its own acquisition lot (see
+        // `compute_gains`'s doc comment), so "b" and "c" are each both a
+        // disposal *and* an open lot competing for FIFO order alongside "a".
+        let notes = vec![
+            note("a", Pool::Sapling, 100_000_000, "2021-01-01T00:00:00Z", None),
+            note(
+                "b",
+                Pool::Sapling,
+                30_000_000,
+                "2021-06-01T00:00:00Z",
+                Some("spend1"),
+            ),
+            note(
+                "c",
+                Pool::Sapling,
+                80_000_000,
+                "2021-07-01T00:00:00Z",
+                Some("spend2"),
+            ),
+        ];
+        let prices = HashMap::new();
+
+        let report = compute_gains(&notes, LotMethod::Fifo, &prices, &HashMap::new()).unwrap();
+
+        // Disposal "b" (30M) drains part of lot "a". Disposal "c" (80M) then
+        // drains the rest of lot "a" (70M) and spills the remaining 10M into
+        // lot "b" - that spillover is the partial-consumption carry-forward
+        // this test is named for.
+        assert_eq!(report.records.len(), 3);
+        assert_eq!(report.records[0].disposal_note_id, "b-sapling-0");
+        assert_eq!(report.records[0].lot_note_id, "a-sapling-0");
+        assert_eq!(report.records[0].value_zatoshi, 30_000_000);
+        assert_eq!(report.records[1].disposal_note_id, "c-sapling-0");
+        assert_eq!(report.records[1].lot_note_id, "a-sapling-0");
+        assert_eq!(report.records[1].value_zatoshi, 70_000_000);
+        assert_eq!(report.records[2].disposal_note_id, "c-sapling-0");
+        assert_eq!(report.records[2].lot_note_id, "b-sapling-0");
+        assert_eq!(report.records[2].value_zatoshi, 10_000_000);
+    }
+
+    #[test]
+    fn test_insufficient_basis_is_an_error() {
+        let notes = vec![note(
+            "a",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        )];
+        let prices = HashMap::new();
+
+        let err = compute_gains(&notes, LotMethod::Fifo, &prices, &HashMap::new()).unwrap_err();
+        match err {
+            GainsError::InsufficientBasis {
+                pool,
+                shortfall_zatoshi,
+            } => {
+                assert_eq!(pool, Pool::Sapling);
+                assert_eq!(shortfall_zatoshi, 100_000_000);
+            }
+            _ => panic!("expected InsufficientBasis"),
+        }
+    }
+
+    #[test]
+    fn test_holding_term_classifies_long_term_past_one_year() {
+        let notes = vec![note(
+            "a",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        )];
+        let mut prices = HashMap::new();
+        prices.insert("a".to_string(), quote("2021-01-01", 40.0));
+        prices.insert("spend1".to_string(), quote("2022-01-02", 60.0));
+
+        let report = compute_gains(&notes, LotMethod::Fifo, &prices, &HashMap::new()).unwrap();
+
+        assert_eq!(report.records[0].term, Some(HoldingTerm::LongTerm));
+        assert_eq!(report.records[0].gain, Some(60.0 - 40.0));
+    }
+
+    #[test]
+    fn test_pending_spent_note_is_not_a_disposal() {
+        let mut pending = note(
+            "a",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        );
+        pending.status = NoteStatus::PendingSpent;
+        let prices = HashMap::new();
+
+        let report = compute_gains(&[pending], LotMethod::Fifo, &prices, &HashMap::new()).unwrap();
+
+        assert!(report.records.is_empty());
+    }
+
+    #[test]
+    fn test_fee_share_reduces_gain() {
+        let mut spend = note(
+            "a",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        );
+        spend.fee_zat = Some(1_000_000);
+        let mut prices = HashMap::new();
+        prices.insert("a".to_string(), quote("2021-01-01", 40.0));
+        prices.insert("spend1".to_string(), quote("2022-01-02", 60.0));
+
+        let report = compute_gains(&[spend], LotMethod::Fifo, &prices, &HashMap::new()).unwrap();
+
+        assert_eq!(report.records.len(), 1);
+        // 0.01 ZEC fee, all of it on this one note, at 60 per ZEC.
+        assert_eq!(report.records[0].fee_share, Some(0.6));
+        assert_eq!(report.records[0].gain, Some(60.0 - 40.0 - 0.6));
+    }
+
+    #[test]
+    fn test_fee_is_prorated_across_notes_sharing_a_spending_txid() {
+        let mut spend_a = note(
+            "a",
+            Pool::Sapling,
+            75_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        );
+        spend_a.fee_zat = Some(1_000_000);
+        let mut spend_b = note(
+            "b",
+            Pool::Sapling,
+            25_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        );
+        spend_b.fee_zat = Some(1_000_000);
+        let notes = vec![
+            note("lot", Pool::Sapling, 100_000_000, "2020-01-01T00:00:00Z", None),
+            spend_a,
+            spend_b,
+        ];
+        let mut prices = HashMap::new();
+        prices.insert("spend1".to_string(), quote("2021-06-01", 100.0));
+
+        let report = compute_gains(&notes, LotMethod::Fifo, &prices, &HashMap::new()).unwrap();
+
+        // 0.01 ZEC fee split 3:1 by value, at 100 per ZEC.
+        assert_eq!(report.records.len(), 2);
+        assert_eq!(report.records[0].fee_share, Some(0.75));
+        assert_eq!(report.records[1].fee_share, Some(0.25));
+    }
+
+    #[test]
+    fn test_missing_fee_does_not_block_gain() {
+        let notes = vec![note(
+            "a",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        )];
+        let mut prices = HashMap::new();
+        prices.insert("a".to_string(), quote("2021-01-01", 40.0));
+        prices.insert("spend1".to_string(), quote("2022-01-02", 60.0));
+
+        let report = compute_gains(&notes, LotMethod::Fifo, &prices, &HashMap::new()).unwrap();
+
+        assert_eq!(report.records[0].fee_share, None);
+        assert_eq!(report.records[0].gain, Some(60.0 - 40.0));
+    }
+
+    #[test]
+    fn test_wallet_internal_transfer_is_not_a_disposal() {
+        let notes = vec![note(
+            "a",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("spend1"),
+        )];
+        let mut prices = HashMap::new();
+        prices.insert("a".to_string(), quote("2021-01-01", 40.0));
+        prices.insert("spend1".to_string(), quote("2022-01-02", 60.0));
+        let mut transfer_types = HashMap::new();
+        transfer_types.insert("spend1".to_string(), TransferType::WalletInternal);
+
+        let report = compute_gains(&notes, LotMethod::Fifo, &prices, &transfer_types).unwrap();
+
+        assert!(report.records.is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+//! Daily ZEC/fiat close-price series used to value a note at acquisition.
+//!
+//! Unlike `fiat`'s [`value_notes`](crate::fiat::value_notes), which takes a
+//! caller-supplied `date -> price` table for one-off valuation, this module
+//! keeps a running series per currency so it can be queried repeatedly as
+//! notes are stored. [`HistoricalPrices`] is the in-memory series (a
+//! `BTreeMap` so a missing day - weekends, an oracle outage - falls back to
+//! the nearest earlier recorded date); [`PriceCache`] persists it in a
+//! `prices` table keyed by ISO date and currency, following the same
+//! "transport not wired in" degrade as cli's `PriceOracle`
+//! (`cli/src/price.rs`): fetching a quote is out of scope for this crate, so
+//! a lookup that isn't already cached just returns `None` rather than
+//! failing the note it was priced for.
+
+use std::collections::BTreeMap;
+
+use rusqlite::{Connection, params};
+
+use crate::fiat::acquisition_date;
+use crate::types::StoredNote;
+
+/// Errors from [`PriceCache`].
+#[derive(Debug)]
+pub enum HistoricalPricesError {
+    Sqlite(rusqlite::Error),
+}
+
+impl core::fmt::Display for HistoricalPricesError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            HistoricalPricesError::Sqlite(e) => write!(f, "price cache error: {e}"),
+        }
+    }
+}
+
+impl core::error::Error for HistoricalPricesError {}
+
+impl From<rusqlite::Error> for HistoricalPricesError {
+    fn from(e: rusqlite::Error) -> Self {
+        HistoricalPricesError::Sqlite(e)
+    }
+}
+
+/// A daily ZEC/fiat price series for a single currency, keyed by ISO
+/// (`YYYY-MM-DD`) date.
+#[derive(Debug, Clone, Default)]
+pub struct HistoricalPrices {
+    prices: BTreeMap<String, f64>,
+}
+
+impl HistoricalPrices {
+    /// An empty series.
+    pub fn new() -> Self {
+        HistoricalPrices::default()
+    }
+
+    /// Record (or overwrite) the close price for `date`.
+    pub fn insert(&mut self, date: impl Into<String>, price: f64) {
+        self.prices.insert(date.into(), price);
+    }
+
+    /// The price on `date`, falling back to the nearest earlier recorded
+    /// date when `date` itself has no entry (common for weekends/gaps in the
+    /// series). Returns `None` only when the series has no entry at or
+    /// before `date`.
+    pub fn price_on(&self, date: &str) -> Option<f64> {
+        self.prices
+            .range(..=date.to_string())
+            .next_back()
+            .map(|(_, price)| *price)
+    }
+}
+
+/// Set `note.acquired_fiat_value` and `note.fiat_currency` by looking up
+/// `note`'s acquisition date in `prices`. Leaves both fields `None` if
+/// `prices` has no entry at or before that date.
+pub fn populate_acquired_fiat_value(
+    note: &mut StoredNote,
+    prices: &HistoricalPrices,
+    currency: &str,
+) {
+    let date = acquisition_date(note);
+    note.acquired_fiat_value = prices.price_on(&date);
+    note.fiat_currency = note.acquired_fiat_value.is_some().then(|| currency.to_string());
+}
+
+/// SQLite-backed cache of a [`HistoricalPrices`] series, keyed by
+/// `(date, currency)` so one cache can hold more than one fiat currency's
+/// series.
+pub struct PriceCache {
+    conn: Connection,
+}
+
+impl PriceCache {
+    /// Open (creating if needed) a price cache at `path`, creating its
+    /// schema if it doesn't exist yet.
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, HistoricalPricesError> {
+        let conn = Connection::open(path)?;
+        let cache = Self { conn };
+        cache.init_schema()?;
+        Ok(cache)
+    }
+
+    /// Open an in-memory cache (for testing).
+    pub fn open_in_memory() -> Result<Self, HistoricalPricesError> {
+        let conn = Connection::open_in_memory()?;
+        let cache = Self { conn };
+        cache.init_schema()?;
+        Ok(cache)
+    }
+
+    fn init_schema(&self) -> Result<(), HistoricalPricesError> {
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS prices (
+                date TEXT NOT NULL,
+                currency TEXT NOT NULL,
+                price REAL NOT NULL,
+                PRIMARY KEY (date, currency)
+            );
+            "#,
+        )?;
+        Ok(())
+    }
+
+    /// Cache `price` for `date`/`currency`, overwriting any previously
+    /// cached price for that day.
+    pub fn set(&self, date: &str, currency: &str, price: f64) -> Result<(), HistoricalPricesError> {
+        self.conn.execute(
+            "INSERT INTO prices (date, currency, price) VALUES (?1, ?2, ?3)
+             ON CONFLICT(date, currency) DO UPDATE SET price = excluded.price",
+            params![date, currency, price],
+        )?;
+        Ok(())
+    }
+
+    /// Load the full cached series for `currency` into an in-memory
+    /// [`HistoricalPrices`], for repeated `price_on` lookups without
+    /// round-tripping to SQLite per note.
+    pub fn load_series(&self, currency: &str) -> Result<HistoricalPrices, HistoricalPricesError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT date, price FROM prices WHERE currency = ?1 ORDER BY date")?;
+        let rows = stmt.query_map(params![currency], |row| {
+            let date: String = row.get(0)?;
+            let price: f64 = row.get(1)?;
+            Ok((date, price))
+        })?;
+
+        let mut prices = HistoricalPrices::new();
+        for row in rows {
+            let (date, price) = row?;
+            prices.insert(date, price);
+        }
+        Ok(prices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{KeyScope, NoteId, NoteStatus, Pool};
+
+    fn note(created_at: &str) -> StoredNote {
+        StoredNote {
+            id: NoteId::new("a", Pool::Sapling, 0),
+            wallet_id: "wallet1".to_string(),
+            txid: "a".to_string(),
+            output_index: 0,
+            pool: Pool::Sapling,
+            value: 100_000_000,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: created_at.to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        }
+    }
+
+    #[test]
+    fn test_price_on_exact_match() {
+        let mut prices = HistoricalPrices::new();
+        prices.insert("2021-01-01", 40.0);
+        prices.insert("2021-01-03", 42.0);
+
+        assert_eq!(prices.price_on("2021-01-01"), Some(40.0));
+        assert_eq!(prices.price_on("2021-01-03"), Some(42.0));
+    }
+
+    #[test]
+    fn test_price_on_falls_back_to_nearest_earlier_date() {
+        let mut prices = HistoricalPrices::new();
+        prices.insert("2021-01-01", 40.0);
+        prices.insert("2021-01-03", 42.0);
+
+        // 2021-01-02 is a gap - falls back to 01-01, not 01-03.
+        assert_eq!(prices.price_on("2021-01-02"), Some(40.0));
+    }
+
+    #[test]
+    fn test_price_on_missing_before_first_entry() {
+        let mut prices = HistoricalPrices::new();
+        prices.insert("2021-01-03", 42.0);
+
+        assert_eq!(prices.price_on("2021-01-01"), None);
+    }
+
+    #[test]
+    fn test_populate_acquired_fiat_value_sets_both_fields() {
+        let mut prices = HistoricalPrices::new();
+        prices.insert("2021-01-01", 40.0);
+        let mut n = note("2021-01-02T00:00:00Z");
+
+        populate_acquired_fiat_value(&mut n, &prices, "USD");
+
+        assert_eq!(n.acquired_fiat_value, Some(40.0));
+        assert_eq!(n.fiat_currency, Some("USD".to_string()));
+    }
+
+    #[test]
+    fn test_populate_acquired_fiat_value_leaves_none_when_uncovered() {
+        let prices = HistoricalPrices::new();
+        let mut n = note("2021-01-02T00:00:00Z");
+
+        populate_acquired_fiat_value(&mut n, &prices, "USD");
+
+        assert_eq!(n.acquired_fiat_value, None);
+        assert_eq!(n.fiat_currency, None);
+    }
+
+    #[test]
+    fn test_price_cache_round_trips_through_sqlite() {
+        let cache = PriceCache::open_in_memory().unwrap();
+        cache.set("2021-01-01", "USD", 40.0).unwrap();
+        cache.set("2021-01-03", "USD", 42.0).unwrap();
+        cache.set("2021-01-01", "EUR", 35.0).unwrap();
+
+        let usd = cache.load_series("USD").unwrap();
+        assert_eq!(usd.price_on("2021-01-02"), Some(40.0));
+        assert_eq!(usd.price_on("2021-01-03"), Some(42.0));
+
+        let eur = cache.load_series("EUR").unwrap();
+        assert_eq!(eur.price_on("2021-01-01"), Some(35.0));
+    }
+
+    #[test]
+    fn test_price_cache_set_overwrites_existing_price() {
+        let cache = PriceCache::open_in_memory().unwrap();
+        cache.set("2021-01-01", "USD", 40.0).unwrap();
+        cache.set("2021-01-01", "USD", 41.0).unwrap();
+
+        let usd = cache.load_series("USD").unwrap();
+        assert_eq!(usd.price_on("2021-01-01"), Some(41.0));
+    }
+}
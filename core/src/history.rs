@@ -0,0 +1,386 @@
+//! Chronological per-transaction ledger, for tax-software ingestion.
+//!
+//! Unlike `gains`'s per-lot disposal records, a ledger entry groups every
+//! note a transaction touched - received and spent - under that
+//! transaction's txid, the way tax-import tools expect one combined row
+//! (or event group) per transaction rather than per note.
+
+use std::collections::HashMap;
+
+use crate::types::{
+    EventKind, GainsReport, HoldingTerm, Pool, StoredNote, TransactionEvent,
+    TransactionHistoryEntry,
+};
+
+/// Per-note disposal info pulled from a `GainsReport`, summed across every
+/// lot the note was matched against.
+#[derive(Default)]
+struct DisposalInfo {
+    proceeds: Option<f64>,
+    gain: Option<f64>,
+    disposed_date: Option<String>,
+}
+
+/// Build a time-ordered, per-txid transaction ledger from `notes`.
+///
+/// A note opens a `Received` event on its own `txid`. A note that's been
+/// confirmed spent (`StoredNote::is_spent`) also opens a `Sent` event on
+/// its `spent_txid`, sharing that transaction's recorded fee (if any)
+/// proportionally by value the same way `gains::compute_gains` does -
+/// both events land in the same entry when a transaction both spends and
+/// creates notes (e.g. a shielded spend with change). `wallet_id`
+/// restricts the ledger to one wallet's notes when supplied; `gains`, if
+/// supplied, fills in each event's `fiat_value`/`gain` from a prior
+/// `compute_gains` pass - both stay `None` without it. Entries are sorted
+/// by `date` (unknown dates sort first), then `txid`.
+pub fn build_transaction_history(
+    notes: &[StoredNote],
+    wallet_id: Option<&str>,
+    gains: Option<&GainsReport>,
+) -> Vec<TransactionHistoryEntry> {
+    let notes: Vec<&StoredNote> = notes
+        .iter()
+        .filter(|n| match wallet_id {
+            Some(w) => n.wallet_id == w,
+            None => true,
+        })
+        .collect();
+
+    let mut basis_by_lot_note: HashMap<&str, f64> = HashMap::new();
+    let mut disposal_by_note: HashMap<&str, DisposalInfo> = HashMap::new();
+    if let Some(gains) = gains {
+        for record in &gains.records {
+            if let Some(basis) = record.basis {
+                *basis_by_lot_note.entry(record.lot_note_id.as_str()).or_default() += basis;
+            }
+            let info = disposal_by_note
+                .entry(record.disposal_note_id.as_str())
+                .or_default();
+            if let Some(proceeds) = record.proceeds {
+                *info.proceeds.get_or_insert(0.0) += proceeds;
+            }
+            if let Some(gain) = record.gain {
+                *info.gain.get_or_insert(0.0) += gain;
+            }
+            if info.disposed_date.is_none() {
+                info.disposed_date = record.disposed_date.clone();
+            }
+        }
+    }
+
+    let mut value_by_spending_txid: HashMap<&str, u64> = HashMap::new();
+    for note in &notes {
+        if note.is_spent() {
+            if let Some(spent_txid) = note.spent_txid.as_deref() {
+                *value_by_spending_txid.entry(spent_txid).or_default() += note.value;
+            }
+        }
+    }
+
+    let mut entries: HashMap<String, TransactionHistoryEntry> = HashMap::new();
+
+    for note in &notes {
+        if note.value == 0 {
+            continue;
+        }
+
+        let received = entries
+            .entry(note.txid.clone())
+            .or_insert_with(|| TransactionHistoryEntry {
+                txid: note.txid.clone(),
+                date: None,
+                events: Vec::new(),
+            });
+        received.date = Some(match received.date.take() {
+            Some(existing) if existing <= note.created_at => existing,
+            _ => note.created_at.clone(),
+        });
+        let note_id = note.id.to_string();
+        received.events.push(TransactionEvent {
+            note_id: note_id.clone(),
+            kind: EventKind::Received,
+            pool: note.pool,
+            value_zatoshi: note.value,
+            address: note.address.clone(),
+            memo: note.memo.clone(),
+            fee_share_zat: None,
+            fiat_value: basis_by_lot_note.get(note_id.as_str()).copied(),
+            gain: None,
+        });
+
+        if !note.is_spent() {
+            continue;
+        }
+        let Some(spent_txid) = note.spent_txid.clone() else {
+            continue;
+        };
+
+        let fee_share_zat = note.fee_zat.and_then(|fee| {
+            let total = *value_by_spending_txid.get(spent_txid.as_str())?;
+            if total == 0 {
+                None
+            } else {
+                Some(((fee as u128 * note.value as u128) / total as u128) as u64)
+            }
+        });
+        let disposal = disposal_by_note.get(note_id.as_str());
+
+        let spent = entries
+            .entry(spent_txid.clone())
+            .or_insert_with(|| TransactionHistoryEntry {
+                txid: spent_txid.clone(),
+                date: disposal.and_then(|d| d.disposed_date.clone()),
+                events: Vec::new(),
+            });
+        if spent.date.is_none() {
+            spent.date = disposal.and_then(|d| d.disposed_date.clone());
+        }
+        spent.events.push(TransactionEvent {
+            note_id,
+            kind: EventKind::Sent,
+            pool: note.pool,
+            value_zatoshi: note.value,
+            address: note.address.clone(),
+            memo: note.memo.clone(),
+            fee_share_zat,
+            fiat_value: disposal.and_then(|d| d.proceeds),
+            gain: disposal.and_then(|d| d.gain),
+        });
+    }
+
+    let mut history: Vec<TransactionHistoryEntry> = entries.into_values().collect();
+    history.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.txid.cmp(&b.txid)));
+    history
+}
+
+/// A tax-import CSV column layout `export_history_csv` can render to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistorySchema {
+    /// `date,type,asset,amount,fiat_value,fee,gain` - one row per event.
+    Generic,
+}
+
+impl HistorySchema {
+    /// Parse a schema name, case-insensitively.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "generic" => Some(HistorySchema::Generic),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that can occur while exporting a transaction history to CSV.
+#[derive(Debug)]
+pub enum HistoryError {
+    /// `schema` wasn't a name `HistorySchema::parse` recognizes.
+    InvalidSchema(String),
+}
+
+impl core::fmt::Display for HistoryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidSchema(schema) => write!(f, "unknown history export schema: {}", schema),
+        }
+    }
+}
+
+impl core::error::Error for HistoryError {}
+
+const ZATOSHI_PER_ZEC: f64 = 100_000_000.0;
+
+/// Render `history` as CSV, one row per event, in `schema`'s column layout.
+pub fn export_history_csv(history: &[TransactionHistoryEntry], schema: HistorySchema) -> String {
+    match schema {
+        HistorySchema::Generic => export_generic_csv(history),
+    }
+}
+
+fn export_generic_csv(history: &[TransactionHistoryEntry]) -> String {
+    let mut out = String::from("date,type,asset,amount,fiat_value,fee,gain\n");
+    for entry in history {
+        for event in &entry.events {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                entry.date.as_deref().unwrap_or(""),
+                match event.kind {
+                    EventKind::Received => "received",
+                    EventKind::Sent => "sent",
+                },
+                event.pool.as_str(),
+                event.value_zatoshi as f64 / ZATOSHI_PER_ZEC,
+                opt_to_string(event.fiat_value),
+                opt_to_string(
+                    event
+                        .fee_share_zat
+                        .map(|f| f as f64 / ZATOSHI_PER_ZEC)
+                ),
+                opt_to_string(event.gain),
+            ));
+        }
+    }
+    out
+}
+
+fn opt_to_string<T: std::fmt::Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{GainRecord, KeyScope, NoteId, NoteStatus, PoolGainsTotals};
+
+    fn note(
+        id: &str,
+        txid: &str,
+        pool: Pool,
+        value: u64,
+        created_at: &str,
+        spent_txid: Option<&str>,
+    ) -> StoredNote {
+        StoredNote {
+            id: NoteId::new(id, pool, 0),
+            wallet_id: "wallet1".to_string(),
+            txid: txid.to_string(),
+            output_index: 0,
+            pool,
+            value,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: Some("addr1".to_string()),
+            spent_txid: spent_txid.map(|s| s.to_string()),
+            created_at: created_at.to_string(),
+            position: None,
+            witness: None,
+            status: if spent_txid.is_some() {
+                NoteStatus::Spent
+            } else {
+                NoteStatus::Confirmed
+            },
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        }
+    }
+
+    #[test]
+    fn test_received_and_sent_events_grouped_by_txid() {
+        let notes = vec![
+            note("a", "tx1", Pool::Sapling, 100_000_000, "2021-01-01T00:00:00Z", None),
+            note("b", "tx2", Pool::Sapling, 100_000_000, "2021-06-01T00:00:00Z", Some("tx3")),
+        ];
+
+        let history = build_transaction_history(&notes, None, None);
+
+        assert_eq!(history.len(), 3);
+        let tx1 = history.iter().find(|e| e.txid == "tx1").unwrap();
+        assert_eq!(tx1.events.len(), 1);
+        assert_eq!(tx1.events[0].kind, EventKind::Received);
+        let tx3 = history.iter().find(|e| e.txid == "tx3").unwrap();
+        assert_eq!(tx3.events.len(), 1);
+        assert_eq!(tx3.events[0].kind, EventKind::Sent);
+    }
+
+    #[test]
+    fn test_wallet_id_filters_notes() {
+        let mut other = note("a", "tx1", Pool::Sapling, 100_000_000, "2021-01-01T00:00:00Z", None);
+        other.wallet_id = "wallet2".to_string();
+        let notes = vec![other];
+
+        let history = build_transaction_history(&notes, Some("wallet1"), None);
+
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_fee_is_prorated_across_spent_notes_in_the_same_transaction() {
+        let mut a = note(
+            "a", "tx_a", Pool::Sapling, 75_000_000, "2021-01-01T00:00:00Z", Some("tx3"),
+        );
+        a.fee_zat = Some(1_000_000);
+        let mut b = note(
+            "b", "tx_b", Pool::Sapling, 25_000_000, "2021-01-01T00:00:00Z", Some("tx3"),
+        );
+        b.fee_zat = Some(1_000_000);
+        let notes = vec![a, b];
+
+        let history = build_transaction_history(&notes, None, None);
+
+        let tx3 = history.iter().find(|e| e.txid == "tx3").unwrap();
+        let fee_a = tx3
+            .events
+            .iter()
+            .find(|e| e.note_id == "a-sapling-0")
+            .unwrap()
+            .fee_share_zat;
+        let fee_b = tx3
+            .events
+            .iter()
+            .find(|e| e.note_id == "b-sapling-0")
+            .unwrap()
+            .fee_share_zat;
+        assert_eq!(fee_a, Some(750_000));
+        assert_eq!(fee_b, Some(250_000));
+    }
+
+    #[test]
+    fn test_gains_report_fills_in_fiat_value_and_gain() {
+        let notes = vec![note(
+            "a",
+            "tx1",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            Some("tx2"),
+        )];
+        let mut totals = HashMap::new();
+        totals.insert(Pool::Sapling, PoolGainsTotals::default());
+        let gains = GainsReport {
+            records: vec![GainRecord {
+                pool: Pool::Sapling,
+                disposal_note_id: "a-sapling-0".to_string(),
+                lot_note_id: "a-sapling-0".to_string(),
+                value_zatoshi: 100_000_000,
+                acquired_date: "2021-01-01".to_string(),
+                disposed_date: Some("2022-01-02".to_string()),
+                holding_days: Some(366),
+                term: Some(HoldingTerm::LongTerm),
+                proceeds: Some(60.0),
+                basis: Some(40.0),
+                fee_share: None,
+                gain: Some(20.0),
+            }],
+            totals,
+        };
+
+        let history = build_transaction_history(&notes, None, Some(&gains));
+
+        let tx2 = history.iter().find(|e| e.txid == "tx2").unwrap();
+        assert_eq!(tx2.date.as_deref(), Some("2022-01-02"));
+        assert_eq!(tx2.events[0].fiat_value, Some(60.0));
+        assert_eq!(tx2.events[0].gain, Some(20.0));
+    }
+
+    #[test]
+    fn test_export_generic_csv_has_one_row_per_event() {
+        let notes = vec![note(
+            "a",
+            "tx1",
+            Pool::Sapling,
+            100_000_000,
+            "2021-01-01T00:00:00Z",
+            None,
+        )];
+        let history = build_transaction_history(&notes, None, None);
+
+        let csv = export_history_csv(&history, HistorySchema::Generic);
+
+        assert!(csv.starts_with("date,type,asset,amount,fiat_value,fee,gain\n"));
+        assert!(csv.contains("2021-01-01T00:00:00Z,received,sapling,1,,,\n"));
+    }
+}
@@ -1,19 +1,59 @@
+pub mod coin_select;
+pub mod commitment_tree;
+pub mod fee;
+pub mod fiat;
+pub mod gains;
+pub mod historical_prices;
+pub mod history;
+pub mod partial_tx;
 pub mod scanner;
+pub mod signer;
+pub mod store;
+pub mod transaction;
 pub mod types;
 pub mod wallet;
+mod zip244;
+pub mod zip321;
 
+pub use coin_select::{CoinSelection, CoinSelectionError, FeeWeight, select_utxos};
+pub use commitment_tree::{
+    CommitmentTreeError, MERKLE_DEPTH, MerklePath, OrchardTreeTracker, SaplingTreeTracker,
+    TreeTracker, read_orchard_witness, read_sapling_witness,
+};
+pub use fee::{FeeRule, zip317_fee};
+pub use fiat::{fiat_balance, value_notes};
+pub use gains::{GainsError, LotMethod, compute_gains};
+pub use historical_prices::{
+    HistoricalPrices, HistoricalPricesError, PriceCache, populate_acquired_fiat_value,
+};
+pub use history::{HistoryError, HistorySchema, build_transaction_history, export_history_csv};
+pub use partial_tx::{PartialTx, PartialTxInput, PartialTxOutput, export_unsigned, sign_partial_tx};
 pub use scanner::{
     ScannerError, extract_nullifiers, parse_transaction, parse_viewing_key_capabilities,
     scan_transaction, scan_transaction_hex,
 };
+pub use signer::{SeedSigner, TransparentSigner};
+pub use store::{JsonStore, NoteStore, SqliteStore, StoreError};
+pub use transaction::{
+    ChangeStrategy, MAX_SWEEP_INPUTS_PER_TX, Recipient, SignedTransaction, SweepTransaction,
+    TransactionError, TransparentChain, UnsignedTransaction, Utxo, build_sweep_transaction,
+    build_transparent_transaction, build_unsigned_transaction, build_unsigned_transaction_auto_select,
+    build_unsigned_transaction_with_change, find_address_index, find_address_index_on_chain,
+    sign_with_signer,
+};
 pub use types::{
-    DecryptedOrchardAction, DecryptedSaplingOutput, DecryptedTransaction, DecryptionResult,
-    DerivedAddress, NetworkKind, NoteCollection, Pool, ScanResult, ScanTransactionResult,
-    ScannedNote, ScannedTransparentOutput, SpentNullifier, StorageResult, StoredNote, StoredWallet,
-    TransparentInput, TransparentOutput, TransparentSpend, ViewingKeyInfo, WalletCollection,
-    WalletResult,
+    AccountBalance, AddressDetails, Balance, Currency, DecryptedOrchardAction,
+    DecryptedSaplingOutput, DecryptedTransaction, DecryptionResult, DerivedAddress, EventKind,
+    FiatBalance, GainRecord, GainsReport, GainsResult, HoldingTerm, InspectDetails, InspectKind,
+    InspectResult, KeyScope, MemoContents, NetworkKind, NoteCollection, NoteFiatValue, NoteId,
+    NoteStatus, Pool, PoolGainsTotals, PriceQuote, ScanResult, ScanTransactionResult, ScannedNote,
+    ScannedTransparentOutput, SpentNullifier, StorageResult, StoredNote, StoredWallet,
+    TransactionEvent, TransactionHistoryEntry, TransactionSummary, TransferDirection,
+    TransferType, TransparentInput, TransparentOutput, TransparentSpend, ViewingKeyInfo,
+    WalletCollection, WalletResult,
 };
 pub use wallet::{
-    WalletInfo, derive_transparent_addresses, derive_unified_addresses, derive_wallet,
-    generate_wallet, restore_wallet,
+    DiversifiedAddress, WalletError, WalletInfo, derive_transparent_addresses,
+    derive_unified_addresses, derive_wallet, generate_wallet, restore_wallet,
 };
+pub use zip321::{Payment, TransactionRequest, Zip321Error};
@@ -0,0 +1,243 @@
+//! A portable, serde-serializable "partial transaction" for air-gapped
+//! (offline) signing.
+//!
+//! [`export_unsigned`] turns an [`UnsignedTransaction`] built on an online,
+//! watch-only instance into a [`PartialTx`] that carries no secret key
+//! material - just enough, per input, for a separate offline device to
+//! re-derive its signing key from its BIP44 address index. That device
+//! (or the same one, later) finishes the job with [`sign_partial_tx`],
+//! mirroring the online/offline split other Zcash wallets use for
+//! cold-storage spends.
+
+use serde::{Deserialize, Serialize};
+use zcash_protocol::consensus::{BlockHeight, BranchId};
+use zcash_transparent::bundle::{OutPoint, TxOut};
+
+use crate::signer::{SeedSigner, TransparentSigner};
+use crate::transaction::{SignedTransaction, TransactionError, UnsignedTransaction};
+use crate::types::NetworkKind;
+use crate::zip244;
+
+const SIGHASH_ALL: u8 = 0x01;
+
+/// Number of blocks after `height` a transaction stays valid for, matching
+/// `transaction::build_transparent_transaction`'s default.
+const DEFAULT_EXPIRY_DELTA: u32 = 20;
+
+/// One transparent input of a [`PartialTx`]: the coin being spent, plus the
+/// BIP44 address index needed to re-derive its signing key offline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialTxInput {
+    /// Txid of the transaction that created this input, as hex.
+    pub txid: String,
+    /// Output index within that transaction.
+    pub vout: u32,
+    /// Value of the coin being spent, in zatoshis.
+    pub value: u64,
+    /// The spent coin's scriptPubKey, as hex.
+    pub script_pubkey: String,
+    /// The non-hardened BIP44 address index the input's key was derived
+    /// from (see `zcash_transparent::keys::NonHardenedChildIndex`).
+    pub address_index: u32,
+}
+
+/// One transparent output of a [`PartialTx`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialTxOutput {
+    /// Value sent by this output, in zatoshis.
+    pub value: u64,
+    /// The output's scriptPubKey, as hex.
+    pub script_pubkey: String,
+}
+
+/// A transaction skeleton with no secret key material, portable to an
+/// offline signer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialTx {
+    pub network: NetworkKind,
+    pub account: u32,
+    pub height: u32,
+    pub inputs: Vec<PartialTxInput>,
+    pub outputs: Vec<PartialTxOutput>,
+}
+
+/// Export an `UnsignedTransaction` (and the account/height it was built
+/// against) as a secret-free [`PartialTx`], ready to hand to an offline
+/// signer.
+pub fn export_unsigned(unsigned: &UnsignedTransaction, account: u32, height: u32) -> PartialTx {
+    let inputs = unsigned
+        .bundle
+        .vin
+        .iter()
+        .zip(unsigned.bundle.authorization.input_txouts())
+        .zip(unsigned.input_address_indices.iter())
+        .map(|((txin, txout), &address_index)| {
+            let mut txid_bytes = *txin.prevout.hash();
+            // `OutPoint` stores the txid already reversed to its internal
+            // (little-endian digest) form; undo that for display, matching
+            // `transaction::parse_txid`'s convention.
+            txid_bytes.reverse();
+            PartialTxInput {
+                txid: hex::encode(txid_bytes),
+                vout: txin.prevout.n(),
+                value: u64::from(txout.value()),
+                script_pubkey: hex::encode(&txout.script_pubkey().0),
+                address_index,
+            }
+        })
+        .collect();
+
+    let outputs = unsigned
+        .bundle
+        .vout
+        .iter()
+        .map(|txout| PartialTxOutput {
+            value: u64::from(txout.value()),
+            script_pubkey: hex::encode(&txout.script_pubkey().0),
+        })
+        .collect();
+
+    PartialTx {
+        network: unsigned.network.into(),
+        account,
+        height,
+        inputs,
+        outputs,
+    }
+}
+
+fn decode_script(script_hex: &str) -> Result<Vec<u8>, TransactionError> {
+    hex::decode(script_hex)
+        .map_err(|e| TransactionError::InvalidInput(format!("Invalid scriptPubKey hex: {}", e)))
+}
+
+/// Re-derive each input's signing key from `seed_phrase` and `partial`'s
+/// stored address indices, compute the ZIP 244 signature digest for each
+/// input, and return the finalized, broadcastable transaction - all
+/// without `partial` ever having carried a secret key.
+pub fn sign_partial_tx(
+    partial: PartialTx,
+    seed_phrase: &str,
+) -> Result<SignedTransaction, TransactionError> {
+    let network = partial.network.to_network();
+    let signer = SeedSigner::new(seed_phrase, network, partial.account)?;
+
+    let branch_id = u32::from(BranchId::for_height(
+        &network,
+        BlockHeight::from_u32(partial.height),
+    ));
+    let lock_time = partial.height;
+    let expiry_height = partial.height.saturating_add(DEFAULT_EXPIRY_DELTA);
+
+    let mut vin = Vec::with_capacity(partial.inputs.len());
+    for input in &partial.inputs {
+        let txid = crate::transaction::parse_txid(&input.txid)?;
+        let outpoint = OutPoint::new(*txid.as_ref(), input.vout);
+        let value = zcash_protocol::value::Zatoshis::from_u64(input.value)
+            .map_err(|_| TransactionError::InvalidInput("Invalid input value".to_string()))?;
+        let txout = TxOut::new(value, decode_script(&input.script_pubkey)?.into());
+        vin.push((outpoint, txout));
+    }
+
+    let mut vout = Vec::with_capacity(partial.outputs.len());
+    for output in &partial.outputs {
+        let value = zcash_protocol::value::Zatoshis::from_u64(output.value)
+            .map_err(|_| TransactionError::InvalidInput("Invalid output value".to_string()))?;
+        vout.push(TxOut::new(value, decode_script(&output.script_pubkey)?.into()));
+    }
+
+    let mut signed_vin = Vec::with_capacity(vin.len());
+
+    for (i, (input, (outpoint, _))) in partial.inputs.iter().zip(vin.iter()).enumerate() {
+        let sighash = zip244::signature_hash_raw(branch_id, lock_time, expiry_height, &vin, &vout, i);
+
+        let mut sig_bytes = signer.sign_input(sighash, input.address_index)?;
+        sig_bytes.push(SIGHASH_ALL);
+
+        let pubkey = signer.public_key(input.address_index)?;
+        let mut script_sig = Vec::with_capacity(sig_bytes.len() + 36);
+        zip244::write_script(&sig_bytes, &mut script_sig);
+        zip244::write_script(&pubkey.serialize(), &mut script_sig);
+
+        signed_vin.push(zip244::SignedTxIn {
+            outpoint: outpoint.clone(),
+            script_sig,
+        });
+    }
+
+    let tx_bytes = zip244::serialize_v5_transparent(branch_id, lock_time, expiry_height, &signed_vin, &vout);
+    let txid_bytes = zip244::txid_digest(branch_id, lock_time, expiry_height, &vin, &vout);
+    let mut txid_display = txid_bytes;
+    txid_display.reverse();
+
+    let total_input: u64 = partial.inputs.iter().map(|i| i.value).sum();
+    let total_output: u64 = partial.outputs.iter().map(|o| o.value).sum();
+
+    Ok(SignedTransaction {
+        tx_hex: hex::encode(tx_bytes),
+        txid: hex::encode(txid_display),
+        total_input,
+        total_output,
+        fee: total_input.saturating_sub(total_output),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::{Recipient, Utxo, build_unsigned_transaction};
+    use zcash_protocol::consensus::Network;
+
+    const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+    #[test]
+    fn test_export_then_sign_matches_direct_signing() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let utxos = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 100000,
+            address: addresses[0].clone(),
+            script_pubkey: None,
+        }];
+        let recipients = vec![Recipient {
+            address: addresses[0].clone(),
+            amount: 50000,
+        }];
+
+        let unsigned = build_unsigned_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos.clone(),
+            recipients.clone(),
+            10000,
+        )
+        .unwrap();
+
+        let partial = export_unsigned(&unsigned, 0, 2_500_000);
+        assert_eq!(partial.inputs.len(), 1);
+        assert_eq!(partial.inputs[0].address_index, 0);
+
+        let json = serde_json::to_string(&partial).unwrap();
+        let round_tripped: PartialTx = serde_json::from_str(&json).unwrap();
+
+        let signed = sign_partial_tx(round_tripped, TEST_SEED_PHRASE).unwrap();
+        let direct = crate::transaction::build_transparent_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            recipients,
+            10000,
+            2_500_000,
+        )
+        .unwrap();
+
+        assert_eq!(signed.txid, direct.txid);
+        assert_eq!(signed.tx_hex, direct.tx_hex);
+    }
+}
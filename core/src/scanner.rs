@@ -0,0 +1,712 @@
+//! Transaction scanning: trial decryption of shielded outputs and extraction
+//! of transparent/shielded spend information for a given viewing key.
+//!
+//! [`scan_transaction`] (and its hex-decoding wrapper [`scan_transaction_hex`])
+//! is the read side of the wallet: given a parsed transaction and a viewing
+//! key, it recovers every note/output that belongs to the wallet, plus the
+//! nullifiers/outpoints that indicate previously-received funds being spent.
+//! [`parse_viewing_key_capabilities`] answers the cheaper question of what a
+//! viewing key *can* see, without needing a transaction at all.
+
+use std::collections::HashMap;
+
+use orchard::keys::{
+    FullViewingKey as OrchardFvk, OutgoingViewingKey as OrchardOvk,
+    PreparedIncomingViewingKey as OrchardPreparedIvk, Scope as OrchardScope,
+};
+use orchard::note_encryption::OrchardDomain;
+use sapling_crypto::NullifierDerivingKey;
+use sapling_crypto::keys::OutgoingViewingKey as SaplingOvk;
+use sapling_crypto::note_encryption::{
+    PreparedIncomingViewingKey as SaplingPreparedIvk, try_sapling_note_decryption,
+    try_sapling_output_recovery,
+};
+use zcash_address::unified::{self, Container, Encoding};
+use zcash_keys::encoding::AddressCodec;
+use zcash_keys::keys::{UnifiedFullViewingKey, UnifiedIncomingViewingKey};
+use zcash_note_encryption::{try_note_decryption, try_output_recovery_with_ovk};
+use zcash_primitives::transaction::Transaction;
+use zcash_protocol::consensus::{BlockHeight, BranchId, Network, NetworkType};
+use zcash_transparent::address::TransparentAddress;
+
+use crate::types::{
+    KeyScope, MemoContents, NetworkKind, Pool, ScanResult, ScannedNote, ScannedTransparentOutput,
+    SpentNullifier, TransferDirection, TransferType, TransparentSpend, ViewingKeyInfo,
+};
+
+/// Errors that can occur while parsing or scanning a transaction.
+#[derive(Debug)]
+pub enum ScannerError {
+    /// The supplied transaction hex was not valid hex.
+    InvalidHex(String),
+    /// The transaction bytes didn't parse under any known consensus branch.
+    ParseFailed,
+    /// The viewing key wasn't a recognized UFVK/UIVK.
+    InvalidViewingKey(String),
+    /// The viewing key was recognized but isn't supported for this
+    /// operation (e.g. a legacy Sapling key for trial decryption).
+    UnsupportedViewingKey(String),
+}
+
+impl core::fmt::Display for ScannerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidHex(msg) => write!(f, "Invalid transaction hex: {}", msg),
+            Self::ParseFailed => write!(f, "Failed to parse transaction with any known branch ID"),
+            Self::InvalidViewingKey(msg) => write!(f, "Invalid viewing key: {}", msg),
+            Self::UnsupportedViewingKey(msg) => write!(f, "Unsupported viewing key: {}", msg),
+        }
+    }
+}
+
+impl core::error::Error for ScannerError {}
+
+/// Parse a transaction from hex, trying each post-Sapling consensus branch
+/// ID in turn since the transaction's own encoding doesn't name one.
+pub fn parse_transaction(tx_hex: &str, network: Network) -> Result<Transaction, ScannerError> {
+    let tx_bytes = hex::decode(tx_hex.trim()).map_err(|e| ScannerError::InvalidHex(e.to_string()))?;
+
+    const BRANCH_IDS: [BranchId; 4] = [
+        BranchId::Nu6,
+        BranchId::Nu5,
+        BranchId::Canopy,
+        BranchId::Heartwood,
+    ];
+
+    for branch_id in BRANCH_IDS {
+        if let Ok(tx) = Transaction::read(&tx_bytes[..], branch_id) {
+            return Ok(tx);
+        }
+    }
+
+    let _ = network;
+    Err(ScannerError::ParseFailed)
+}
+
+/// Extract nullifiers from a transaction's shielded spends/actions (these
+/// indicate previously-received notes being spent).
+pub fn extract_nullifiers(tx: &Transaction) -> Vec<SpentNullifier> {
+    let mut nullifiers = Vec::new();
+
+    if let Some(sapling_bundle) = tx.sapling_bundle() {
+        for spend in sapling_bundle.shielded_spends() {
+            nullifiers.push(SpentNullifier {
+                pool: Pool::Sapling,
+                nullifier: hex::encode(spend.nullifier().0),
+            });
+        }
+    }
+
+    if let Some(orchard_bundle) = tx.orchard_bundle() {
+        for action in orchard_bundle.actions() {
+            nullifiers.push(SpentNullifier {
+                pool: Pool::Orchard,
+                nullifier: hex::encode(action.nullifier().to_bytes()),
+            });
+        }
+    }
+
+    nullifiers
+}
+
+/// Decode a 512-byte memo field per ZIP-302: a leading `0xF6` followed by an
+/// all-zero remainder means no memo; a leading byte `<= 0xF4` means the field
+/// (trailing zero padding trimmed) is UTF-8 text; anything else (including
+/// text bytes that fail UTF-8 validation) is reserved/arbitrary data kept as
+/// raw bytes.
+pub(crate) fn decode_memo(bytes: &[u8]) -> Option<MemoContents> {
+    if bytes.is_empty() {
+        return None;
+    }
+    if bytes[0] == 0xF6 && bytes[1..].iter().all(|&b| b == 0) {
+        return Some(MemoContents::Empty);
+    }
+    if bytes[0] <= 0xF4 {
+        let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        let trimmed = &bytes[..end];
+        return Some(match std::str::from_utf8(trimmed) {
+            Ok(text) => MemoContents::Text(text.to_string()),
+            Err(_) => MemoContents::Arbitrary(bytes.to_vec()),
+        });
+    }
+    Some(MemoContents::Arbitrary(bytes.to_vec()))
+}
+
+/// Recognize a standard P2PKH or P2SH scriptPubKey and return the address
+/// it pays to. Any other script form isn't recognized and returns `None`.
+fn decode_script_pubkey(script: &[u8]) -> Option<TransparentAddress> {
+    const OP_DUP: u8 = 0x76;
+    const OP_HASH160: u8 = 0xa9;
+    const OP_EQUALVERIFY: u8 = 0x88;
+    const OP_EQUAL: u8 = 0x87;
+    const OP_CHECKSIG: u8 = 0xac;
+    const PUSH_20: u8 = 0x14;
+
+    match script {
+        [OP_DUP, OP_HASH160, PUSH_20, hash @ .., OP_EQUALVERIFY, OP_CHECKSIG] if hash.len() == 20 => {
+            Some(TransparentAddress::PublicKeyHash(hash.try_into().ok()?))
+        }
+        [OP_HASH160, PUSH_20, hash @ .., OP_EQUAL] if hash.len() == 20 => {
+            Some(TransparentAddress::ScriptHash(hash.try_into().ok()?))
+        }
+        _ => None,
+    }
+}
+
+fn network_type_to_kind(network: NetworkType) -> NetworkKind {
+    match network {
+        NetworkType::Main => NetworkKind::Mainnet,
+        NetworkType::Test => NetworkKind::Testnet,
+        NetworkType::Regtest => NetworkKind::Regtest,
+    }
+}
+
+/// Check what a viewing key can see, without scanning any transaction.
+///
+/// Unlike the decryption keys built internally by [`scan_transaction`], this
+/// decodes the key in its self-describing `zcash_address` form, so it works
+/// without the caller having to already know which network the key is for.
+pub fn parse_viewing_key_capabilities(viewing_key: &str) -> ViewingKeyInfo {
+    let viewing_key = viewing_key.trim();
+
+    if let Ok((network, ufvk)) = unified::Ufvk::decode(viewing_key) {
+        let items = ufvk.items();
+        let has_sapling = items.iter().any(|item| matches!(item, unified::Fvk::Sapling(_)));
+        let has_orchard = items.iter().any(|item| matches!(item, unified::Fvk::Orchard(_)));
+        return ViewingKeyInfo {
+            valid: true,
+            key_type: "UFVK".to_string(),
+            has_sapling,
+            has_orchard,
+            network: Some(network_type_to_kind(network)),
+            error: None,
+        };
+    }
+
+    if let Ok((network, uivk)) = unified::Uivk::decode(viewing_key) {
+        let items = uivk.items();
+        let has_sapling = items.iter().any(|item| matches!(item, unified::Ivk::Sapling(_)));
+        let has_orchard = items.iter().any(|item| matches!(item, unified::Ivk::Orchard(_)));
+        return ViewingKeyInfo {
+            valid: true,
+            key_type: "UIVK".to_string(),
+            has_sapling,
+            has_orchard,
+            network: Some(network_type_to_kind(network)),
+            error: None,
+        };
+    }
+
+    if viewing_key.starts_with("zxview") || viewing_key.starts_with("zxviews") {
+        return ViewingKeyInfo {
+            valid: false,
+            key_type: "Sapling ExtFVK".to_string(),
+            has_sapling: true,
+            has_orchard: false,
+            network: None,
+            error: Some(
+                "Legacy Sapling viewing keys aren't supported for trial decryption; \
+                 re-export a unified viewing key (UFVK/UIVK) instead."
+                    .to_string(),
+            ),
+        };
+    }
+
+    ViewingKeyInfo {
+        valid: false,
+        key_type: String::new(),
+        has_sapling: false,
+        has_orchard: false,
+        network: None,
+        error: Some("Unrecognized viewing key format".to_string()),
+    }
+}
+
+/// Decryption key material extracted from a parsed viewing key, prepared
+/// for repeated trial decryption.
+///
+/// Each shielded pool carries two scopes, matching ZIP 32's diversifiable
+/// key derivation: the external IVK recovers notes received from other
+/// wallets, while the internal IVK recovers change the wallet sent back to
+/// itself (only derivable from a full viewing key, since deriving it needs
+/// the OVK). `orchard_fvk` carries the nullifier-deriving material for a
+/// decrypted Orchard note; Sapling nullifier derivation additionally needs
+/// the note's commitment-tree position, so `sapling_nk_external`/
+/// `sapling_nk_internal` only yield a nullifier once `scan_transaction`'s
+/// `leaf_positions` map supplies one.
+///
+/// `sapling_ovk*`/`orchard_ovk*` are only ever populated from a UFVK, since a
+/// UIVK carries no outgoing viewing key and so can't recover a wallet's own
+/// *sent* outputs - only notes addressed to it. Like the IVKs, each OVK has
+/// an external and internal scope: the external OVK recovers outputs paid
+/// to someone else, the internal OVK recovers change the wallet sent back
+/// to itself - letting a spend be classified as `TransferType::Outgoing` or
+/// `TransferType::WalletInternal` rather than just "not a receive".
+struct ViewingKeys {
+    sapling_external: Option<SaplingPreparedIvk>,
+    sapling_internal: Option<SaplingPreparedIvk>,
+    sapling_nk_external: Option<NullifierDerivingKey>,
+    sapling_nk_internal: Option<NullifierDerivingKey>,
+    sapling_ovk: Option<SaplingOvk>,
+    sapling_ovk_internal: Option<SaplingOvk>,
+    orchard_external: Option<OrchardPreparedIvk>,
+    orchard_internal: Option<OrchardPreparedIvk>,
+    orchard_ovk: Option<OrchardOvk>,
+    orchard_ovk_internal: Option<OrchardOvk>,
+    orchard_fvk: Option<OrchardFvk>,
+    transparent_default_address: Option<TransparentAddress>,
+}
+
+fn parse_viewing_key(viewing_key: &str, network: Network) -> Result<ViewingKeys, ScannerError> {
+    if let Ok(ufvk) = UnifiedFullViewingKey::decode(&network, viewing_key) {
+        let sapling_external = ufvk
+            .sapling()
+            .map(|dfvk| SaplingPreparedIvk::new(&dfvk.to_ivk(zip32::Scope::External)));
+        let sapling_internal = ufvk
+            .sapling()
+            .map(|dfvk| SaplingPreparedIvk::new(&dfvk.to_ivk(zip32::Scope::Internal)));
+        let sapling_nk_external = ufvk.sapling().map(|dfvk| dfvk.to_nk(zip32::Scope::External));
+        let sapling_nk_internal = ufvk.sapling().map(|dfvk| dfvk.to_nk(zip32::Scope::Internal));
+        let sapling_ovk = ufvk.sapling().map(|dfvk| dfvk.to_ovk(zip32::Scope::External));
+        let sapling_ovk_internal = ufvk.sapling().map(|dfvk| dfvk.to_ovk(zip32::Scope::Internal));
+        let orchard_external = ufvk
+            .orchard()
+            .map(|fvk| OrchardPreparedIvk::new(&fvk.to_ivk(OrchardScope::External)));
+        let orchard_internal = ufvk
+            .orchard()
+            .map(|fvk| OrchardPreparedIvk::new(&fvk.to_ivk(OrchardScope::Internal)));
+        let orchard_ovk = ufvk.orchard().map(|fvk| fvk.to_ovk(OrchardScope::External));
+        let orchard_ovk_internal = ufvk.orchard().map(|fvk| fvk.to_ovk(OrchardScope::Internal));
+
+        let transparent_default_address = ufvk
+            .transparent()
+            .map(|tfvk| tfvk.derive_external_ivk().map(|ivk| ivk.default_address().0))
+            .transpose()
+            .map_err(|e| ScannerError::InvalidViewingKey(format!("{:?}", e)))?;
+
+        return Ok(ViewingKeys {
+            sapling_external,
+            sapling_internal,
+            sapling_nk_external,
+            sapling_nk_internal,
+            sapling_ovk,
+            sapling_ovk_internal,
+            orchard_external,
+            orchard_internal,
+            orchard_ovk,
+            orchard_ovk_internal,
+            orchard_fvk: ufvk.orchard().cloned(),
+            transparent_default_address,
+        });
+    }
+
+    if let Ok(uivk) = UnifiedIncomingViewingKey::decode(&network, viewing_key) {
+        let sapling_external = uivk.sapling().map(SaplingPreparedIvk::new);
+        let orchard_external = uivk.orchard().map(OrchardPreparedIvk::new);
+        let transparent_default_address = uivk.transparent().map(|ivk| ivk.default_address().0);
+
+        return Ok(ViewingKeys {
+            sapling_external,
+            sapling_internal: None,
+            sapling_nk_external: None,
+            sapling_nk_internal: None,
+            sapling_ovk: None,
+            sapling_ovk_internal: None,
+            orchard_external,
+            orchard_internal: None,
+            orchard_ovk: None,
+            orchard_ovk_internal: None,
+            orchard_fvk: None,
+            transparent_default_address,
+        });
+    }
+
+    if viewing_key.starts_with("zxview") || viewing_key.starts_with("zxviews") {
+        return Err(ScannerError::UnsupportedViewingKey(
+            "Legacy Sapling viewing keys aren't supported for trial decryption; \
+             re-export a unified viewing key (UFVK/UIVK) instead."
+                .to_string(),
+        ));
+    }
+
+    Err(ScannerError::InvalidViewingKey("Unrecognized viewing key format".to_string()))
+}
+
+/// Scan a transaction for notes/outputs belonging to `viewing_key`.
+///
+/// `height` picks the block height used for Sapling's ZIP 212 note-plaintext
+/// version; pass `None` when the height isn't known yet (e.g. a
+/// just-broadcast transaction) to assume current consensus rules apply.
+///
+/// `leaf_positions` maps `(pool, output_index)` to that output's leaf
+/// position in its pool's note commitment tree, as maintained by the
+/// `commitment_tree` module. Positions are needed to derive a *received*
+/// Sapling note's nullifier (Orchard's nullifier doesn't depend on tree
+/// position); pass an empty map if the caller hasn't built the tree up to
+/// this transaction yet - Sapling nullifiers for the unmapped outputs are
+/// simply left unset.
+pub fn scan_transaction(
+    tx: &Transaction,
+    viewing_key: &str,
+    network: Network,
+    height: Option<u32>,
+    leaf_positions: &HashMap<(String, usize), u64>,
+) -> Result<ScanResult, ScannerError> {
+    let txid = tx.txid().to_string();
+    let mut notes = Vec::new();
+    let mut transparent_received = 0u64;
+    let mut transparent_outputs = Vec::new();
+
+    let keys = parse_viewing_key(viewing_key, network)?;
+    let decryption_height = BlockHeight::from_u32(height.unwrap_or(u32::MAX));
+
+    if let Some(transparent_bundle) = tx.transparent_bundle() {
+        for (i, output) in transparent_bundle.vout.iter().enumerate() {
+            let value = u64::from(output.value());
+            let decoded = decode_script_pubkey(&output.script_pubkey().0);
+            let is_ours = decoded.is_some() && decoded == keys.transparent_default_address;
+            if is_ours {
+                transparent_received += value;
+            }
+            transparent_outputs.push(ScannedTransparentOutput {
+                index: i,
+                value,
+                address: decoded.map(|addr| addr.encode(&network)),
+                scope: is_ours.then_some(KeyScope::External),
+            });
+        }
+    }
+
+    // Sapling outputs: try the external IVK (funds received from elsewhere)
+    // first, then the internal IVK (change the wallet sent back to itself),
+    // then fall back to OVK-based output recovery (a payment the wallet
+    // sent to someone else). Outputs that recover under none of these
+    // aren't ours and are skipped.
+    if let Some(sapling_bundle) = tx.sapling_bundle() {
+        for (i, output) in sapling_bundle.shielded_outputs().iter().enumerate() {
+            let cmu = output.cmu();
+            let commitment = hex::encode(cmu.to_bytes());
+            let position = leaf_positions.get(&("sapling".to_string(), i)).copied();
+
+            let incoming = keys
+                .sapling_external
+                .as_ref()
+                .and_then(|ivk| try_sapling_note_decryption(&network, decryption_height, ivk, output))
+                .map(|note| (note, keys.sapling_nk_external.as_ref(), KeyScope::External))
+                .or_else(|| {
+                    keys.sapling_internal.as_ref().and_then(|ivk| {
+                        try_sapling_note_decryption(&network, decryption_height, ivk, output)
+                    }).map(|note| (note, keys.sapling_nk_internal.as_ref(), KeyScope::Internal))
+                });
+
+            let (recovered, direction, nk, scope) = match incoming {
+                Some((note, nk, scope)) => {
+                    (Some(note), Some(TransferDirection::Incoming), nk, Some(scope))
+                }
+                None => {
+                    let outgoing = keys
+                        .sapling_ovk
+                        .as_ref()
+                        .and_then(|ovk| {
+                            try_sapling_output_recovery(&network, decryption_height, ovk, output)
+                        })
+                        .map(|r| (r, None))
+                        .or_else(|| {
+                            keys.sapling_ovk_internal
+                                .as_ref()
+                                .and_then(|ovk| {
+                                    try_sapling_output_recovery(
+                                        &network,
+                                        decryption_height,
+                                        ovk,
+                                        output,
+                                    )
+                                })
+                                .map(|r| (r, Some(KeyScope::Internal)))
+                        });
+                    let direction = outgoing.is_some().then_some(TransferDirection::Outgoing);
+                    match outgoing {
+                        Some((r, scope)) => (Some(r), direction, None, scope),
+                        None => (None, direction, None, None),
+                    }
+                }
+            };
+
+            if let Some((note, address, memo)) = recovered {
+                let nullifier = match (nk, position) {
+                    (Some(nk), Some(pos)) => Some(hex::encode(note.nf(nk, pos).0)),
+                    _ => None,
+                };
+                notes.push(ScannedNote {
+                    output_index: i,
+                    pool: Pool::Sapling,
+                    value: note.value().inner(),
+                    commitment,
+                    nullifier,
+                    memo: decode_memo(memo.as_array()),
+                    address: Some(address.encode(&network)),
+                    direction,
+                    position,
+                    scope,
+                });
+            }
+        }
+    }
+
+    // Orchard actions, same external-then-internal-then-OVK strategy as Sapling.
+    if let Some(orchard_bundle) = tx.orchard_bundle() {
+        for (i, action) in orchard_bundle.actions().iter().enumerate() {
+            let cmx = action.cmx();
+            let commitment = hex::encode(cmx.to_bytes());
+            let position = leaf_positions.get(&("orchard".to_string(), i)).copied();
+
+            let domain = OrchardDomain::for_action(action);
+            let incoming = keys
+                .orchard_external
+                .as_ref()
+                .and_then(|ivk| try_note_decryption(&domain, ivk, action))
+                .map(|note| (note, KeyScope::External))
+                .or_else(|| {
+                    keys.orchard_internal
+                        .as_ref()
+                        .and_then(|ivk| try_note_decryption(&domain, ivk, action))
+                        .map(|note| (note, KeyScope::Internal))
+                });
+
+            let (recovered, direction, scope) = match incoming {
+                Some((note, scope)) => {
+                    (Some(note), Some(TransferDirection::Incoming), Some(scope))
+                }
+                None => {
+                    let outgoing = keys
+                        .orchard_ovk
+                        .as_ref()
+                        .and_then(|ovk| {
+                            try_output_recovery_with_ovk(
+                                &domain,
+                                ovk,
+                                action,
+                                &action.cv_net(),
+                                &action.encrypted_note().out_ciphertext,
+                            )
+                        })
+                        .map(|r| (r, None))
+                        .or_else(|| {
+                            keys.orchard_ovk_internal
+                                .as_ref()
+                                .and_then(|ovk| {
+                                    try_output_recovery_with_ovk(
+                                        &domain,
+                                        ovk,
+                                        action,
+                                        &action.cv_net(),
+                                        &action.encrypted_note().out_ciphertext,
+                                    )
+                                })
+                                .map(|r| (r, Some(KeyScope::Internal)))
+                        });
+                    let direction = outgoing.is_some().then_some(TransferDirection::Outgoing);
+                    match outgoing {
+                        Some((r, scope)) => (Some(r), direction, scope),
+                        None => (None, direction, None),
+                    }
+                }
+            };
+
+            if let Some((note, address, memo)) = recovered {
+                // Unlike Sapling, an Orchard note's nullifier folds in its
+                // own rho value rather than a commitment-tree position, so
+                // it's derivable as soon as the note itself is known.
+                let nullifier = keys
+                    .orchard_fvk
+                    .as_ref()
+                    .map(|fvk| hex::encode(note.nullifier(fvk).to_bytes()));
+                let encoded_address = zcash_keys::address::UnifiedAddress::from_receivers(Some(address), None, None)
+                    .map(|ua| ua.encode(&network));
+                notes.push(ScannedNote {
+                    output_index: i,
+                    pool: Pool::Orchard,
+                    value: note.value().inner(),
+                    commitment,
+                    nullifier,
+                    memo: decode_memo(&memo),
+                    address: encoded_address,
+                    direction,
+                    position,
+                    scope,
+                });
+            }
+        }
+    }
+
+    let spent_nullifiers = extract_nullifiers(tx);
+
+    let mut transparent_spends = Vec::new();
+    if let Some(transparent_bundle) = tx.transparent_bundle() {
+        for input in transparent_bundle.vin.iter() {
+            let mut txid_bytes = *input.prevout().hash();
+            // `OutPoint` stores the txid already reversed to its internal
+            // (little-endian digest) form; undo that for display, matching
+            // `transaction::parse_txid`'s convention.
+            txid_bytes.reverse();
+            transparent_spends.push(TransparentSpend {
+                prevout_txid: hex::encode(txid_bytes),
+                prevout_index: input.prevout().n(),
+            });
+        }
+    }
+
+    let transfer_type = classify_transfer_type(&notes);
+
+    Ok(ScanResult {
+        txid,
+        notes,
+        spent_nullifiers,
+        transparent_spends,
+        transparent_received,
+        transparent_outputs,
+        transfer_type,
+    })
+}
+
+/// Classify a transaction's overall nature from its recovered notes: a
+/// payment to someone else (we sent an output recovered via the *external*
+/// OVK) outweighs any accompanying change, since the transaction still
+/// represents a disposition; failing that, an output recovered via the
+/// *internal* OVK means every output we sent was change back to ourselves;
+/// no outgoing-direction note at all means we only received.
+fn classify_transfer_type(notes: &[ScannedNote]) -> TransferType {
+    let mut saw_internal_outgoing = false;
+    for note in notes {
+        if note.direction != Some(TransferDirection::Outgoing) {
+            continue;
+        }
+        if note.scope == Some(KeyScope::Internal) {
+            saw_internal_outgoing = true;
+        } else {
+            return TransferType::Outgoing;
+        }
+    }
+    if saw_internal_outgoing {
+        TransferType::WalletInternal
+    } else {
+        TransferType::Incoming
+    }
+}
+
+/// Parse `raw_tx_hex` and scan it for notes/outputs belonging to
+/// `viewing_key` in one step. See `scan_transaction` for `leaf_positions`.
+pub fn scan_transaction_hex(
+    raw_tx_hex: &str,
+    viewing_key: &str,
+    network: Network,
+    height: Option<u32>,
+    leaf_positions: &HashMap<(String, usize), u64>,
+) -> Result<ScanResult, ScannerError> {
+    let tx = parse_transaction(raw_tx_hex, network)?;
+    scan_transaction(&tx, viewing_key, network, height, leaf_positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_UFVK: &str = "uviewtest1w4wqdd4qw09p5hwll0u5wgl9m359nzn0z5hevyllf9ymg7a2ep7ndk5rhh4gut0gaanep78eylutxdua5unlpcpj8gvh9tjwf7r20de8074g7g6ywvawjuhuxc0hlsxezvn64cdsr49pcyzncjx5q084fcnk9qwa2hj5ae3dplstlg9yv950hgs9jjfnxvtcvu79mdrq66ajh62t5zrvp8tqkqsgh8r4xa6dr2v0mdruac46qk4hlddm58h3khmrrn8awwdm20vfxsr9n6a94vkdf3dzyfpdul558zgxg80kkgth4ghzudd7nx5gvry49sxs78l9xft0lme0llmc5pkh0a4dv4ju6xv4a2y7xh6ekrnehnyrhwcfnpsqw4qwwm3q6c8r02fnqxt9adqwuj5hyzedt9ms9sk0j35ku7j6sm6z0m2x4cesch6nhe9ln44wpw8e7nnyak0up92d6mm6dwdx4r60pyaq7k8vj0r2neqxtqmsgcrd";
+
+    #[test]
+    fn test_parse_viewing_key_capabilities_ufvk() {
+        let info = parse_viewing_key_capabilities(TEST_UFVK);
+        assert!(info.valid);
+        assert_eq!(info.key_type, "UFVK");
+        assert!(info.has_sapling);
+        assert!(info.has_orchard);
+        assert_eq!(info.network, Some(NetworkKind::Testnet));
+    }
+
+    #[test]
+    fn test_parse_viewing_key_capabilities_rejects_garbage() {
+        let info = parse_viewing_key_capabilities("not a viewing key");
+        assert!(!info.valid);
+        assert!(info.error.is_some());
+    }
+
+    #[test]
+    fn test_parse_transaction_rejects_invalid_hex() {
+        let err = parse_transaction("not hex", Network::TestNetwork).unwrap_err();
+        assert!(matches!(err, ScannerError::InvalidHex(_)));
+    }
+
+    #[test]
+    fn test_extract_nullifiers_empty_for_transparent_only_transaction() {
+        let tx = parse_transaction(
+            &hex::encode(crate::zip244::serialize_v5_transparent(
+                u32::from(BranchId::Nu5),
+                0,
+                500_000,
+                &[],
+                &[],
+            )),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        assert!(extract_nullifiers(&tx).is_empty());
+    }
+
+    #[test]
+    fn test_scan_transaction_rejects_unrecognized_viewing_key() {
+        let tx = parse_transaction(
+            &hex::encode(crate::zip244::serialize_v5_transparent(
+                u32::from(BranchId::Nu5),
+                0,
+                500_000,
+                &[],
+                &[],
+            )),
+            Network::TestNetwork,
+        )
+        .unwrap();
+        let err =
+            scan_transaction(&tx, "garbage", Network::TestNetwork, None, &HashMap::new())
+                .unwrap_err();
+        assert!(matches!(err, ScannerError::InvalidViewingKey(_)));
+    }
+
+    fn note(direction: Option<TransferDirection>, scope: Option<KeyScope>) -> ScannedNote {
+        ScannedNote {
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 1000,
+            commitment: "cmx".to_string(),
+            nullifier: None,
+            memo: None,
+            address: None,
+            direction,
+            position: None,
+            scope,
+        }
+    }
+
+    #[test]
+    fn test_classify_transfer_type_receive_only_is_incoming() {
+        let notes = vec![note(Some(TransferDirection::Incoming), Some(KeyScope::External))];
+        assert_eq!(classify_transfer_type(&notes), TransferType::Incoming);
+    }
+
+    #[test]
+    fn test_classify_transfer_type_all_change_is_wallet_internal() {
+        let notes = vec![
+            note(Some(TransferDirection::Incoming), Some(KeyScope::Internal)),
+            note(Some(TransferDirection::Outgoing), Some(KeyScope::Internal)),
+        ];
+        assert_eq!(classify_transfer_type(&notes), TransferType::WalletInternal);
+    }
+
+    #[test]
+    fn test_classify_transfer_type_payment_to_others_is_outgoing() {
+        let notes = vec![
+            note(Some(TransferDirection::Outgoing), None),
+            note(Some(TransferDirection::Outgoing), Some(KeyScope::Internal)),
+        ];
+        assert_eq!(classify_transfer_type(&notes), TransferType::Outgoing);
+    }
+}
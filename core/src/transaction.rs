@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize};
 use zcash_keys::encoding::AddressCodec;
 use zcash_keys::keys::UnifiedSpendingKey;
 use zcash_primitives::transaction::TxId;
-use zcash_protocol::consensus::Network;
+use zcash_protocol::consensus::{BlockHeight, BranchId, Network};
 use zcash_protocol::value::Zatoshis;
 use zcash_transparent::address::TransparentAddress;
 use zcash_transparent::builder::{TransparentBuilder, TransparentSigningSet};
@@ -17,7 +17,11 @@ use zcash_transparent::bundle::{OutPoint, TxOut};
 use zcash_transparent::keys::{AccountPrivKey, IncomingViewingKey, NonHardenedChildIndex};
 use zip32::AccountId;
 
+use crate::coin_select;
+use crate::fee::{self, FeeRule};
+use crate::signer::{SeedSigner, TransparentSigner};
 use crate::types::{Pool, StoredNote};
+use crate::zip244;
 
 /// Errors that can occur during transaction operations.
 #[derive(Debug)]
@@ -148,6 +152,11 @@ pub struct UnsignedTransaction {
     pub bundle: zcash_transparent::bundle::Bundle<zcash_transparent::builder::Unauthorized>,
     /// Signing keys collected during building.
     pub signing_set: TransparentSigningSet,
+    /// The secret key for each input in `bundle.vin`, in the same order.
+    pub input_secret_keys: Vec<secp256k1::SecretKey>,
+    /// The BIP44 non-hardened address index each input's key was derived
+    /// from, in the same order as `bundle.vin`.
+    pub input_address_indices: Vec<u32>,
     /// Total input value in zatoshis.
     pub total_input: u64,
     /// Total output value in zatoshis (excluding fee).
@@ -158,7 +167,17 @@ pub struct UnsignedTransaction {
     pub network: Network,
 }
 
-/// Find the address index for a given transparent address.
+/// Which BIP44 transparent chain an address was derived on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransparentChain {
+    /// Chain 0: receiving addresses handed out to counterparties.
+    External,
+    /// Chain 1: change addresses, never handed out.
+    Internal,
+}
+
+/// Find the address index for a given transparent address on a specific
+/// BIP44 chain.
 ///
 /// This function iterates through address indices (0 to max_index) to find
 /// which index produces the given address.
@@ -168,16 +187,19 @@ pub struct UnsignedTransaction {
 /// * `seed_phrase` - The wallet's seed phrase
 /// * `network` - The network (mainnet or testnet)
 /// * `account` - The account index
+/// * `chain` - Whether to search the external (receiving) or internal
+///   (change) chain
 /// * `address` - The transparent address to find
 /// * `max_index` - Maximum index to search (default 1000)
 ///
 /// # Returns
 ///
 /// The address index if found, or None.
-pub fn find_address_index(
+pub fn find_address_index_on_chain(
     seed_phrase: &str,
     network: Network,
     account: u32,
+    chain: TransparentChain,
     address: &str,
     max_index: u32,
 ) -> Option<u32> {
@@ -189,7 +211,10 @@ pub fn find_address_index(
     let ufvk = usk.to_unified_full_viewing_key();
 
     let tfvk = ufvk.transparent()?;
-    let ivk = tfvk.derive_external_ivk().ok()?;
+    let ivk = match chain {
+        TransparentChain::External => tfvk.derive_external_ivk().ok()?,
+        TransparentChain::Internal => tfvk.derive_internal_ivk().ok()?,
+    };
 
     for i in 0..max_index {
         if let Some(child_index) = NonHardenedChildIndex::from_index(i) {
@@ -205,8 +230,40 @@ pub fn find_address_index(
     None
 }
 
+/// Find the address index for a given transparent address on the external
+/// (receiving) chain. See [`find_address_index_on_chain`] to also search the
+/// internal (change) chain.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - The wallet's seed phrase
+/// * `network` - The network (mainnet or testnet)
+/// * `account` - The account index
+/// * `address` - The transparent address to find
+/// * `max_index` - Maximum index to search (default 1000)
+///
+/// # Returns
+///
+/// The address index if found, or None.
+pub fn find_address_index(
+    seed_phrase: &str,
+    network: Network,
+    account: u32,
+    address: &str,
+    max_index: u32,
+) -> Option<u32> {
+    find_address_index_on_chain(
+        seed_phrase,
+        network,
+        account,
+        TransparentChain::External,
+        address,
+        max_index,
+    )
+}
+
 /// Derive the transparent account private key.
-fn derive_transparent_account_key(
+pub(crate) fn derive_transparent_account_key(
     seed_phrase: &str,
     network: Network,
     account: u32,
@@ -223,6 +280,39 @@ fn derive_transparent_account_key(
     Ok(usk.transparent().clone())
 }
 
+/// Derive a transparent address on the account's internal (BIP44 change)
+/// chain at `index`.
+fn derive_internal_address(
+    seed_phrase: &str,
+    network: Network,
+    account: u32,
+    index: u32,
+) -> Result<TransparentAddress, TransactionError> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .map_err(|e| TransactionError::InvalidSeedPhrase(e.to_string()))?;
+    let seed = mnemonic.to_seed("");
+
+    let account_id = AccountId::try_from(account)
+        .map_err(|_| TransactionError::SpendingKeyDerivation("Invalid account index".to_string()))?;
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account_id)
+        .map_err(|e| TransactionError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let tfvk = ufvk
+        .transparent()
+        .ok_or_else(|| TransactionError::SpendingKeyDerivation("Account has no transparent keys".to_string()))?;
+    let ivk = tfvk
+        .derive_internal_ivk()
+        .map_err(|e| TransactionError::SpendingKeyDerivation(format!("{:?}", e)))?;
+
+    let child_index = NonHardenedChildIndex::from_index(index).ok_or_else(|| {
+        TransactionError::InvalidInput(format!("Invalid address index: {}", index))
+    })?;
+
+    ivk.derive_address(child_index)
+        .map_err(|e| TransactionError::SpendingKeyDerivation(format!("{:?}", e)))
+}
+
 /// Parse a transparent address from a string.
 fn parse_transparent_address(
     address: &str,
@@ -233,7 +323,7 @@ fn parse_transparent_address(
 }
 
 /// Parse a transaction ID from a hex string.
-fn parse_txid(txid_hex: &str) -> Result<TxId, TransactionError> {
+pub(crate) fn parse_txid(txid_hex: &str) -> Result<TxId, TransactionError> {
     let bytes = hex::decode(txid_hex)
         .map_err(|e| TransactionError::InvalidInput(format!("Invalid txid hex: {}", e)))?;
 
@@ -252,10 +342,29 @@ fn parse_txid(txid_hex: &str) -> Result<TxId, TransactionError> {
     Ok(TxId::from_bytes(txid_bytes))
 }
 
+/// Where a transaction's leftover change is sent, see
+/// [`build_unsigned_transaction_with_change`].
+#[derive(Debug, Clone, Copy)]
+pub enum ChangeStrategy {
+    /// Send change back to the first spent UTXO's address. Simple, but
+    /// links the change output to a spent (now-public) address - prefer
+    /// `InternalChain` unless a caller has a specific reason to reuse it.
+    ReuseFirstInputAddress,
+    /// Derive a fresh address on the account's internal (BIP44 change)
+    /// chain at `index` and send change there, keeping it unlinked from any
+    /// input address. The caller is responsible for choosing an `index`
+    /// that hasn't been handed out before - this module has no visibility
+    /// into which internal addresses a wallet has already used.
+    InternalChain { index: u32 },
+}
+
 /// Build an unsigned transparent transaction.
 ///
 /// This creates the transaction structure and collects signing keys,
-/// but does not compute sighashes or apply signatures.
+/// but does not compute sighashes or apply signatures. Change is sent to a
+/// fresh address on the account's internal (change) chain; use
+/// [`build_unsigned_transaction_with_change`] to reuse the first input's
+/// address instead.
 ///
 /// # Arguments
 ///
@@ -276,6 +385,42 @@ pub fn build_unsigned_transaction(
     utxos: Vec<Utxo>,
     recipients: Vec<Recipient>,
     fee: u64,
+) -> Result<UnsignedTransaction, TransactionError> {
+    build_unsigned_transaction_with_change(
+        seed_phrase,
+        network,
+        account,
+        utxos,
+        recipients,
+        fee,
+        ChangeStrategy::InternalChain { index: 0 },
+    )
+}
+
+/// Like [`build_unsigned_transaction`], but with explicit control over
+/// where leftover change is sent - see [`ChangeStrategy`].
+///
+/// # Arguments
+///
+/// * `seed_phrase` - The wallet's seed phrase
+/// * `network` - The network (mainnet or testnet)
+/// * `account` - The account index
+/// * `utxos` - The UTXOs to spend
+/// * `recipients` - The recipients and amounts
+/// * `fee` - The transaction fee in zatoshis
+/// * `change_strategy` - Where to send leftover change, if any
+///
+/// # Returns
+///
+/// An `UnsignedTransaction` containing the bundle and signing keys.
+pub fn build_unsigned_transaction_with_change(
+    seed_phrase: &str,
+    network: Network,
+    account: u32,
+    utxos: Vec<Utxo>,
+    recipients: Vec<Recipient>,
+    fee: u64,
+    change_strategy: ChangeStrategy,
 ) -> Result<UnsignedTransaction, TransactionError> {
     // Validate inputs
     if utxos.is_empty() {
@@ -307,27 +452,42 @@ pub fn build_unsigned_transaction(
     // Build the transparent bundle and collect signing keys
     let mut builder = TransparentBuilder::empty();
     let mut signing_set = TransparentSigningSet::new();
+    let mut input_secret_keys = Vec::with_capacity(utxos.len());
+    let mut input_address_indices = Vec::with_capacity(utxos.len());
 
     // Add inputs
     for utxo in &utxos {
-        // Find the address index for this UTXO
-        let address_index = find_address_index(seed_phrase, network, account, &utxo.address, 1000)
-            .ok_or_else(|| TransactionError::AddressNotFound(utxo.address.clone()))?;
+        // Find the address index for this UTXO, checking the external
+        // (receiving) chain first and falling back to the internal (change)
+        // chain so a previously-issued change output can be spent too.
+        let (chain, address_index) =
+            match find_address_index_on_chain(seed_phrase, network, account, TransparentChain::External, &utxo.address, 1000) {
+                Some(index) => (TransparentChain::External, index),
+                None => {
+                    let index = find_address_index_on_chain(seed_phrase, network, account, TransparentChain::Internal, &utxo.address, 1000)
+                        .ok_or_else(|| TransactionError::AddressNotFound(utxo.address.clone()))?;
+                    (TransparentChain::Internal, index)
+                }
+            };
 
         let child_index = NonHardenedChildIndex::from_index(address_index).ok_or_else(|| {
             TransactionError::InvalidInput(format!("Invalid address index: {}", address_index))
         })?;
 
         // Derive the secret key and compute the public key
-        let secret_key = account_privkey
-            .derive_external_secret_key(child_index)
-            .map_err(|e| TransactionError::SpendingKeyDerivation(format!("{:?}", e)))?;
+        let secret_key = match chain {
+            TransparentChain::External => account_privkey.derive_external_secret_key(child_index),
+            TransparentChain::Internal => account_privkey.derive_internal_secret_key(child_index),
+        }
+        .map_err(|e| TransactionError::SpendingKeyDerivation(format!("{:?}", e)))?;
 
         let secp = secp256k1::Secp256k1::new();
         let pubkey = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
 
         // Add the secret key to the signing set
-        signing_set.add_key(secret_key);
+        input_secret_keys.push(secret_key);
+        input_address_indices.push(address_index);
+        signing_set.add_key(secret_key.clone());
 
         // Parse the outpoint
         let txid = parse_txid(&utxo.txid)?;
@@ -362,8 +522,12 @@ pub fn build_unsigned_transaction(
     // Add change output if needed
     let change = total_input - required;
     if change > 0 {
-        // Send change back to the first input address
-        let change_address = parse_transparent_address(&utxos[0].address, network)?;
+        let change_address = match change_strategy {
+            ChangeStrategy::ReuseFirstInputAddress => parse_transparent_address(&utxos[0].address, network)?,
+            ChangeStrategy::InternalChain { index } => {
+                derive_internal_address(seed_phrase, network, account, index)?
+            }
+        };
         let change_value = Zatoshis::from_u64(change)
             .map_err(|_| TransactionError::InvalidOutput("Invalid change value".to_string()))?;
 
@@ -380,6 +544,8 @@ pub fn build_unsigned_transaction(
     Ok(UnsignedTransaction {
         bundle: unsigned_bundle,
         signing_set,
+        input_secret_keys,
+        input_address_indices,
         total_input,
         total_output,
         fee,
@@ -387,11 +553,210 @@ pub fn build_unsigned_transaction(
     })
 }
 
-/// Build and sign a transparent transaction.
+/// Default number of blocks after `height` that the transaction remains
+/// valid for, matching the delta used by `zcashd`/`zecwallet-lite`.
+const DEFAULT_EXPIRY_DELTA: u32 = 20;
+
+/// Maximum number of coin-selection/fee-recomputation rounds
+/// [`build_unsigned_transaction_auto_select`] will run under
+/// `FeeRule::Zip317` before giving up.
+const MAX_ZIP317_ITERATIONS: u32 = 10;
+
+fn coin_selection_error(e: coin_select::CoinSelectionError) -> TransactionError {
+    match e {
+        coin_select::CoinSelectionError::InsufficientFunds { available, required } => {
+            TransactionError::InsufficientFunds { available, required }
+        }
+    }
+}
+
+/// Build an unsigned transparent transaction, automatically choosing which
+/// `spendable` UTXOs to spend via [`coin_select::select_utxos`] instead of
+/// requiring the caller to pre-select inputs and a fixed fee.
 ///
-/// Note: This function is currently limited. Full transparent transaction signing
-/// requires computing sighashes according to ZIP 244, which requires the full
-/// transaction context. This will be implemented in a future version.
+/// Under `FeeRule::Zip317`, the fee depends on the number of inputs and
+/// outputs the selection ends up with, which in turn depends on the fee -
+/// so selection and fee computation are run back to back, feeding each
+/// round's input count into the next round's fee estimate, until the input
+/// count stops changing (or `MAX_ZIP317_ITERATIONS` is exhausted). The fee
+/// actually used is then recomputed from that input count and the output
+/// count including any change output.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - The wallet's seed phrase
+/// * `network` - The network (mainnet or testnet)
+/// * `account` - The account index
+/// * `spendable` - The full set of spendable UTXOs to select from (e.g.
+///   from `Utxo::from_stored_notes`)
+/// * `recipients` - The recipients and amounts
+/// * `fee_rule` - How to compute the fee: a flat `Fixed` amount, or the
+///   size-dependent ZIP 317 conventional fee
+/// * `dust_threshold` - Leftover change below this is folded into the fee
+///
+/// # Returns
+///
+/// An `UnsignedTransaction` containing the bundle and signing keys, built
+/// from whichever inputs `select_utxos` chose.
+pub fn build_unsigned_transaction_auto_select(
+    seed_phrase: &str,
+    network: Network,
+    account: u32,
+    spendable: Vec<Utxo>,
+    recipients: Vec<Recipient>,
+    fee_rule: FeeRule,
+    dust_threshold: u64,
+) -> Result<UnsignedTransaction, TransactionError> {
+    let target: u64 = recipients.iter().map(|r| r.amount).sum();
+    let num_recipient_outputs = recipients.len() as u32;
+
+    match fee_rule {
+        FeeRule::Fixed(flat_fee) => {
+            let selection = coin_select::select_utxos(
+                &spendable,
+                target,
+                num_recipient_outputs,
+                coin_select::FeeWeight { per_input: 0, per_output: 0 },
+                flat_fee,
+                dust_threshold,
+            )
+            .map_err(coin_selection_error)?;
+
+            build_unsigned_transaction(
+                seed_phrase,
+                network,
+                account,
+                selection.selected,
+                recipients,
+                selection.fee,
+            )
+        }
+        FeeRule::Zip317 => {
+            let weight = coin_select::FeeWeight {
+                per_input: fee::ZIP317_MARGINAL_FEE,
+                per_output: fee::ZIP317_MARGINAL_FEE,
+            };
+
+            let mut num_inputs_guess = 1u64;
+            for _ in 0..MAX_ZIP317_ITERATIONS {
+                let fee_guess = fee::zip317_fee(num_inputs_guess, u64::from(num_recipient_outputs));
+
+                let selection =
+                    coin_select::select_utxos(&spendable, target, num_recipient_outputs, weight, fee_guess, dust_threshold)
+                        .map_err(coin_selection_error)?;
+
+                let num_inputs = selection.selected.len() as u64;
+                let num_outputs =
+                    u64::from(num_recipient_outputs) + if selection.change > 0 { 1 } else { 0 };
+                let actual_fee = fee::zip317_fee(num_inputs, num_outputs);
+
+                if num_inputs == num_inputs_guess {
+                    return build_unsigned_transaction(
+                        seed_phrase,
+                        network,
+                        account,
+                        selection.selected,
+                        recipients,
+                        actual_fee,
+                    );
+                }
+                num_inputs_guess = num_inputs;
+            }
+
+            Err(TransactionError::BuildFailed(
+                "ZIP 317 fee computation did not converge on a stable input count".to_string(),
+            ))
+        }
+    }
+}
+
+/// Finish an `UnsignedTransaction` into a broadcastable [`SignedTransaction`],
+/// obtaining each input's signature from `signer` rather than handling key
+/// material directly.
+///
+/// This is where the ZIP 244 signature digest for each input is computed
+/// and scriptSigs are assembled - see the [`zip244`] module for the digest
+/// computation itself. Only the signature itself comes from `signer`, which
+/// lets an external device (e.g. a Ledger-style HSM) produce it without the
+/// seed phrase ever entering this crate.
+///
+/// # Arguments
+///
+/// * `unsigned` - The transaction structure built by `build_unsigned_transaction`
+/// * `signer` - Produces each input's public key and ECDSA signature, by
+///   BIP44 address index
+/// * `height` - The current chain height, used to pick the consensus branch
+///   id and as the basis for `nLockTime`/`nExpiryHeight`
+///
+/// # Returns
+///
+/// A `SignedTransaction` containing the signed transaction hex.
+pub fn sign_with_signer(
+    unsigned: &UnsignedTransaction,
+    signer: &dyn TransparentSigner,
+    height: u32,
+) -> Result<SignedTransaction, TransactionError> {
+    let branch_id = u32::from(BranchId::for_height(&unsigned.network, BlockHeight::from_u32(height)));
+    let lock_time = height;
+    let expiry_height = height.saturating_add(DEFAULT_EXPIRY_DELTA);
+
+    let mut signed_vin = Vec::with_capacity(unsigned.bundle.vin.len());
+
+    for (i, &address_index) in unsigned.input_address_indices.iter().enumerate() {
+        let sighash = zip244::signature_hash(&unsigned.bundle, branch_id, lock_time, expiry_height, i);
+
+        let mut sig_bytes = signer.sign_input(sighash, address_index)?;
+        sig_bytes.push(SIGHASH_ALL);
+
+        let pubkey = signer.public_key(address_index)?;
+        let mut script_sig = Vec::with_capacity(sig_bytes.len() + 36);
+        zip244::write_script(&sig_bytes, &mut script_sig);
+        zip244::write_script(&pubkey.serialize(), &mut script_sig);
+
+        signed_vin.push(zip244::SignedTxIn {
+            outpoint: unsigned.bundle.vin[i].prevout.clone(),
+            script_sig,
+        });
+    }
+
+    let tx_bytes = zip244::serialize_v5_transparent(
+        branch_id,
+        lock_time,
+        expiry_height,
+        &signed_vin,
+        &unsigned.bundle.vout,
+    );
+
+    let txid_bytes = zip244::txid_digest(
+        branch_id,
+        lock_time,
+        expiry_height,
+        &signed_vin
+            .iter()
+            .zip(unsigned.bundle.authorization.input_txouts())
+            .map(|(signed, txout)| (signed.outpoint.clone(), txout.clone()))
+            .collect::<Vec<_>>(),
+        &unsigned.bundle.vout,
+    );
+    // Zcash displays txids byte-reversed relative to their internal
+    // (little-endian digest) representation, matching `parse_txid` above.
+    let mut txid_display = txid_bytes;
+    txid_display.reverse();
+
+    Ok(SignedTransaction {
+        tx_hex: hex::encode(tx_bytes),
+        txid: hex::encode(txid_display),
+        total_input: unsigned.total_input,
+        total_output: unsigned.total_output,
+        fee: unsigned.fee,
+    })
+}
+
+/// Build and sign a transparent transaction using a [`SeedSigner`] derived
+/// from `seed_phrase` - the default, in-process signing path. Callers that
+/// need to sign with an external device should instead build with
+/// `build_unsigned_transaction` and finish with [`sign_with_signer`] and
+/// their own `TransparentSigner`.
 ///
 /// # Arguments
 ///
@@ -401,6 +766,8 @@ pub fn build_unsigned_transaction(
 /// * `utxos` - The UTXOs to spend
 /// * `recipients` - The recipients and amounts
 /// * `fee` - The transaction fee in zatoshis
+/// * `height` - The current chain height, used to pick the consensus branch
+///   id and as the basis for `nLockTime`/`nExpiryHeight`
 ///
 /// # Returns
 ///
@@ -412,41 +779,106 @@ pub fn build_transparent_transaction(
     utxos: Vec<Utxo>,
     recipients: Vec<Recipient>,
     fee: u64,
+    height: u32,
 ) -> Result<SignedTransaction, TransactionError> {
-    // Build the unsigned transaction
     let unsigned = build_unsigned_transaction(seed_phrase, network, account, utxos, recipients, fee)?;
+    let signer = SeedSigner::new(seed_phrase, network, account)?;
+    sign_with_signer(&unsigned, &signer, height)
+}
 
-    // Note: Full signing requires integrating with zcash_primitives transaction builder
-    // or implementing the ZIP 244 sighash computation manually.
-    //
-    // The transparent bundle's apply_signatures() method requires a sighash calculator
-    // that needs the full transaction context (version, lock_time, expiry_height, etc.)
-    // which is not available when building just the transparent component.
-    //
-    // Options for completing this implementation:
-    // 1. Use zcash_primitives::transaction::builder::Builder with mock provers
-    // 2. Implement ZIP 244 sighash computation for transparent-only v5 transactions
-    // 3. Wait for upstream support for transparent-only transaction building
-    //
-    // For now, return an informative error.
-
-    Err(TransactionError::BuildFailed(
-        format!(
-            "Transaction building succeeded (inputs: {} zatoshis, outputs: {} zatoshis, fee: {} zatoshis), \
-             but signing is not yet fully implemented. \
-             The transparent bundle has been constructed with {} inputs and outputs are ready. \
-             Full signing requires ZIP 244 sighash computation which is tracked in issue #70.",
-            unsigned.total_input,
-            unsigned.total_output,
-            unsigned.fee,
-            unsigned.bundle.vin.len()
-        )
-    ))
+/// Maximum number of UTXOs swept into a single transaction by
+/// [`build_sweep_transaction`]; larger UTXO sets are split across multiple
+/// sweep transactions rather than growing one transaction without bound.
+pub const MAX_SWEEP_INPUTS_PER_TX: usize = 200;
+
+/// One transaction produced by [`build_sweep_transaction`], plus the number
+/// of inputs it consumed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepTransaction {
+    /// The signed, broadcastable sweep transaction.
+    pub signed: SignedTransaction,
+    /// How many UTXOs this transaction consumed.
+    pub input_count: usize,
+}
+
+/// Consolidate many transparent UTXOs into a single `destination` output -
+/// useful for cleaning up dust accumulated across many addresses before
+/// tax-year reporting.
+///
+/// `utxos` is split into batches of at most [`MAX_SWEEP_INPUTS_PER_TX`]
+/// inputs. Each batch becomes its own transaction with a single output
+/// (`total_input - fee`, no change) sent to `destination`; the fee for each
+/// batch is the ZIP 317 conventional fee for that batch's input count and a
+/// single output.
+///
+/// # Arguments
+///
+/// * `seed_phrase` - The wallet's seed phrase
+/// * `network` - The network (mainnet or testnet)
+/// * `account` - The account index
+/// * `utxos` - The UTXOs to sweep
+/// * `destination` - Where to send the swept funds
+/// * `height` - The current chain height, used for the consensus branch id
+///   and `nLockTime`/`nExpiryHeight` of every resulting transaction
+///
+/// # Returns
+///
+/// One [`SweepTransaction`] per batch, in the same order as `utxos`.
+pub fn build_sweep_transaction(
+    seed_phrase: &str,
+    network: Network,
+    account: u32,
+    utxos: Vec<Utxo>,
+    destination: &str,
+    height: u32,
+) -> Result<Vec<SweepTransaction>, TransactionError> {
+    if utxos.is_empty() {
+        return Err(TransactionError::InvalidInput(
+            "At least one UTXO is required".to_string(),
+        ));
+    }
+
+    let mut results = Vec::with_capacity(utxos.len().div_ceil(MAX_SWEEP_INPUTS_PER_TX));
+
+    for batch in utxos.chunks(MAX_SWEEP_INPUTS_PER_TX) {
+        let input_count = batch.len();
+        let total_input: u64 = batch.iter().map(|u| u.value).sum();
+        let fee_amount = fee::zip317_fee(input_count as u64, 1);
+
+        if total_input <= fee_amount {
+            return Err(TransactionError::InsufficientFunds {
+                available: total_input,
+                required: fee_amount + 1,
+            });
+        }
+
+        let recipients = vec![Recipient {
+            address: destination.to_string(),
+            amount: total_input - fee_amount,
+        }];
+
+        let signed = build_transparent_transaction(
+            seed_phrase,
+            network,
+            account,
+            batch.to_vec(),
+            recipients,
+            fee_amount,
+            height,
+        )?;
+
+        results.push(SweepTransaction { signed, input_count });
+    }
+
+    Ok(results)
 }
 
+const SIGHASH_ALL: u8 = 0x01;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{KeyScope, NoteId, NoteStatus};
 
     const TEST_SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
 
@@ -501,6 +933,7 @@ mod tests {
             utxos,
             recipients,
             1000,
+            2_500_000,
         );
 
         match result {
@@ -512,6 +945,335 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_auto_select_with_zip317_fee_rule_converges() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let spendable = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 200_000,
+            address: addresses[0].clone(),
+            script_pubkey: None,
+        }];
+        let recipients = vec![Recipient {
+            address: addresses[0].clone(),
+            amount: 50_000,
+        }];
+
+        let unsigned = build_unsigned_transaction_auto_select(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            spendable,
+            recipients,
+            FeeRule::Zip317,
+            100,
+        )
+        .unwrap();
+
+        // 1 input, 2 outputs (payment + change) -> the ZIP 317 grace minimum.
+        assert_eq!(unsigned.fee, crate::fee::zip317_fee(1, 2));
+        assert_eq!(unsigned.bundle.vin.len(), 1);
+        assert_eq!(unsigned.bundle.vout.len(), 2);
+    }
+
+    #[test]
+    fn test_auto_select_with_fixed_fee_rule() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let spendable = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 200_000,
+            address: addresses[0].clone(),
+            script_pubkey: None,
+        }];
+        let recipients = vec![Recipient {
+            address: addresses[0].clone(),
+            amount: 50_000,
+        }];
+
+        let unsigned = build_unsigned_transaction_auto_select(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            spendable,
+            recipients,
+            FeeRule::Fixed(1000),
+            100,
+        )
+        .unwrap();
+
+        assert_eq!(unsigned.fee, 1000);
+    }
+
+    #[test]
+    fn test_build_transparent_transaction_is_deterministic() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let make_utxos = || {
+            vec![Utxo {
+                txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+                vout: 0,
+                value: 100000,
+                address: addresses[0].clone(),
+                script_pubkey: None,
+            }]
+        };
+        let make_recipients = || {
+            vec![Recipient {
+                address: addresses[0].clone(),
+                amount: 50000,
+            }]
+        };
+
+        let a = build_transparent_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            make_utxos(),
+            make_recipients(),
+            10000,
+            2_500_000,
+        )
+        .unwrap();
+        let b = build_transparent_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            make_utxos(),
+            make_recipients(),
+            10000,
+            2_500_000,
+        )
+        .unwrap();
+
+        assert_eq!(a.txid, b.txid);
+        assert_eq!(a.tx_hex, b.tx_hex);
+        assert_eq!(a.total_input, 100000);
+        assert_eq!(a.total_output, 50000);
+        assert_eq!(a.fee, 10000);
+    }
+
+    #[test]
+    fn test_transparent_input_signature_validates_against_derived_pubkey() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let utxos = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 100000,
+            address: addresses[0].clone(),
+            script_pubkey: None,
+        }];
+        let recipients = vec![Recipient {
+            address: addresses[0].clone(),
+            amount: 50000,
+        }];
+
+        let unsigned = build_unsigned_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            recipients,
+            10000,
+        )
+        .unwrap();
+
+        let height = 2_500_000u32;
+        let branch_id = u32::from(BranchId::for_height(
+            &Network::TestNetwork,
+            BlockHeight::from_u32(height),
+        ));
+        let expiry_height = height + DEFAULT_EXPIRY_DELTA;
+
+        let secp = secp256k1::Secp256k1::new();
+        let secret_key = &unsigned.input_secret_keys[0];
+        let sighash = zip244::signature_hash(&unsigned.bundle, branch_id, height, expiry_height, 0);
+        let message = secp256k1::Message::from_digest(sighash);
+
+        let mut signature = secp.sign_ecdsa(&message, secret_key);
+        signature.normalize_s();
+        let pubkey = secp256k1::PublicKey::from_secret_key(&secp, secret_key);
+
+        assert!(secp.verify_ecdsa(&message, &signature, &pubkey).is_ok());
+
+        // The signature must not validate against a digest that commits to
+        // a different expiry height (the digest covers the header fields).
+        let other_sighash = zip244::signature_hash(&unsigned.bundle, branch_id, height, expiry_height + 1, 0);
+        let other_message = secp256k1::Message::from_digest(other_sighash);
+        assert!(secp.verify_ecdsa(&other_message, &signature, &pubkey).is_err());
+    }
+
+    #[test]
+    fn test_sign_with_signer_matches_build_transparent_transaction() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let make_utxos = || {
+            vec![Utxo {
+                txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+                vout: 0,
+                value: 100000,
+                address: addresses[0].clone(),
+                script_pubkey: None,
+            }]
+        };
+        let make_recipients = || {
+            vec![Recipient {
+                address: addresses[0].clone(),
+                amount: 50000,
+            }]
+        };
+
+        let unsigned = build_unsigned_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            make_utxos(),
+            make_recipients(),
+            10000,
+        )
+        .unwrap();
+
+        let signer = SeedSigner::new(TEST_SEED_PHRASE, Network::TestNetwork, 0).unwrap();
+        let via_signer = sign_with_signer(&unsigned, &signer, 2_500_000).unwrap();
+
+        let direct = build_transparent_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            make_utxos(),
+            make_recipients(),
+            10000,
+            2_500_000,
+        )
+        .unwrap();
+
+        assert_eq!(via_signer.txid, direct.txid);
+        assert_eq!(via_signer.tx_hex, direct.tx_hex);
+    }
+
+    #[test]
+    fn test_change_is_routed_to_internal_chain_by_default() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let utxos = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 100000,
+            address: addresses[0].clone(),
+            script_pubkey: None,
+        }];
+        let recipients = vec![Recipient {
+            address: addresses[0].clone(),
+            amount: 50000,
+        }];
+
+        let unsigned = build_unsigned_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            recipients,
+            10000,
+        )
+        .unwrap();
+
+        let change_address = derive_internal_address(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0).unwrap();
+        let change_txout = &unsigned.bundle.vout[1];
+        assert_eq!(change_txout.script_pubkey().0, change_address.script().0);
+        // The change address must not coincide with the spent (external)
+        // input address - that's the whole point of routing it internally.
+        assert_ne!(
+            change_txout.script_pubkey().0,
+            parse_transparent_address(&addresses[0], Network::TestNetwork)
+                .unwrap()
+                .script()
+                .0
+        );
+    }
+
+    #[test]
+    fn test_change_can_be_routed_to_first_input_address() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let utxos = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 100000,
+            address: addresses[0].clone(),
+            script_pubkey: None,
+        }];
+        let recipients = vec![Recipient {
+            address: addresses[0].clone(),
+            amount: 50000,
+        }];
+
+        let unsigned = build_unsigned_transaction_with_change(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            recipients,
+            10000,
+            ChangeStrategy::ReuseFirstInputAddress,
+        )
+        .unwrap();
+
+        let input_address = parse_transparent_address(&addresses[0], Network::TestNetwork).unwrap();
+        let change_txout = &unsigned.bundle.vout[1];
+        assert_eq!(change_txout.script_pubkey().0, input_address.script().0);
+    }
+
+    #[test]
+    fn test_spending_a_previously_issued_internal_change_utxo() {
+        let change_address = derive_internal_address(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0)
+            .unwrap()
+            .encode(&Network::TestNetwork);
+
+        let utxos = vec![Utxo {
+            txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+            vout: 0,
+            value: 100000,
+            address: change_address,
+            script_pubkey: None,
+        }];
+        let recipients = vec![Recipient {
+            address: crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap()[0]
+                .clone(),
+            amount: 50000,
+        }];
+
+        let unsigned = build_unsigned_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            recipients,
+            10000,
+        )
+        .unwrap();
+
+        assert_eq!(unsigned.input_address_indices, vec![0]);
+    }
+
     #[test]
     fn test_build_unsigned_with_valid_utxo() {
         // Derive an address first
@@ -552,10 +1314,84 @@ mod tests {
         assert_eq!(unsigned.bundle.vout.len(), 2);
     }
 
+    #[test]
+    fn test_sweep_merges_utxos_into_one_changeless_output() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 3)
+                .unwrap();
+
+        let utxos = vec![
+            Utxo {
+                txid: "0000000000000000000000000000000000000000000000000000000000000001".to_string(),
+                vout: 0,
+                value: 100_000,
+                address: addresses[0].clone(),
+                script_pubkey: None,
+            },
+            Utxo {
+                txid: "0000000000000000000000000000000000000000000000000000000000000002".to_string(),
+                vout: 0,
+                value: 50_000,
+                address: addresses[1].clone(),
+                script_pubkey: None,
+            },
+        ];
+
+        let results = build_sweep_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            &addresses[2],
+            2_500_000,
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        let sweep = &results[0];
+        assert_eq!(sweep.input_count, 2);
+        let expected_fee = crate::fee::zip317_fee(2, 1);
+        assert_eq!(sweep.signed.fee, expected_fee);
+        assert_eq!(sweep.signed.total_input, 150_000);
+        assert_eq!(sweep.signed.total_output, 150_000 - expected_fee);
+    }
+
+    #[test]
+    fn test_sweep_splits_large_utxo_sets_into_batches() {
+        let addresses =
+            crate::wallet::derive_transparent_addresses(TEST_SEED_PHRASE, Network::TestNetwork, 0, 0, 1)
+                .unwrap();
+
+        let utxos: Vec<Utxo> = (0..(MAX_SWEEP_INPUTS_PER_TX + 1))
+            .map(|i| Utxo {
+                txid: format!("{:064x}", i + 1),
+                vout: 0,
+                // Above the 10,000 zat ZIP 317 fee of the one-input last batch.
+                value: 20_000,
+                address: addresses[0].clone(),
+                script_pubkey: None,
+            })
+            .collect();
+
+        let results = build_sweep_transaction(
+            TEST_SEED_PHRASE,
+            Network::TestNetwork,
+            0,
+            utxos,
+            &addresses[0],
+            2_500_000,
+        )
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].input_count, MAX_SWEEP_INPUTS_PER_TX);
+        assert_eq!(results[1].input_count, 1);
+    }
+
     #[test]
     fn test_utxo_from_stored_note_transparent() {
         let note = StoredNote {
-            id: "test-transparent-0".to_string(),
+            id: NoteId::new("abc123def456", Pool::Transparent, 2),
             wallet_id: "w1".to_string(),
             txid: "abc123def456".to_string(),
             output_index: 2,
@@ -567,6 +1403,15 @@ mod tests {
             address: Some("tmXXXYYYZZZ".to_string()),
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         };
 
         let utxo = Utxo::from_stored_note(&note);
@@ -582,7 +1427,7 @@ mod tests {
     #[test]
     fn test_utxo_from_stored_note_shielded() {
         let note = StoredNote {
-            id: "test-orchard-0".to_string(),
+            id: NoteId::new("abc123def456", Pool::Orchard, 0),
             wallet_id: "w1".to_string(),
             txid: "abc123def456".to_string(),
             output_index: 0,
@@ -594,6 +1439,15 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         };
 
         let utxo = Utxo::from_stored_note(&note);
@@ -605,7 +1459,7 @@ mod tests {
         let notes = vec![
             // Unspent transparent - should be included
             StoredNote {
-                id: "test-transparent-0".to_string(),
+                id: NoteId::new("tx1", Pool::Transparent, 0),
                 wallet_id: "w1".to_string(),
                 txid: "tx1".to_string(),
                 output_index: 0,
@@ -617,10 +1471,19 @@ mod tests {
                 address: Some("tm1".to_string()),
                 spent_txid: None,
                 created_at: "2024-01-01T00:00:00Z".to_string(),
+                position: None,
+                witness: None,
+                status: NoteStatus::Confirmed,
+                confirmation_height: None,
+                fee_zat: None,
+                scope: KeyScope::External,
+                received_height: None,
+                acquired_fiat_value: None,
+                fiat_currency: None,
             },
             // Spent transparent - should NOT be included
             StoredNote {
-                id: "test-transparent-1".to_string(),
+                id: NoteId::new("tx2", Pool::Transparent, 0),
                 wallet_id: "w1".to_string(),
                 txid: "tx2".to_string(),
                 output_index: 0,
@@ -632,10 +1495,19 @@ mod tests {
                 address: Some("tm2".to_string()),
                 spent_txid: Some("spending_tx".to_string()),
                 created_at: "2024-01-01T00:00:00Z".to_string(),
+                position: None,
+                witness: None,
+                status: NoteStatus::Spent,
+                confirmation_height: None,
+                fee_zat: None,
+                scope: KeyScope::External,
+                received_height: None,
+                acquired_fiat_value: None,
+                fiat_currency: None,
             },
             // Orchard note - should NOT be included
             StoredNote {
-                id: "test-orchard-0".to_string(),
+                id: NoteId::new("tx3", Pool::Orchard, 0),
                 wallet_id: "w1".to_string(),
                 txid: "tx3".to_string(),
                 output_index: 0,
@@ -647,6 +1519,15 @@ mod tests {
                 address: None,
                 spent_txid: None,
                 created_at: "2024-01-01T00:00:00Z".to_string(),
+                position: None,
+                witness: None,
+                status: NoteStatus::Confirmed,
+                confirmation_height: None,
+                fee_zat: None,
+                scope: KeyScope::External,
+                received_height: None,
+                acquired_fiat_value: None,
+                fiat_currency: None,
             },
         ];
 
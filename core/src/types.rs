@@ -3,6 +3,8 @@
 //! This module contains data structures used across the codebase for
 //! representing transactions, viewing keys, and wallet data.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use zcash_protocol::consensus::Network;
 
@@ -82,6 +84,108 @@ impl<'de> Deserialize<'de> for NetworkKind {
     }
 }
 
+/// Which side of a shielded transfer a decrypted output represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferDirection {
+    /// Decrypted with the incoming viewing key: a note received by this
+    /// wallet (possibly change sent back to itself).
+    Incoming,
+    /// Recovered with the outgoing viewing key: a note this wallet sent,
+    /// reconstructed from `out_ciphertext` even though it isn't the
+    /// recipient.
+    Outgoing,
+}
+
+impl std::fmt::Display for TransferDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Incoming => write!(f, "incoming"),
+            Self::Outgoing => write!(f, "outgoing"),
+        }
+    }
+}
+
+/// Which ZIP 32 key scope (external or internal/change) recovered a note.
+///
+/// Distinct from [`TransferDirection`]: `External` means the note was
+/// addressed using the wallet's ordinary receiving IVK, `Internal` means it
+/// is change, either decrypted with the internal IVK derived per ZIP 32
+/// from the same UFVK (`TransferDirection::Incoming`) or recovered with the
+/// internal OVK (`TransferDirection::Outgoing`). A tax tool must not
+/// count `Internal` notes as received income, since they're the wallet
+/// paying itself back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyScope {
+    /// Recovered with the external (ordinary receiving) IVK.
+    External,
+    /// Recovered with the internal (change) IVK or internal OVK.
+    Internal,
+}
+
+impl std::fmt::Display for KeyScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::External => write!(f, "external"),
+            Self::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+/// The overall nature of a transaction, classified from its recovered notes
+/// rather than any single one of them - see `scanner::scan_transaction`'s
+/// `classify_transfer_type`. Lets downstream tax export net wallet-internal
+/// transfers to zero and treat only genuine outgoing transactions as
+/// dispositions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransferType {
+    /// Only produced notes/outputs this wallet received; it didn't spend
+    /// any of its own nullifiers or transparent outputs.
+    Incoming,
+    /// Spent this wallet's notes/outputs and sent value to an address that
+    /// isn't the wallet's own.
+    Outgoing,
+    /// Spent this wallet's notes/outputs but returned all of that value to
+    /// the wallet's own internal (change) addresses.
+    WalletInternal,
+}
+
+impl std::fmt::Display for TransferType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Incoming => write!(f, "incoming"),
+            Self::Outgoing => write!(f, "outgoing"),
+            Self::WalletInternal => write!(f, "wallet_internal"),
+        }
+    }
+}
+
+/// Decoded contents of a ZIP-302 512-byte memo field.
+///
+/// Per ZIP-302, the leading byte of the memo selects how the remaining bytes
+/// are interpreted: `0xF6` (with an all-zero remainder) marks an empty memo,
+/// `0x00..=0xF4` marks UTF-8 text, and `0xF5..=0xFF` are reserved for
+/// future/application-defined binary formats and are kept as raw bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemoContents {
+    /// No memo was included (leading byte `0xF6`, remainder all zero).
+    Empty,
+    /// UTF-8 text memo, with trailing zero padding trimmed.
+    Text(String),
+    /// Reserved or application-defined memo (leading byte `0xF5..=0xFF`, or
+    /// text bytes that failed UTF-8 validation), kept as raw bytes.
+    Arbitrary(Vec<u8>),
+}
+
+impl std::fmt::Display for MemoContents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, ""),
+            Self::Text(text) => write!(f, "{}", text),
+            Self::Arbitrary(bytes) => write!(f, "{}", hex::encode(bytes)),
+        }
+    }
+}
+
 /// A fully parsed and decrypted Zcash transaction.
 ///
 /// Contains all components of a transaction including transparent inputs/outputs
@@ -101,6 +205,8 @@ pub struct DecryptedTransaction {
     pub transparent_outputs: Vec<TransparentOutput>,
     /// Transaction fee in zatoshis, if calculable.
     pub fee: Option<u64>,
+    /// This transaction's overall nature - see [`TransferType`].
+    pub transfer_type: TransferType,
 }
 
 /// A decrypted Sapling shielded output.
@@ -113,14 +219,17 @@ pub struct DecryptedSaplingOutput {
     pub index: usize,
     /// Note value in zatoshis (1 ZEC = 100,000,000 zatoshis). Zero if not decrypted.
     pub value: u64,
-    /// Memo field contents. Empty or "(encrypted)" if not decrypted.
-    pub memo: String,
+    /// Decoded memo field contents, per ZIP-302. `None` if not decrypted.
+    pub memo: Option<MemoContents>,
     /// Recipient address, if available from decryption.
     pub address: Option<String>,
     /// Note commitment (cmu) as a hex string. Used to identify the note on-chain.
     pub note_commitment: String,
     /// Nullifier as a hex string. Used to detect when this note is spent.
     pub nullifier: Option<String>,
+    /// Whether this note was recovered as an incoming receive or an
+    /// outgoing send. `None` if the output couldn't be decrypted at all.
+    pub direction: Option<TransferDirection>,
 }
 
 /// A decrypted Orchard shielded action.
@@ -133,14 +242,17 @@ pub struct DecryptedOrchardAction {
     pub index: usize,
     /// Note value in zatoshis. Zero if not decrypted.
     pub value: u64,
-    /// Memo field contents. Empty or "(encrypted)" if not decrypted.
-    pub memo: String,
+    /// Decoded memo field contents, per ZIP-302. `None` if not decrypted.
+    pub memo: Option<MemoContents>,
     /// Recipient address, if available from decryption.
     pub address: Option<String>,
     /// Note commitment (cmx) as a hex string.
     pub note_commitment: String,
     /// Nullifier as a hex string. Present for all Orchard actions.
     pub nullifier: Option<String>,
+    /// Whether this note was recovered as an incoming receive or an
+    /// outgoing send. `None` if the action couldn't be decrypted at all.
+    pub direction: Option<TransferDirection>,
 }
 
 /// A transparent transaction input.
@@ -255,11 +367,31 @@ impl<'de> Deserialize<'de> for Pool {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// A string didn't match one of `Pool`'s names.
+#[derive(Debug)]
+pub struct ParsePoolError(String);
+
+impl std::fmt::Display for ParsePoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown pool: {}", self.0)
+    }
+}
+
+impl core::error::Error for ParsePoolError {}
+
+impl std::str::FromStr for Pool {
+    type Err = ParsePoolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "transparent" => Ok(Pool::Transparent),
             "sapling" => Ok(Pool::Sapling),
             "orchard" => Ok(Pool::Orchard),
-            _ => Err(serde::de::Error::custom(format!("unknown pool: {}", s))),
+            _ => Err(ParsePoolError(s.to_string())),
         }
     }
 }
@@ -286,11 +418,25 @@ pub struct ScannedNote {
     /// Nullifier for shielded notes, used to detect when it's spent.
     /// None for transparent outputs (they use input references instead).
     pub nullifier: Option<String>,
-    /// Memo field contents if decrypted and valid UTF-8.
-    /// None for transparent outputs.
-    pub memo: Option<String>,
+    /// Decoded memo field contents, per ZIP-302.
+    /// None for transparent outputs, or if a shielded output couldn't be recovered at all.
+    pub memo: Option<MemoContents>,
     /// Recipient address if available.
     pub address: Option<String>,
+    /// Whether this note was recovered as an incoming receive or an
+    /// outgoing send (via the outgoing viewing key). `None` for transparent
+    /// outputs, or if a shielded output couldn't be recovered at all.
+    pub direction: Option<TransferDirection>,
+    /// This note's leaf position in its pool's note commitment tree, needed
+    /// to build a spending witness. `None` until the caller supplies it -
+    /// see `scan_transaction`'s `leaf_positions` parameter.
+    pub position: Option<u64>,
+    /// Which key scope recovered this note - `External` for an ordinary
+    /// receive, `Internal` for change the wallet sent back to itself
+    /// (recovered with the internal IVK, or with the internal OVK when
+    /// `direction == Outgoing`). `None` for transparent outputs, and for an
+    /// outgoing output recovered with the external OVK.
+    pub scope: Option<KeyScope>,
 }
 
 /// A nullifier found in a transaction, indicating a spent shielded note.
@@ -328,6 +474,10 @@ pub struct ScannedTransparentOutput {
     pub value: u64,
     /// Decoded transparent address, if available.
     pub address: Option<String>,
+    /// Always `External`, when set - the scanner only derives the wallet's
+    /// external transparent address today, so an internal (change) t-addr
+    /// can't be recognized yet. `None` when `address` isn't this wallet's.
+    pub scope: Option<KeyScope>,
 }
 
 /// Result of scanning a transaction for notes and nullifiers.
@@ -349,6 +499,8 @@ pub struct ScanResult {
     pub transparent_received: u64,
     /// Raw transparent outputs (kept for backward compatibility).
     pub transparent_outputs: Vec<ScannedTransparentOutput>,
+    /// This transaction's overall nature - see [`TransferType`].
+    pub transfer_type: TransferType,
 }
 
 /// Result of a transaction scan operation.
@@ -394,6 +546,323 @@ pub struct WalletResult {
     pub error: Option<String>,
 }
 
+// ============================================================================
+// Inspect Types
+// ============================================================================
+
+/// What kind of Zcash object `inspect` recognized its input as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InspectKind {
+    /// A unified, legacy Sapling, or transparent address.
+    Address,
+    /// A UFVK, UIVK, or legacy Sapling extended viewing key.
+    ViewingKey,
+    /// Raw transaction hex.
+    Transaction,
+    /// Nothing recognized the input.
+    Unrecognized,
+}
+
+/// The receivers contained in a decoded address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddressDetails {
+    /// "unified", "sapling", or "transparent".
+    pub address_type: String,
+    /// Which pools this address can receive into, e.g. `["orchard",
+    /// "sapling", "transparent"]` for a unified address with all receivers.
+    pub receiver_types: Vec<String>,
+}
+
+/// A summary of a transaction's bundles, suitable for an `inspect` preview
+/// without requiring a viewing key to decrypt anything.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionSummary {
+    /// The transaction identifier (hash) as a hex string.
+    pub txid: String,
+    /// Number of Sapling shielded outputs.
+    pub sapling_output_count: usize,
+    /// Number of Orchard shielded actions.
+    pub orchard_action_count: usize,
+    /// Number of transparent inputs.
+    pub transparent_input_count: usize,
+    /// Number of transparent outputs.
+    pub transparent_output_count: usize,
+    /// Net value (zatoshis) flowing out of the Sapling pool, if the
+    /// transaction has a Sapling bundle. Public transaction data - no
+    /// viewing key needed.
+    pub sapling_value_balance: Option<i64>,
+    /// Net value (zatoshis) flowing out of the Orchard pool, if the
+    /// transaction has an Orchard bundle. Public transaction data - no
+    /// viewing key needed.
+    pub orchard_value_balance: Option<i64>,
+    /// Block height after which this transaction is no longer valid, if set.
+    pub expiry_height: Option<u32>,
+    /// Transaction fee in zatoshis, if calculable without a viewing key -
+    /// only possible for a transaction with no transparent inputs or
+    /// outputs, since a transparent input's value has to be looked up from
+    /// the UTXO it spends and isn't recorded in the transaction itself.
+    pub fee: Option<u64>,
+}
+
+/// The kind-specific payload of an [`InspectResult`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InspectDetails {
+    /// Present when `kind` is [`InspectKind::Address`].
+    Address(AddressDetails),
+    /// Present when `kind` is [`InspectKind::ViewingKey`].
+    ViewingKey(ViewingKeyInfo),
+    /// Present when `kind` is [`InspectKind::Transaction`].
+    Transaction(TransactionSummary),
+}
+
+/// Result of classifying and decoding an arbitrary piece of pasted Zcash
+/// data - an address, viewing key, or raw transaction - so a UI can offer a
+/// single paste box that explains whatever was entered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InspectResult {
+    /// What kind of object the input was recognized as.
+    pub kind: InspectKind,
+    /// Network the object is valid for, if recognized.
+    pub network: Option<NetworkKind>,
+    /// Kind-specific decoded details, if recognized.
+    pub details: Option<InspectDetails>,
+    /// Error message if nothing recognized the input.
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// Gains Types
+// ============================================================================
+
+/// A holding period's capital-gains treatment under the common one-year
+/// short/long-term split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldingTerm {
+    /// Held for 365 days or less.
+    ShortTerm,
+    /// Held for more than 365 days.
+    LongTerm,
+}
+
+impl HoldingTerm {
+    /// Classify a holding period of `days`, using the common 365-day
+    /// short/long-term cutoff (e.g. US capital-gains treatment).
+    pub fn from_holding_days(days: i64) -> Self {
+        if days > 365 {
+            HoldingTerm::LongTerm
+        } else {
+            HoldingTerm::ShortTerm
+        }
+    }
+}
+
+/// A historical per-unit ZEC/fiat price quote for one transaction, keyed by
+/// that transaction's txid in a [`compute_gains`](crate::gains::compute_gains)
+/// price oracle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceQuote {
+    /// Calendar date the transaction is considered to have occurred on
+    /// (`YYYY-MM-DD`), used for the holding-period calculation.
+    pub date: String,
+    /// Fiat price per whole ZEC on `date`.
+    pub price: f64,
+}
+
+/// One matched (disposal, lot) pair: part or all of an acquisition lot
+/// consumed by part or all of a disposal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GainRecord {
+    /// The pool both the lot and the disposal belong to.
+    pub pool: Pool,
+    /// Id of the note whose spend triggered this disposal.
+    pub disposal_note_id: String,
+    /// Id of the note whose creation opened the lot this record consumes.
+    pub lot_note_id: String,
+    /// Portion of the disposal matched to this lot, in zatoshis.
+    pub value_zatoshi: u64,
+    /// The lot's acquisition date.
+    pub acquired_date: String,
+    /// The disposal's date, if a price quote was found for the spending txid.
+    pub disposed_date: Option<String>,
+    /// Whole days between `acquired_date` and `disposed_date`.
+    pub holding_days: Option<i64>,
+    /// Short/long-term classification of `holding_days`.
+    pub term: Option<HoldingTerm>,
+    /// Fiat value of `value_zatoshi` at the disposal's price, if known.
+    pub proceeds: Option<f64>,
+    /// Fiat value of `value_zatoshi` at the lot's price, if known.
+    pub basis: Option<f64>,
+    /// This record's share of the disposal note's spending transaction fee,
+    /// prorated by value among every note that transaction spent, valued in
+    /// fiat at the disposal's price.
+    pub fee_share: Option<f64>,
+    /// `proceeds - basis - fee_share`, if all three are known.
+    pub gain: Option<f64>,
+}
+
+/// Aggregate short/long-term realized gains for one pool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PoolGainsTotals {
+    /// Sum of `proceeds` across this pool's short-term records.
+    pub short_term_proceeds: f64,
+    /// Sum of `basis` across this pool's short-term records.
+    pub short_term_basis: f64,
+    /// Sum of `gain` across this pool's short-term records.
+    pub short_term_gain: f64,
+    /// Sum of `proceeds` across this pool's long-term records.
+    pub long_term_proceeds: f64,
+    /// Sum of `basis` across this pool's long-term records.
+    pub long_term_basis: f64,
+    /// Sum of `gain` across this pool's long-term records.
+    pub long_term_gain: f64,
+}
+
+/// The full output of [`compute_gains`](crate::gains::compute_gains).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GainsReport {
+    /// One entry per matched (disposal, lot) pair, across all pools.
+    pub records: Vec<GainRecord>,
+    /// Aggregate totals, keyed by pool.
+    pub totals: HashMap<Pool, PoolGainsTotals>,
+}
+
+/// Result of a `compute_gains` operation, for JavaScript interop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GainsResult {
+    /// Whether the computation completed without errors.
+    pub success: bool,
+    /// The gains report, if successful.
+    pub report: Option<GainsReport>,
+    /// Error message if the computation failed.
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// Fiat Types
+// ============================================================================
+
+/// A fiat currency a wallet can be valued in.
+///
+/// `minor_units` is the number of minor units per major unit (e.g. 100 for
+/// USD cents), used to round a zatoshi -> fiat conversion to the currency's
+/// smallest denomination once, rather than letting float error accumulate
+/// across further arithmetic.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Currency {
+    /// ISO 4217 currency code (e.g. "USD"). Not validated against a fixed
+    /// list - the price oracle is caller-supplied, so any code the caller
+    /// prices in is accepted.
+    pub code: String,
+    /// Minor units per major unit (e.g. 100 for USD cents).
+    pub minor_units: u32,
+}
+
+impl Currency {
+    /// Resolve an ISO 4217 currency code to its minor-unit scale.
+    ///
+    /// Defaults unrecognized codes to 100 (cent-level rounding, the common
+    /// case); a handful of known zero- and three-decimal currencies are
+    /// special cased rather than pulling in a full ISO 4217 table.
+    pub fn from_code(code: &str) -> Self {
+        let minor_units = match code.to_uppercase().as_str() {
+            "JPY" | "KRW" | "VND" | "CLP" => 1,
+            "BHD" | "KWD" | "OMR" => 1000,
+            _ => 100,
+        };
+        Currency {
+            code: code.to_uppercase(),
+            minor_units,
+        }
+    }
+}
+
+/// One note's fiat value at its acquisition price, from
+/// [`fiat::value_notes`](crate::fiat::value_notes).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteFiatValue {
+    /// Id of the valued note.
+    pub note_id: String,
+    /// Calendar date (`YYYY-MM-DD`) the note's price was looked up for,
+    /// derived from its `created_at` timestamp.
+    pub date: String,
+    /// Fiat value of the note's `value` zatoshi at `date`'s price, or
+    /// `None` if `date` wasn't found in the supplied price table.
+    pub fiat_value: Option<f64>,
+}
+
+/// A wallet's unspent balance valued at a single spot price, from
+/// [`fiat::fiat_balance`](crate::fiat::fiat_balance).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiatBalance {
+    /// Fiat value of the total unspent balance across all pools.
+    pub total: f64,
+    /// Fiat value of the unspent balance in each pool.
+    pub by_pool: HashMap<Pool, f64>,
+}
+
+// ============================================================================
+// History Types
+// ============================================================================
+
+/// What kind of ledger event a [`TransactionEvent`] represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// Value received into the wallet - the note's creation.
+    Received,
+    /// Value spent out of the wallet, by a confirmed spend.
+    Sent,
+}
+
+/// One note's contribution to its transaction's ledger entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionEvent {
+    /// Id of the note behind this event.
+    pub note_id: String,
+    /// Whether this event is a receipt or a spend.
+    pub kind: EventKind,
+    /// The pool the note belongs to.
+    pub pool: Pool,
+    /// The note's value, in zatoshis.
+    pub value_zatoshi: u64,
+    /// Recipient address, if available.
+    pub address: Option<String>,
+    /// Memo field contents, if available.
+    pub memo: Option<String>,
+    /// This event's share of its transaction's fee, prorated by value the
+    /// same way `gains::compute_gains` prorates it - only set on `Sent`
+    /// events whose transaction had a fee recorded via
+    /// `NoteCollection::record_transaction_fee`.
+    pub fee_share_zat: Option<u64>,
+    /// Fiat value of this event, if a `GainsReport` was supplied to
+    /// `history::build_transaction_history`: cost basis for a `Received`
+    /// event that opened a lot, proceeds for a `Sent` event that disposed
+    /// of one. Summed across every lot the note was matched against.
+    pub fiat_value: Option<f64>,
+    /// Realized gain/loss from disposing this note, summed the same way -
+    /// only set on `Sent` events, and only when a `GainsReport` was
+    /// supplied.
+    pub gain: Option<f64>,
+}
+
+/// A chronological, per-txid ledger entry, from
+/// [`history::build_transaction_history`](crate::history::build_transaction_history).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionHistoryEntry {
+    /// The transaction every event in this entry belongs to.
+    pub txid: String,
+    /// The entry's date (`YYYY-MM-DD` or full ISO 8601, whatever the
+    /// source notes used), if known: the earliest `created_at` among its
+    /// `Received` events, or - for an entry with none - the disposal's
+    /// price-quote date, if a `GainsReport` was supplied.
+    pub date: Option<String>,
+    /// Every event - received and spent - this transaction touched.
+    pub events: Vec<TransactionEvent>,
+}
+
 // ============================================================================
 // Storage Types (SQLite-compatible)
 // ============================================================================
@@ -492,6 +961,116 @@ pub struct DerivedAddress {
     pub address: String,
 }
 
+/// Where a note sits in its confirmation/spend lifecycle.
+///
+/// This is tracked separately from `spent_txid`, which only records *which*
+/// transaction spent a note, not whether that spend (or the note's own
+/// receipt) has confirmed yet - information a tax report needs to avoid
+/// counting a disposal before it's final.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NoteStatus {
+    /// The note's receiving transaction hasn't confirmed yet.
+    PendingConfirmation,
+    /// The note is confirmed and, as far as this wallet knows, unspent.
+    Confirmed,
+    /// A spend referencing this note has been broadcast but hasn't confirmed.
+    PendingSpent,
+    /// The note's spend has confirmed.
+    Spent,
+    /// A pending spend referencing this note never confirmed, so the note is
+    /// spendable again.
+    Expired,
+}
+
+/// A note's unique identifier, structured as the transaction that created it,
+/// its pool, and its output index within that transaction.
+///
+/// Serializes as - and round-trips through `FromStr` from - the hyphenated
+/// `"{txid}-{pool}-{output_index}"` form `StoredNote::id` used before this
+/// type existed, so already-stored ids keep parsing. Storing the components
+/// directly instead of building/parsing that string by hand rules out a
+/// class of bugs where a txid or pool is substituted into the wrong id.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NoteId {
+    /// Transaction ID that created this note.
+    pub txid: String,
+    /// The pool this note belongs to.
+    pub pool: Pool,
+    /// Output index within the transaction.
+    pub output_index: u16,
+}
+
+impl NoteId {
+    /// Construct an id from its parts.
+    pub fn new(txid: impl Into<String>, pool: Pool, output_index: u16) -> Self {
+        NoteId {
+            txid: txid.into(),
+            pool,
+            output_index,
+        }
+    }
+}
+
+impl std::fmt::Display for NoteId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}-{}", self.txid, self.pool, self.output_index)
+    }
+}
+
+/// A string didn't parse as a [`NoteId`] - either malformed or its
+/// `output_index` component isn't a valid `u16`.
+#[derive(Debug)]
+pub struct ParseNoteIdError(String);
+
+impl std::fmt::Display for ParseNoteIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid note id: {}", self.0)
+    }
+}
+
+impl core::error::Error for ParseNoteIdError {}
+
+impl std::str::FromStr for NoteId {
+    type Err = ParseNoteIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.rsplitn(3, '-');
+        let (output_index, pool, txid) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(output_index), Some(pool), Some(txid)) => (output_index, pool, txid),
+            _ => return Err(ParseNoteIdError(s.to_string())),
+        };
+        let output_index: u16 = output_index
+            .parse()
+            .map_err(|_| ParseNoteIdError(s.to_string()))?;
+        let pool: Pool = pool.parse().map_err(|_| ParseNoteIdError(s.to_string()))?;
+        Ok(NoteId {
+            txid: txid.to_string(),
+            pool,
+            output_index,
+        })
+    }
+}
+
+impl Serialize for NoteId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for NoteId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// A note stored in the database/localStorage.
 ///
 /// Represents the "notes" table with the following columns:
@@ -507,10 +1086,14 @@ pub struct DerivedAddress {
 /// - address: TEXT
 /// - spent_txid: TEXT (null if unspent)
 /// - created_at: TEXT (ISO 8601)
+/// - status: TEXT (NoteStatus, see above)
+/// - confirmation_height: INTEGER (height at which the current status was confirmed, if any)
+/// - scope: TEXT (KeyScope - "external" or "internal")
+/// - received_height: INTEGER (height at which this note was received, if known)
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct StoredNote {
-    /// Unique note identifier: "{txid}-{pool}-{output_index}".
-    pub id: String,
+    /// Unique note identifier - see [`NoteId`].
+    pub id: NoteId,
     /// Foreign key to the wallet that owns this note.
     pub wallet_id: String,
     /// Transaction ID where this note was received.
@@ -535,12 +1118,44 @@ pub struct StoredNote {
     pub spent_txid: Option<String>,
     /// Creation timestamp in ISO 8601 format.
     pub created_at: String,
+    /// This note's leaf position in its pool's note commitment tree, if known.
+    pub position: Option<u64>,
+    /// Serialized incremental Merkle witness as a hex string, snapshotted by
+    /// `commitment_tree::TreeTracker` once the note is confirmed. `None`
+    /// until a witness has been recorded for this note.
+    pub witness: Option<String>,
+    /// Where this note sits in its confirmation/spend lifecycle.
+    pub status: NoteStatus,
+    /// Block height at which `status` was last confirmed (receipt or
+    /// spend), if known.
+    pub confirmation_height: Option<u32>,
+    /// The full fee (in zatoshis) paid by this note's spending transaction,
+    /// if recorded via `NoteCollection::record_transaction_fee`. Shared by
+    /// every note spent in the same transaction - `gains::compute_gains`
+    /// prorates it by value across them to get each disposal's fee share.
+    pub fee_zat: Option<u64>,
+    /// Which IVK scope recovered this note - see [`KeyScope`]. Used by
+    /// `NoteCollection::received_income_notes` to exclude wallet-internal
+    /// change from taxable received income.
+    pub scope: KeyScope,
+    /// Block height at which this note was received, if known. Used by
+    /// `NoteCollection::account_balance` to compute confirmation depth
+    /// against a chain tip, independent of `confirmation_height` (which
+    /// tracks the *current* status transition, not the original receipt).
+    pub received_height: Option<u32>,
+    /// This note's fiat value at acquisition - see
+    /// [`populate_acquired_fiat_value`](crate::historical_prices::populate_acquired_fiat_value).
+    /// `None` until a price series covering `created_at`'s date has been
+    /// looked up for this note.
+    pub acquired_fiat_value: Option<f64>,
+    /// The currency `acquired_fiat_value` was priced in, if set.
+    pub fiat_currency: Option<String>,
 }
 
 impl StoredNote {
     /// Generate the unique ID for a note.
-    pub fn generate_id(txid: &str, pool: Pool, output_index: u32) -> String {
-        format!("{}-{}-{}", txid, pool.as_str(), output_index)
+    pub fn generate_id(txid: &str, pool: Pool, output_index: u16) -> NoteId {
+        NoteId::new(txid, pool, output_index)
     }
 
     /// Create a new StoredNote from a scanned note.
@@ -550,7 +1165,7 @@ impl StoredNote {
         wallet_id: &str,
         created_at: &str,
     ) -> Self {
-        let id = Self::generate_id(txid, note.pool, note.output_index as u32);
+        let id = Self::generate_id(txid, note.pool, note.output_index as u16);
         StoredNote {
             id,
             wallet_id: wallet_id.to_string(),
@@ -564,22 +1179,68 @@ impl StoredNote {
                 Some(note.commitment.clone())
             },
             nullifier: note.nullifier.clone(),
-            memo: note.memo.clone(),
+            memo: note.memo.as_ref().map(|m| m.to_string()),
             address: note.address.clone(),
             spent_txid: None,
             created_at: created_at.to_string(),
+            position: note.position,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: note.scope.unwrap_or(KeyScope::External),
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         }
     }
 
-    /// Check if this note is spent.
+    /// Check if this note's spend has confirmed.
     pub fn is_spent(&self) -> bool {
-        self.spent_txid.is_some()
+        matches!(self.status, NoteStatus::Spent)
+    }
+
+    /// Check if this note is referenced by an unconfirmed spend.
+    pub fn is_pending_spent(&self) -> bool {
+        matches!(self.status, NoteStatus::PendingSpent)
     }
 
     /// Check if this note has a positive value.
     pub fn has_value(&self) -> bool {
         self.value > 0
     }
+
+    /// Check if this note is wallet-internal change rather than received
+    /// income - see [`KeyScope`]. A derived accessor rather than its own
+    /// stored field, since `scope` (set once, at scan time, from which IVK
+    /// recovered the note) is already the single source of truth for this
+    /// distinction.
+    pub fn is_change(&self) -> bool {
+        self.scope == KeyScope::Internal
+    }
+}
+
+/// Per-pool unspent value, split by how soon it can actually be spent - see
+/// [`NoteCollection::account_balance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct Balance {
+    /// Value that has matured (reached the caller's `min_confirmations`)
+    /// and is ready to spend right now.
+    pub spendable_value: u64,
+    /// Value in wallet-internal change notes (`KeyScope::Internal`) that
+    /// haven't matured yet.
+    pub change_pending_confirmation: u64,
+    /// Value in externally-received notes (`KeyScope::External`) that
+    /// haven't matured yet.
+    pub value_pending_spendability: u64,
+}
+
+/// A wallet's unspent balance at a chain tip, split per pool by
+/// spendability - see [`NoteCollection::account_balance`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct AccountBalance {
+    /// This wallet's balance in each pool.
+    pub by_pool: HashMap<Pool, Balance>,
 }
 
 /// Collection of notes for balance calculation and storage.
@@ -595,10 +1256,20 @@ impl NoteCollection {
         Self { notes: Vec::new() }
     }
 
+    /// Look up a note by its structured id.
+    pub fn find_by_id(&self, id: &NoteId) -> Option<&StoredNote> {
+        self.notes.iter().find(|n| &n.id == id)
+    }
+
+    /// Look up a note by its structured id, for in-place updates.
+    pub fn find_by_id_mut(&mut self, id: &NoteId) -> Option<&mut StoredNote> {
+        self.notes.iter_mut().find(|n| &n.id == id)
+    }
+
     /// Add or update a note in the collection.
     /// Returns true if a new note was added, false if an existing note was updated.
     pub fn add_or_update(&mut self, note: StoredNote) -> bool {
-        if let Some(existing) = self.notes.iter_mut().find(|n| n.id == note.id) {
+        if let Some(existing) = self.find_by_id_mut(&note.id) {
             *existing = note;
             false
         } else {
@@ -608,17 +1279,32 @@ impl NoteCollection {
     }
 
     /// Mark notes as spent by matching nullifiers.
-    /// Returns the number of notes marked as spent.
+    ///
+    /// Notes move to `PendingSpent` unless `confirmed` is set, in which case
+    /// they move straight to `Spent` and record `confirmation_height`. A
+    /// note already `PendingSpent` or `Spent` is left alone; an `Expired`
+    /// note (whose earlier pending spend never confirmed) can be re-marked.
+    /// Returns the number of notes marked.
     pub fn mark_spent_by_nullifiers(
         &mut self,
         nullifiers: &[SpentNullifier],
         spending_txid: &str,
+        confirmed: bool,
+        confirmation_height: Option<u32>,
     ) -> usize {
         let mut count = 0;
         for nf in nullifiers {
             for note in &mut self.notes {
-                if note.nullifier.as_deref() == Some(&nf.nullifier) && note.spent_txid.is_none() {
+                if note.nullifier.as_deref() == Some(&nf.nullifier)
+                    && !matches!(note.status, NoteStatus::PendingSpent | NoteStatus::Spent)
+                {
                     note.spent_txid = Some(spending_txid.to_string());
+                    note.status = if confirmed {
+                        NoteStatus::Spent
+                    } else {
+                        NoteStatus::PendingSpent
+                    };
+                    note.confirmation_height = confirmation_height;
                     count += 1;
                 }
             }
@@ -627,11 +1313,15 @@ impl NoteCollection {
     }
 
     /// Mark transparent notes as spent by matching prevout references.
-    /// Returns the number of notes marked as spent.
+    ///
+    /// Same `PendingSpent`/`Spent` promotion rules as
+    /// `mark_spent_by_nullifiers`. Returns the number of notes marked.
     pub fn mark_spent_by_transparent(
         &mut self,
         spends: &[TransparentSpend],
         spending_txid: &str,
+        confirmed: bool,
+        confirmation_height: Option<u32>,
     ) -> usize {
         let mut count = 0;
         for spend in spends {
@@ -639,9 +1329,15 @@ impl NoteCollection {
                 if note.pool == Pool::Transparent
                     && note.txid == spend.prevout_txid
                     && note.output_index == spend.prevout_index
-                    && note.spent_txid.is_none()
+                    && !matches!(note.status, NoteStatus::PendingSpent | NoteStatus::Spent)
                 {
                     note.spent_txid = Some(spending_txid.to_string());
+                    note.status = if confirmed {
+                        NoteStatus::Spent
+                    } else {
+                        NoteStatus::PendingSpent
+                    };
+                    note.confirmation_height = confirmation_height;
                     count += 1;
                 }
             }
@@ -649,11 +1345,111 @@ impl NoteCollection {
         count
     }
 
-    /// Get all unspent notes with positive value.
+    /// Reconcile pending notes and spends against which txids have since
+    /// confirmed on chain, and which are still sitting in the mempool
+    /// (waiting to confirm or be rebroadcast).
+    ///
+    /// A note `PendingConfirmation` promotes to `Confirmed` once its own
+    /// `txid` appears in `confirmed_txids`; a note `PendingSpent` promotes
+    /// to `Spent` once its `spent_txid` does. A pending spend only reverts
+    /// to `Expired` (spendable again, `spent_txid` cleared) once its txid is
+    /// missing from *both* `confirmed_txids` and `mempool_txids` - i.e. it's
+    /// actually been dropped, not merely still waiting to confirm. Calling
+    /// `reconcile` right after broadcasting a spend (before it could have
+    /// confirmed) is therefore safe as long as the caller's `mempool_txids`
+    /// still includes it; a pending receipt left unconfirmed-but-in-mempool
+    /// just stays `PendingConfirmation` the same way, since it too may yet
+    /// confirm or be rebroadcast.
+    pub fn reconcile(
+        &mut self,
+        confirmed_txids: &std::collections::HashSet<String>,
+        mempool_txids: &std::collections::HashSet<String>,
+    ) {
+        for note in &mut self.notes {
+            match note.status {
+                NoteStatus::PendingConfirmation => {
+                    if confirmed_txids.contains(&note.txid) {
+                        note.status = NoteStatus::Confirmed;
+                    }
+                }
+                NoteStatus::PendingSpent => {
+                    let Some(spent_txid) = note.spent_txid.as_deref() else {
+                        continue;
+                    };
+                    if confirmed_txids.contains(spent_txid) {
+                        note.status = NoteStatus::Spent;
+                    } else if !mempool_txids.contains(spent_txid) {
+                        note.status = NoteStatus::Expired;
+                        note.spent_txid = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Record a transaction's fee on every note it spent, so
+    /// `gains::compute_gains` can prorate it by value across their
+    /// disposals. Overwrites any fee previously recorded for `spending_txid`.
+    /// Returns the number of notes updated.
+    pub fn record_transaction_fee(&mut self, spending_txid: &str, fee_zat: u64) -> usize {
+        let mut count = 0;
+        for note in &mut self.notes {
+            if note.spent_txid.as_deref() == Some(spending_txid) {
+                note.fee_zat = Some(fee_zat);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Record a note's leaf position and serialized incremental-Merkle
+    /// witness, as snapshotted by a `commitment_tree::TreeTracker`. Returns
+    /// `false` if no note with `note_id` is in the collection.
+    pub fn set_note_witness(
+        &mut self,
+        note_id: &NoteId,
+        position: u64,
+        witness_bytes: &[u8],
+    ) -> bool {
+        let Some(note) = self.find_by_id_mut(note_id) else {
+            return false;
+        };
+        note.position = Some(position);
+        note.witness = Some(hex::encode(witness_bytes));
+        true
+    }
+
+    /// Look up a note's leaf position and serialized witness, if one has
+    /// been recorded. Needed to build a spend proof against this note.
+    pub fn get_note_witness(&self, note_id: &NoteId) -> Option<(u64, &str)> {
+        let note = self.find_by_id(note_id)?;
+        Some((note.position?, note.witness.as_deref()?))
+    }
+
+    /// Get all unspent, spendable notes with positive value.
+    ///
+    /// Excludes notes that are fully `Spent` or `PendingSpent` - a note
+    /// referenced by an unconfirmed spend isn't available until that spend
+    /// either confirms (`Spent`) or expires (`Expired`, spendable again).
     pub fn unspent_notes(&self) -> Vec<&StoredNote> {
         self.notes
             .iter()
-            .filter(|n| !n.is_spent() && n.has_value())
+            .filter(|n| {
+                !matches!(n.status, NoteStatus::Spent | NoteStatus::PendingSpent) && n.has_value()
+            })
+            .collect()
+    }
+
+    /// Get all notes currently referenced by an unconfirmed spend.
+    ///
+    /// Distinguished from `unspent_notes` so callers (e.g. a balance
+    /// display) can show this value as "in flight" rather than either
+    /// spendable or gone.
+    pub fn pending_spent_notes(&self) -> Vec<&StoredNote> {
+        self.notes
+            .iter()
+            .filter(|n| n.is_pending_spent() && n.has_value())
             .collect()
     }
 
@@ -662,6 +1458,11 @@ impl NoteCollection {
         self.unspent_notes().iter().map(|n| n.value).sum()
     }
 
+    /// Calculate total balance tied up in unconfirmed spends.
+    pub fn pending_spent_balance(&self) -> u64 {
+        self.pending_spent_notes().iter().map(|n| n.value).sum()
+    }
+
     /// Calculate balance by pool.
     pub fn balance_by_pool(&self) -> std::collections::HashMap<Pool, u64> {
         let mut balances = std::collections::HashMap::new();
@@ -671,6 +1472,68 @@ impl NoteCollection {
         balances
     }
 
+    /// Sum `acquired_fiat_value` across unspent notes, for a quick cost-basis
+    /// total. Notes without a recorded fiat value (no price series covered
+    /// their acquisition date) contribute nothing, so this understates the
+    /// true basis until every note has been priced.
+    pub fn total_fiat_basis(&self) -> f64 {
+        self.unspent_notes()
+            .iter()
+            .filter_map(|n| n.acquired_fiat_value)
+            .sum()
+    }
+
+    /// Split `total_fiat_basis` by pool, the fiat counterpart to
+    /// `balance_by_pool`.
+    pub fn fiat_basis_by_pool(&self) -> std::collections::HashMap<Pool, f64> {
+        let mut basis = std::collections::HashMap::new();
+        for note in self.unspent_notes() {
+            if let Some(value) = note.acquired_fiat_value {
+                *basis.entry(note.pool).or_insert(0.0) += value;
+            }
+        }
+        basis
+    }
+
+    /// Split unspent value per pool by spendability, as of `chain_tip_height`
+    /// and requiring `min_confirmations` to consider a note matured.
+    ///
+    /// A note's confirmation depth is `chain_tip_height - received_height +
+    /// 1`; a note with no `received_height` recorded is treated as
+    /// unconfirmed. Matured notes (any scope) count toward `spendable_value`
+    /// - once confirmed, change spends just like anything else. Immature
+    /// notes split by scope: `KeyScope::Internal` change goes to
+    /// `change_pending_confirmation`, `KeyScope::External` receives go to
+    /// `value_pending_spendability`.
+    pub fn account_balance(&self, chain_tip_height: u32, min_confirmations: u32) -> AccountBalance {
+        let mut by_pool: HashMap<Pool, Balance> = HashMap::new();
+        for note in self.unspent_notes() {
+            let balance = by_pool.entry(note.pool).or_default();
+            let matured = note
+                .received_height
+                .is_some_and(|h| chain_tip_height.saturating_sub(h) + 1 >= min_confirmations);
+            if matured {
+                balance.spendable_value += note.value;
+            } else if note.scope == KeyScope::Internal {
+                balance.change_pending_confirmation += note.value;
+            } else {
+                balance.value_pending_spendability += note.value;
+            }
+        }
+        AccountBalance { by_pool }
+    }
+
+    /// Get all notes this wallet actually received as income, excluding
+    /// wallet-internal change (`KeyScope::Internal`) - regardless of
+    /// whether they've since been spent, since income is recognized at
+    /// receipt, not at disposal.
+    pub fn received_income_notes(&self) -> Vec<&StoredNote> {
+        self.notes
+            .iter()
+            .filter(|n| !n.is_change() && n.has_value())
+            .collect()
+    }
+
     /// Get all notes for a specific wallet.
     pub fn notes_for_wallet(&self, wallet_id: &str) -> Vec<&StoredNote> {
         self.notes
@@ -858,10 +1721,24 @@ mod tests {
     #[test]
     fn test_stored_note_generate_id() {
         let id = StoredNote::generate_id("abc123", Pool::Orchard, 5);
-        assert_eq!(id, "abc123-orchard-5");
+        assert_eq!(id.to_string(), "abc123-orchard-5");
 
         let id = StoredNote::generate_id("def456", Pool::Transparent, 0);
-        assert_eq!(id, "def456-transparent-0");
+        assert_eq!(id.to_string(), "def456-transparent-0");
+    }
+
+    #[test]
+    fn test_note_id_round_trips_through_display_and_from_str() {
+        let id = NoteId::new("abc123", Pool::Orchard, 5);
+        let parsed: NoteId = id.to_string().parse().unwrap();
+        assert_eq!(parsed, id);
+    }
+
+    #[test]
+    fn test_note_id_from_str_rejects_malformed_input() {
+        assert!("abc123-orchard".parse::<NoteId>().is_err());
+        assert!("abc123-unknownpool-5".parse::<NoteId>().is_err());
+        assert!("abc123-orchard-notanumber".parse::<NoteId>().is_err());
     }
 
     #[test]
@@ -872,8 +1749,11 @@ mod tests {
             value: 100_000_000,
             commitment: "cmu123".to_string(),
             nullifier: Some("nf456".to_string()),
-            memo: Some("test memo".to_string()),
+            memo: Some(MemoContents::Text("test memo".to_string())),
             address: Some("zs1addr".to_string()),
+            direction: Some(TransferDirection::Incoming),
+            position: Some(42),
+            scope: Some(KeyScope::Internal),
         };
 
         let stored = StoredNote::from_scanned_note(
@@ -883,7 +1763,7 @@ mod tests {
             "2024-01-01T00:00:00Z",
         );
 
-        assert_eq!(stored.id, "txid789-sapling-2");
+        assert_eq!(stored.id.to_string(), "txid789-sapling-2");
         assert_eq!(stored.wallet_id, "wallet_123");
         assert_eq!(stored.txid, "txid789");
         assert_eq!(stored.output_index, 2);
@@ -895,12 +1775,34 @@ mod tests {
         assert_eq!(stored.address, Some("zs1addr".to_string()));
         assert_eq!(stored.spent_txid, None);
         assert_eq!(stored.created_at, "2024-01-01T00:00:00Z");
+        assert_eq!(stored.scope, KeyScope::Internal);
+    }
+
+    #[test]
+    fn test_stored_note_from_scanned_note_defaults_to_external_scope() {
+        let scanned = ScannedNote {
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 50_000_000,
+            commitment: "cmx".to_string(),
+            nullifier: None,
+            memo: None,
+            address: None,
+            direction: Some(TransferDirection::Incoming),
+            position: None,
+            scope: None,
+        };
+
+        let stored =
+            StoredNote::from_scanned_note(&scanned, "txid1", "wallet_1", "2024-01-01T00:00:00Z");
+
+        assert_eq!(stored.scope, KeyScope::External);
     }
 
     #[test]
     fn test_stored_note_is_spent() {
         let mut note = StoredNote {
-            id: "test-orchard-0".to_string(),
+            id: NoteId::new("test", Pool::Orchard, 0),
             wallet_id: "wallet_1".to_string(),
             txid: "test".to_string(),
             output_index: 0,
@@ -912,19 +1814,34 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         };
 
         assert!(!note.is_spent());
         assert!(note.has_value());
 
         note.spent_txid = Some("spending_tx".to_string());
+        note.status = NoteStatus::PendingSpent;
+        assert!(!note.is_spent());
+        assert!(note.is_pending_spent());
+
+        note.status = NoteStatus::Spent;
         assert!(note.is_spent());
+        assert!(!note.is_pending_spent());
     }
 
     #[test]
     fn test_stored_note_serialization_roundtrip() {
         let note = StoredNote {
-            id: "txid-orchard-0".to_string(),
+            id: NoteId::new("txid", Pool::Orchard, 0),
             wallet_id: "wallet_123".to_string(),
             txid: "txid".to_string(),
             output_index: 0,
@@ -936,6 +1853,15 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T12:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: Some(123),
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         };
 
         let json = serde_json::to_string(&note).unwrap();
@@ -952,7 +1878,7 @@ mod tests {
         let mut collection = NoteCollection::new();
 
         let note1 = StoredNote {
-            id: "tx1-orchard-0".to_string(),
+            id: NoteId::new("tx1", Pool::Orchard, 0),
             wallet_id: "w1".to_string(),
             txid: "tx1".to_string(),
             output_index: 0,
@@ -964,6 +1890,15 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         };
 
         // Add new note
@@ -983,7 +1918,7 @@ mod tests {
         let mut collection = NoteCollection::new();
 
         collection.notes.push(StoredNote {
-            id: "tx1-orchard-0".to_string(),
+            id: NoteId::new("tx1", Pool::Orchard, 0),
             wallet_id: "w1".to_string(),
             txid: "tx1".to_string(),
             output_index: 0,
@@ -995,10 +1930,19 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         });
 
         collection.notes.push(StoredNote {
-            id: "tx2-sapling-0".to_string(),
+            id: NoteId::new("tx2", Pool::Sapling, 0),
             wallet_id: "w1".to_string(),
             txid: "tx2".to_string(),
             output_index: 0,
@@ -1010,6 +1954,15 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         });
 
         let nullifiers = vec![SpentNullifier {
@@ -1017,10 +1970,185 @@ mod tests {
             nullifier: "nf1".to_string(),
         }];
 
-        let marked = collection.mark_spent_by_nullifiers(&nullifiers, "spending_tx");
+        // Unconfirmed spend: moves to PendingSpent, not Spent, and can't be
+        // re-marked while pending.
+        let marked = collection.mark_spent_by_nullifiers(&nullifiers, "spending_tx", false, None);
         assert_eq!(marked, 1);
-        assert!(collection.notes[0].is_spent());
+        assert!(!collection.notes[0].is_spent());
+        assert!(collection.notes[0].is_pending_spent());
         assert!(!collection.notes[1].is_spent());
+
+        let marked_again =
+            collection.mark_spent_by_nullifiers(&nullifiers, "spending_tx", true, Some(100));
+        assert_eq!(marked_again, 0);
+        assert!(collection.notes[0].is_pending_spent());
+    }
+
+    #[test]
+    fn test_note_collection_mark_spent_by_nullifiers_confirmed() {
+        let mut collection = NoteCollection::new();
+
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx1", Pool::Orchard, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx1".to_string(),
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 1000,
+            commitment: None,
+            nullifier: Some("nf1".to_string()),
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        let nullifiers = vec![SpentNullifier {
+            pool: Pool::Orchard,
+            nullifier: "nf1".to_string(),
+        }];
+
+        let marked =
+            collection.mark_spent_by_nullifiers(&nullifiers, "spending_tx", true, Some(500));
+        assert_eq!(marked, 1);
+        assert!(collection.notes[0].is_spent());
+        assert_eq!(collection.notes[0].confirmation_height, Some(500));
+    }
+
+    #[test]
+    fn test_note_collection_record_transaction_fee() {
+        let mut collection = NoteCollection::new();
+
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx1", Pool::Orchard, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx1".to_string(),
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 1000,
+            commitment: None,
+            nullifier: Some("nf1".to_string()),
+            memo: None,
+            address: None,
+            spent_txid: Some("spending_tx".to_string()),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Spent,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx2", Pool::Sapling, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx2".to_string(),
+            output_index: 0,
+            pool: Pool::Sapling,
+            value: 2000,
+            commitment: None,
+            nullifier: Some("nf2".to_string()),
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        let updated = collection.record_transaction_fee("spending_tx", 1_000);
+        assert_eq!(updated, 1);
+        assert_eq!(collection.notes[0].fee_zat, Some(1_000));
+        assert_eq!(collection.notes[1].fee_zat, None);
+    }
+
+    #[test]
+    fn test_note_collection_received_income_notes_excludes_internal_scope() {
+        let mut collection = NoteCollection::new();
+
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx1", Pool::Orchard, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx1".to_string(),
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 1000,
+            commitment: None,
+            nullifier: Some("nf1".to_string()),
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        // Change returned to ourselves - not income, even though it's still
+        // spendable balance.
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx1", Pool::Orchard, 1),
+            wallet_id: "w1".to_string(),
+            txid: "tx1".to_string(),
+            output_index: 1,
+            pool: Pool::Orchard,
+            value: 500,
+            commitment: None,
+            nullifier: Some("nf2".to_string()),
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::Internal,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        let income = collection.received_income_notes();
+        assert_eq!(income.len(), 1);
+        assert_eq!(income[0].id.to_string(), "tx1-orchard-0");
+    }
+
+    #[test]
+    fn test_is_change_reflects_scope() {
+        let mut note = pending_note("tx1", NoteStatus::Confirmed, None);
+        note.scope = KeyScope::External;
+        assert!(!note.is_change());
+
+        note.scope = KeyScope::Internal;
+        assert!(note.is_change());
     }
 
     #[test]
@@ -1028,7 +2156,7 @@ mod tests {
         let mut collection = NoteCollection::new();
 
         collection.notes.push(StoredNote {
-            id: "tx1-transparent-0".to_string(),
+            id: NoteId::new("tx1", Pool::Transparent, 0),
             wallet_id: "w1".to_string(),
             txid: "tx1".to_string(),
             output_index: 0,
@@ -1040,6 +2168,15 @@ mod tests {
             address: Some("t1addr".to_string()),
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         });
 
         let spends = vec![TransparentSpend {
@@ -1047,9 +2184,10 @@ mod tests {
             prevout_index: 0,
         }];
 
-        let marked = collection.mark_spent_by_transparent(&spends, "spending_tx");
+        let marked = collection.mark_spent_by_transparent(&spends, "spending_tx", true, Some(42));
         assert_eq!(marked, 1);
         assert!(collection.notes[0].is_spent());
+        assert_eq!(collection.notes[0].confirmation_height, Some(42));
     }
 
     #[test]
@@ -1058,7 +2196,7 @@ mod tests {
 
         // Unspent orchard note
         collection.notes.push(StoredNote {
-            id: "tx1-orchard-0".to_string(),
+            id: NoteId::new("tx1", Pool::Orchard, 0),
             wallet_id: "w1".to_string(),
             txid: "tx1".to_string(),
             output_index: 0,
@@ -1070,11 +2208,20 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         });
 
         // Unspent sapling note
         collection.notes.push(StoredNote {
-            id: "tx2-sapling-0".to_string(),
+            id: NoteId::new("tx2", Pool::Sapling, 0),
             wallet_id: "w1".to_string(),
             txid: "tx2".to_string(),
             output_index: 0,
@@ -1086,11 +2233,20 @@ mod tests {
             address: None,
             spent_txid: None,
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         });
 
         // Spent note (should not count)
         collection.notes.push(StoredNote {
-            id: "tx3-orchard-0".to_string(),
+            id: NoteId::new("tx3", Pool::Orchard, 0),
             wallet_id: "w1".to_string(),
             txid: "tx3".to_string(),
             output_index: 0,
@@ -1102,16 +2258,263 @@ mod tests {
             address: None,
             spent_txid: Some("tx4".to_string()),
             created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Spent,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        // Pending-spent note (in flight: neither spendable nor gone)
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx5", Pool::Sapling, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx5".to_string(),
+            output_index: 0,
+            pool: Pool::Sapling,
+            value: 750,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: Some("tx6".to_string()),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::PendingSpent,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
         });
 
         assert_eq!(collection.total_balance(), 3000);
         assert_eq!(collection.unspent_notes().len(), 2);
+        assert_eq!(collection.pending_spent_balance(), 750);
+        assert_eq!(collection.pending_spent_notes().len(), 1);
 
         let by_pool = collection.balance_by_pool();
         assert_eq!(*by_pool.get(&Pool::Orchard).unwrap_or(&0), 1000);
         assert_eq!(*by_pool.get(&Pool::Sapling).unwrap_or(&0), 2000);
     }
 
+    #[test]
+    fn test_note_collection_account_balance_splits_by_maturity_and_scope() {
+        let mut collection = NoteCollection::new();
+
+        // Matured external receive: spendable.
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx1", Pool::Orchard, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx1".to_string(),
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 1000,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: Some(100),
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        // Unmatured external receive: pending spendability.
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx2", Pool::Orchard, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx2".to_string(),
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 2000,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: Some(108),
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        // Unmatured internal change: pending confirmation.
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx2", Pool::Orchard, 1),
+            wallet_id: "w1".to_string(),
+            txid: "tx2".to_string(),
+            output_index: 1,
+            pool: Pool::Orchard,
+            value: 500,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::Internal,
+            received_height: Some(108),
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        // Matured internal change: spendable, just like any other confirmed note.
+        collection.notes.push(StoredNote {
+            id: NoteId::new("tx3", Pool::Orchard, 0),
+            wallet_id: "w1".to_string(),
+            txid: "tx3".to_string(),
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 300,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: None,
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status: NoteStatus::Confirmed,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::Internal,
+            received_height: Some(95),
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        });
+
+        // chain tip 109, min_confirmations 10 -> matured iff received at or before height 100.
+        let balance = collection.account_balance(109, 10);
+        let orchard = balance.by_pool.get(&Pool::Orchard).cloned().unwrap_or_default();
+
+        assert_eq!(orchard.spendable_value, 1300);
+        assert_eq!(orchard.value_pending_spendability, 2000);
+        assert_eq!(orchard.change_pending_confirmation, 500);
+    }
+
+    fn pending_note(id: &str, status: NoteStatus, spent_txid: Option<&str>) -> StoredNote {
+        StoredNote {
+            id: NoteId::new(id, Pool::Orchard, 0),
+            wallet_id: "w1".to_string(),
+            txid: id.to_string(),
+            output_index: 0,
+            pool: Pool::Orchard,
+            value: 1000,
+            commitment: None,
+            nullifier: None,
+            memo: None,
+            address: None,
+            spent_txid: spent_txid.map(|t| t.to_string()),
+            created_at: "2024-01-01T00:00:00Z".to_string(),
+            position: None,
+            witness: None,
+            status,
+            confirmation_height: None,
+            fee_zat: None,
+            scope: KeyScope::External,
+            received_height: None,
+            acquired_fiat_value: None,
+            fiat_currency: None,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_promotes_confirmed_txids() {
+        let mut collection = NoteCollection::new();
+        collection
+            .notes
+            .push(pending_note("rx1", NoteStatus::PendingConfirmation, None));
+        collection
+            .notes
+            .push(pending_note("rx2", NoteStatus::PendingSpent, Some("spend1")));
+
+        let mut confirmed = std::collections::HashSet::new();
+        confirmed.insert("rx1".to_string());
+        confirmed.insert("spend1".to_string());
+        collection.reconcile(&confirmed, &std::collections::HashSet::new());
+
+        assert_eq!(collection.notes[0].status, NoteStatus::Confirmed);
+        assert_eq!(collection.notes[1].status, NoteStatus::Spent);
+    }
+
+    #[test]
+    fn test_reconcile_reverts_dropped_spend_to_expired() {
+        let mut collection = NoteCollection::new();
+        collection
+            .notes
+            .push(pending_note("rx1", NoteStatus::PendingSpent, Some("spend1")));
+
+        // "spend1" is in neither the confirmed nor the mempool set - it was
+        // actually dropped, so the spend reverts and the note is spendable
+        // again.
+        collection.reconcile(
+            &std::collections::HashSet::new(),
+            &std::collections::HashSet::new(),
+        );
+
+        assert_eq!(collection.notes[0].status, NoteStatus::Expired);
+        assert_eq!(collection.notes[0].spent_txid, None);
+    }
+
+    #[test]
+    fn test_reconcile_leaves_still_broadcast_spend_pending() {
+        let mut collection = NoteCollection::new();
+        collection
+            .notes
+            .push(pending_note("rx1", NoteStatus::PendingSpent, Some("spend1")));
+
+        // "spend1" hasn't confirmed yet, but it's still sitting in the
+        // mempool - calling reconcile right after broadcast must not treat
+        // this as dropped and re-expose the note as spendable (that would
+        // be a double-spend risk).
+        let mut mempool = std::collections::HashSet::new();
+        mempool.insert("spend1".to_string());
+        collection.reconcile(&std::collections::HashSet::new(), &mempool);
+
+        assert_eq!(collection.notes[0].status, NoteStatus::PendingSpent);
+        assert_eq!(collection.notes[0].spent_txid.as_deref(), Some("spend1"));
+    }
+
+    #[test]
+    fn test_reconcile_leaves_unconfirmed_receipt_pending() {
+        let mut collection = NoteCollection::new();
+        collection
+            .notes
+            .push(pending_note("rx1", NoteStatus::PendingConfirmation, None));
+
+        collection.reconcile(
+            &std::collections::HashSet::new(),
+            &std::collections::HashSet::new(),
+        );
+
+        assert_eq!(collection.notes[0].status, NoteStatus::PendingConfirmation);
+    }
+
     // ========================================================================
     // StoredWallet tests
     // ========================================================================
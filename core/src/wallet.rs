@@ -0,0 +1,258 @@
+//! Wallet generation, restoration, and address derivation.
+//!
+//! This module turns a BIP39 seed phrase into the key material and
+//! addresses a wallet needs: a [`UnifiedSpendingKey`]/[`UnifiedFullViewingKey`]
+//! pair for the requested account, the account's default unified and
+//! transparent addresses, and batches of further diversified addresses for
+//! scanning.
+
+use bip39::{Language, Mnemonic};
+use serde::{Deserialize, Serialize};
+use zcash_keys::encoding::AddressCodec;
+use zcash_keys::keys::{UnifiedAddressRequest, UnifiedFullViewingKey, UnifiedSpendingKey};
+use zcash_protocol::consensus::Network;
+use zcash_transparent::keys::IncomingViewingKey;
+use zip32::{AccountId, DiversifierIndex};
+
+use crate::types::{DerivedAddress, NetworkKind};
+
+/// Errors that can occur while generating, restoring, or deriving addresses
+/// for a wallet.
+#[derive(Debug)]
+pub enum WalletError {
+    /// Invalid seed phrase.
+    InvalidSeedPhrase(String),
+    /// The account index doesn't fit in a ZIP 32 [`AccountId`].
+    InvalidAccountIndex(u32),
+    /// Failed to derive a spending key from the seed.
+    SpendingKeyDerivation(String),
+    /// Failed to derive an address from a viewing key.
+    AddressDerivation(String),
+    /// The decimal diversifier index string couldn't be parsed, or is
+    /// outside the valid 88-bit range.
+    InvalidDiversifierIndex(String),
+    /// The diversifier index range was exhausted before `count` valid
+    /// addresses could be produced.
+    DiversifierIndexExhausted,
+}
+
+impl core::fmt::Display for WalletError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidSeedPhrase(msg) => write!(f, "Invalid seed phrase: {}", msg),
+            Self::InvalidAccountIndex(account) => write!(f, "Invalid account index: {}", account),
+            Self::SpendingKeyDerivation(msg) => {
+                write!(f, "Failed to derive spending key: {}", msg)
+            }
+            Self::AddressDerivation(msg) => write!(f, "Failed to derive address: {}", msg),
+            Self::InvalidDiversifierIndex(raw) => {
+                write!(f, "Invalid diversifier index: {}", raw)
+            }
+            Self::DiversifierIndexExhausted => {
+                write!(
+                    f,
+                    "Diversifier index range exhausted before enough valid addresses were found"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for WalletError {}
+
+/// Key material and default addresses for one account of a wallet.
+#[derive(Debug, Clone)]
+pub struct WalletInfo {
+    /// The wallet's BIP39 seed phrase.
+    pub seed_phrase: String,
+    /// The network this wallet's addresses were encoded for.
+    pub network: NetworkKind,
+    /// The ZIP 32 account index this key material was derived at.
+    pub account_index: u32,
+    /// The diversifier index the search for `unified_address` started at.
+    pub address_index: u32,
+    /// The account's unified address at the first diversifier index, at or
+    /// after `address_index`, that yields a valid Sapling diversifier.
+    pub unified_address: String,
+    /// The account's transparent (P2PKH) address, if the unified address
+    /// request included a transparent receiver.
+    pub transparent_address: Option<String>,
+    /// The account's encoded unified full viewing key.
+    pub unified_full_viewing_key: String,
+}
+
+/// A unified address together with the diversifier index that produced it.
+///
+/// Not every diversifier index yields a valid Sapling diversifier, so the
+/// index reported here may skip ahead of a naively incrementing counter -
+/// this is the index callers need to recover the same address later.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiversifiedAddress {
+    /// The diversifier index, as a decimal string since it can be up to 88
+    /// bits wide and wouldn't round-trip exactly through a JSON number.
+    pub diversifier_index: String,
+    /// The unified address produced at `diversifier_index`.
+    pub unified_address: String,
+}
+
+fn account_id(account_index: u32) -> Result<AccountId, WalletError> {
+    AccountId::try_from(account_index).map_err(|_| WalletError::InvalidAccountIndex(account_index))
+}
+
+fn seed_from_phrase(seed_phrase: &str) -> Result<[u8; 64], WalletError> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase.trim())
+        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+    Ok(mnemonic.to_seed(""))
+}
+
+/// Derive an account's unified spending key from a seed, and the
+/// [`WalletInfo`] describing its key material and default addresses.
+pub fn derive_wallet(
+    seed: &[u8],
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    address_index: u32,
+) -> Result<WalletInfo, WalletError> {
+    let account = account_id(account_index)?;
+    let usk = UnifiedSpendingKey::from_seed(&network, seed, account)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let diversifier_index = DiversifierIndex::from(address_index);
+    let (unified_address, _) = ufvk
+        .find_address(diversifier_index, UnifiedAddressRequest::AllAvailableKeys)
+        .map_err(|e| WalletError::AddressDerivation(format!("{:?}", e)))?;
+
+    let transparent_address = ufvk
+        .transparent()
+        .and_then(|tfvk| tfvk.derive_external_ivk().ok())
+        .map(|ivk| ivk.default_address().0.encode(&network));
+
+    Ok(WalletInfo {
+        seed_phrase: seed_phrase.to_string(),
+        network: NetworkKind::from(network),
+        account_index,
+        address_index,
+        unified_address: unified_address.encode(&network),
+        transparent_address,
+        unified_full_viewing_key: ufvk.encode(&network),
+    })
+}
+
+/// Generate a brand-new wallet from random `entropy` (32 bytes, for a
+/// 24-word mnemonic).
+pub fn generate_wallet(
+    entropy: &[u8],
+    network: Network,
+    account_index: u32,
+    address_index: u32,
+) -> Result<WalletInfo, WalletError> {
+    let mnemonic = Mnemonic::from_entropy_in(Language::English, entropy)
+        .map_err(|e| WalletError::InvalidSeedPhrase(e.to_string()))?;
+    let seed_phrase = mnemonic.to_string();
+    let seed = mnemonic.to_seed("");
+    derive_wallet(&seed, &seed_phrase, network, account_index, address_index)
+}
+
+/// Restore a wallet from an existing BIP39 seed phrase.
+pub fn restore_wallet(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    address_index: u32,
+) -> Result<WalletInfo, WalletError> {
+    let seed = seed_from_phrase(seed_phrase)?;
+    derive_wallet(&seed, seed_phrase, network, account_index, address_index)
+}
+
+/// Derive `count` unified addresses starting from `start_index`.
+///
+/// `start_index` is a decimal string parsed into a full 88-bit
+/// [`DiversifierIndex`], since a diversified address's index can exceed
+/// `u64::MAX`. Not every index yields a valid Sapling diversifier, so
+/// invalid indices are skipped internally - the index advances until
+/// `count` valid addresses have been produced, and each address is
+/// reported alongside the true index that produced it.
+pub fn derive_unified_addresses(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    start_index: &str,
+    count: u32,
+) -> Result<Vec<DiversifiedAddress>, WalletError> {
+    let seed = seed_from_phrase(seed_phrase)?;
+    let account = account_id(account_index)?;
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let ufvk = usk.to_unified_full_viewing_key();
+
+    let mut index: u128 = start_index
+        .parse()
+        .map_err(|_| WalletError::InvalidDiversifierIndex(start_index.to_string()))?;
+
+    let mut addresses = Vec::with_capacity(count as usize);
+    while addresses.len() < count as usize {
+        let diversifier_index = DiversifierIndex::try_from(index)
+            .map_err(|_| WalletError::InvalidDiversifierIndex(index.to_string()))?;
+
+        // `find_address` searches forward from `diversifier_index`, so only
+        // accept a hit at exactly this index; otherwise it is invalid.
+        match ufvk.find_address(diversifier_index, UnifiedAddressRequest::AllAvailableKeys) {
+            Ok((ua, found)) if found == diversifier_index => {
+                addresses.push(DiversifiedAddress {
+                    diversifier_index: index.to_string(),
+                    unified_address: ua.encode(&network),
+                });
+            }
+            _ => {}
+        }
+
+        index = index
+            .checked_add(1)
+            .ok_or(WalletError::DiversifierIndexExhausted)?;
+    }
+
+    Ok(addresses)
+}
+
+/// Derive `count` transparent (BIP44 external-chain) addresses starting from
+/// `start_index`.
+pub fn derive_transparent_addresses(
+    seed_phrase: &str,
+    network: Network,
+    account_index: u32,
+    start_index: u32,
+    count: u32,
+) -> Result<Vec<DerivedAddress>, WalletError> {
+    let seed = seed_from_phrase(seed_phrase)?;
+    let account = account_id(account_index)?;
+    let usk = UnifiedSpendingKey::from_seed(&network, &seed, account)
+        .map_err(|e| WalletError::SpendingKeyDerivation(format!("{:?}", e)))?;
+    let ufvk = usk.to_unified_full_viewing_key();
+    let tfvk = ufvk
+        .transparent()
+        .ok_or_else(|| WalletError::AddressDerivation("no transparent receiver".to_string()))?;
+    let ivk = tfvk
+        .derive_external_ivk()
+        .map_err(|e| WalletError::AddressDerivation(format!("{:?}", e)))?;
+
+    let mut addresses = Vec::with_capacity(count as usize);
+    for offset in 0..count {
+        let address_index = start_index
+            .checked_add(offset)
+            .ok_or(WalletError::DiversifierIndexExhausted)?;
+        let child_index = zcash_transparent::keys::NonHardenedChildIndex::from_index(address_index)
+            .ok_or(WalletError::InvalidDiversifierIndex(address_index.to_string()))?;
+        let address = ivk
+            .derive_address(child_index)
+            .map_err(|e| WalletError::AddressDerivation(format!("{:?}", e)))?;
+        addresses.push(DerivedAddress {
+            wallet_id: String::new(),
+            address_index,
+            address: address.encode(&network),
+        });
+    }
+
+    Ok(addresses)
+}
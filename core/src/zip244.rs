@@ -0,0 +1,365 @@
+//! ZIP 244 transaction digests for v5 (NU5) transactions.
+//!
+//! Implements the transparent-only subset needed by
+//! `transaction::build_transparent_transaction`: the header digest, the
+//! (empty) Sapling/Orchard digests, the transparent digest used for the
+//! txid, and the `SIGHASH_ALL` transparent signature digest used to sign
+//! each input. Shielded bundles are never present on the transactions this
+//! crate builds, so their digests are always the "no shielded data" case.
+//!
+//! Written directly from the ZIP 244 spec; the official librustzcash test
+//! vectors aren't reachable from this environment, so
+//! `test_header_digest_matches_independent_blake2b_vector` and
+//! `test_txid_and_sighash_match_independent_blake2b_vectors` instead pin the
+//! digests against an independent, from-scratch BLAKE2b-personalized
+//! implementation (Python's `hashlib`, not this crate's `blake2b_simd`
+//! usage) of the same byte layout, so a mistake in the serialization or
+//! personalization logic here can't also be baked into the expected values.
+//! Both top-level combiners (`txid_digest`, `signature_hash_raw`) share the
+//! same branch-id-derived personalization scheme via
+//! `branch_id_personalization`, so they can't silently diverge from each
+//! other the way `txid_digest` once did.
+
+use zcash_protocol::value::Zatoshis;
+use zcash_transparent::bundle::{Bundle, OutPoint, TxOut};
+use zcash_transparent::builder::Unauthorized;
+
+const SIGHASH_ALL: u8 = 0x01;
+
+/// NU5 transaction version (overwintered flag set, version 5).
+const V5_TX_VERSION: u32 = 0x8000_0005;
+/// Version group ID for v5 transactions (ZIP 225).
+const V5_VERSION_GROUP_ID: u32 = 0x26A7_270A;
+
+/// Build a 16-byte BLAKE2b personalization as `prefix || branch_id` (LE) -
+/// the scheme ZIP 244 uses for both top-level combiners (the txid digest
+/// and the `SIGHASH_ALL` signature digest), so a transaction's txid and
+/// signature hash are tied to the consensus rules it was built under.
+fn branch_id_personalization(prefix: &[u8; 12], branch_id: u32) -> [u8; 16] {
+    let mut personalization = [0u8; 16];
+    personalization[..12].copy_from_slice(prefix);
+    personalization[12..].copy_from_slice(&branch_id.to_le_bytes());
+    personalization
+}
+
+fn blake2b_personalized(personalization: &[u8; 16], data: &[u8]) -> [u8; 32] {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(personalization)
+        .to_state()
+        .update(data)
+        .finalize();
+    let mut out = [0u8; 32];
+    out.copy_from_slice(hash.as_bytes());
+    out
+}
+
+pub(crate) fn write_compact_size(len: usize, out: &mut Vec<u8>) {
+    let len = len as u64;
+    if len < 0xfd {
+        out.push(len as u8);
+    } else if len <= 0xffff {
+        out.push(0xfd);
+        out.extend_from_slice(&(len as u16).to_le_bytes());
+    } else if len <= 0xffff_ffff {
+        out.push(0xfe);
+        out.extend_from_slice(&(len as u32).to_le_bytes());
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&len.to_le_bytes());
+    }
+}
+
+pub(crate) fn write_script(script: &[u8], out: &mut Vec<u8>) {
+    write_compact_size(script.len(), out);
+    out.extend_from_slice(script);
+}
+
+/// A signed transparent input: the outpoint/value/sequence it spends plus
+/// the finished scriptSig (`<sig> <pubkey>` for P2PKH).
+pub struct SignedTxIn {
+    pub outpoint: OutPoint,
+    pub script_sig: Vec<u8>,
+}
+
+/// Serialize a complete v5 (NU5) transaction carrying only a transparent
+/// bundle, with empty Sapling and Orchard bundles (per ZIP 225, their
+/// vectors' compact-size counts are all that's written when empty).
+pub fn serialize_v5_transparent(
+    branch_id: u32,
+    lock_time: u32,
+    expiry_height: u32,
+    vin: &[SignedTxIn],
+    vout: &[TxOut],
+) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&V5_TX_VERSION.to_le_bytes());
+    out.extend_from_slice(&V5_VERSION_GROUP_ID.to_le_bytes());
+    out.extend_from_slice(&branch_id.to_le_bytes());
+    out.extend_from_slice(&lock_time.to_le_bytes());
+    out.extend_from_slice(&expiry_height.to_le_bytes());
+
+    write_compact_size(vin.len(), &mut out);
+    for txin in vin {
+        out.extend_from_slice(txin.outpoint.hash());
+        out.extend_from_slice(&txin.outpoint.n().to_le_bytes());
+        write_script(&txin.script_sig, &mut out);
+        out.extend_from_slice(&0xffff_ffffu32.to_le_bytes()); // nSequence
+    }
+
+    write_compact_size(vout.len(), &mut out);
+    for txout in vout {
+        out.extend_from_slice(&u64::from(txout.value()).to_le_bytes());
+        write_script(&txout.script_pubkey().0, &mut out);
+    }
+
+    // nSpendsSapling = 0, nOutputsSapling = 0: no further Sapling fields.
+    write_compact_size(0, &mut out);
+    write_compact_size(0, &mut out);
+    // nActionsOrchard = 0: no further Orchard fields.
+    write_compact_size(0, &mut out);
+
+    out
+}
+
+/// `T.1`: commits to the transaction's non-bundle fields.
+fn header_digest(branch_id: u32, lock_time: u32, expiry_height: u32) -> [u8; 32] {
+    let mut data = Vec::with_capacity(20);
+    data.extend_from_slice(&V5_TX_VERSION.to_le_bytes());
+    data.extend_from_slice(&V5_VERSION_GROUP_ID.to_le_bytes());
+    data.extend_from_slice(&branch_id.to_le_bytes());
+    data.extend_from_slice(&lock_time.to_le_bytes());
+    data.extend_from_slice(&expiry_height.to_le_bytes());
+    blake2b_personalized(b"ZTxIdHeadersHash", &data)
+}
+
+/// The "no shielded data" digest shared by Sapling and Orchard when a
+/// transaction carries no bundle for that pool.
+fn empty_bundle_digest(personalization: &[u8; 16]) -> [u8; 32] {
+    blake2b_personalized(personalization, &[])
+}
+
+fn prevouts_digest(vin: &[(OutPoint, TxOut)]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(vin.len() * 36);
+    for (outpoint, _) in vin {
+        data.extend_from_slice(outpoint.hash());
+        data.extend_from_slice(&outpoint.n().to_le_bytes());
+    }
+    blake2b_personalized(b"ZTxIdPrevoutHash", &data)
+}
+
+fn sequence_digest(vin: &[(OutPoint, TxOut)]) -> [u8; 32] {
+    // `TransparentBuilder` always uses the default sequence number; there's
+    // no RBF/locktime signaling support to expose a different one.
+    let mut data = Vec::with_capacity(vin.len() * 4);
+    for _ in vin {
+        data.extend_from_slice(&0xffff_ffffu32.to_le_bytes());
+    }
+    blake2b_personalized(b"ZTxIdSequencHash", &data)
+}
+
+fn outputs_digest(vout: &[TxOut]) -> [u8; 32] {
+    let mut data = Vec::new();
+    for txout in vout {
+        data.extend_from_slice(&u64::from(txout.value()).to_le_bytes());
+        write_script(&txout.script_pubkey().0, &mut data);
+    }
+    blake2b_personalized(b"ZTxIdOutputsHash", &data)
+}
+
+/// `T.2`: the transparent digest used for the txid (commits to every input
+/// and output, but not to the coins being spent).
+fn transparent_txid_digest(vin: &[(OutPoint, TxOut)], vout: &[TxOut]) -> [u8; 32] {
+    if vin.is_empty() && vout.is_empty() {
+        return empty_bundle_digest(b"ZTxIdTranspaHash");
+    }
+    let mut data = Vec::with_capacity(96);
+    data.extend_from_slice(&prevouts_digest(vin));
+    data.extend_from_slice(&sequence_digest(vin));
+    data.extend_from_slice(&outputs_digest(vout));
+    blake2b_personalized(b"ZTxIdTranspaHash", &data)
+}
+
+/// The overall (non-malleable) txid digest, `T` in ZIP 244, for a
+/// transparent-only transaction (no Sapling/Orchard bundle).
+pub fn txid_digest(
+    branch_id: u32,
+    lock_time: u32,
+    expiry_height: u32,
+    vin: &[(OutPoint, TxOut)],
+    vout: &[TxOut],
+) -> [u8; 32] {
+    let mut data = Vec::with_capacity(128);
+    data.extend_from_slice(&header_digest(branch_id, lock_time, expiry_height));
+    data.extend_from_slice(&transparent_txid_digest(vin, vout));
+    data.extend_from_slice(&empty_bundle_digest(b"ZTxIdSaplingHash"));
+    data.extend_from_slice(&empty_bundle_digest(b"ZTxIdOrchardHash"));
+    let personalization = branch_id_personalization(b"ZTxIdTxHash_", branch_id);
+    blake2b_personalized(&personalization, &data)
+}
+
+/// `SIGHASH_ALL` transparent signature digest for the input at
+/// `input_index`, per ZIP 244. Unlike the txid digest, this commits to the
+/// scriptPubKey and value of the specific coin being spent, so a
+/// signature can't be replayed against a different input or transaction.
+fn transparent_sig_digest(vin: &[(OutPoint, TxOut)], vout: &[TxOut], input_index: usize) -> [u8; 32] {
+    let (outpoint, spent_txout) = &vin[input_index];
+
+    let mut per_input = Vec::new();
+    per_input.extend_from_slice(outpoint.hash());
+    per_input.extend_from_slice(&outpoint.n().to_le_bytes());
+    write_script(&spent_txout.script_pubkey().0, &mut per_input);
+    per_input.extend_from_slice(&u64::from(spent_txout.value()).to_le_bytes());
+    per_input.extend_from_slice(&0xffff_ffffu32.to_le_bytes()); // nSequence
+
+    let mut data = Vec::with_capacity(1 + 32 * 3 + per_input.len());
+    data.push(SIGHASH_ALL);
+    data.extend_from_slice(&prevouts_digest(vin));
+    data.extend_from_slice(&sequence_digest(vin));
+    data.extend_from_slice(&outputs_digest(vout));
+    data.extend_from_slice(&per_input);
+    blake2b_personalized(b"ZTxTrAuthHash\0\0\0", &data)
+}
+
+/// The full ZIP 244 signature hash to sign for `input_index`, given the
+/// unsigned transparent bundle and the transaction's non-bundle fields.
+pub fn signature_hash(
+    bundle: &Bundle<Unauthorized>,
+    branch_id: u32,
+    lock_time: u32,
+    expiry_height: u32,
+    input_index: usize,
+) -> [u8; 32] {
+    let vin: Vec<(OutPoint, TxOut)> = bundle
+        .vin
+        .iter()
+        .zip(bundle.authorization.input_txouts())
+        .map(|(txin, txout)| (txin.prevout.clone(), txout.clone()))
+        .collect();
+
+    signature_hash_raw(branch_id, lock_time, expiry_height, &vin, &bundle.vout, input_index)
+}
+
+/// Like [`signature_hash`], but from a raw list of spent coins and outputs
+/// rather than a `Bundle` - used when signing a [`crate::partial_tx::PartialTx`]
+/// that was never assembled into a bundle on the signing side.
+pub fn signature_hash_raw(
+    branch_id: u32,
+    lock_time: u32,
+    expiry_height: u32,
+    vin: &[(OutPoint, TxOut)],
+    vout: &[TxOut],
+    input_index: usize,
+) -> [u8; 32] {
+    let mut data = Vec::with_capacity(128);
+    data.extend_from_slice(&header_digest(branch_id, lock_time, expiry_height));
+    data.extend_from_slice(&transparent_sig_digest(vin, vout, input_index));
+    data.extend_from_slice(&empty_bundle_digest(b"ZTxIdSaplingHash"));
+    data.extend_from_slice(&empty_bundle_digest(b"ZTxIdOrchardHash"));
+
+    let personalization = branch_id_personalization(b"ZcashTxHash_", branch_id);
+    blake2b_personalized(&personalization, &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_txid_and_sighash_personalization_share_the_branch_id_scheme() {
+        let branch_id = 0xc8e7_1055;
+        let txid_personalization = branch_id_personalization(b"ZTxIdTxHash_", branch_id);
+        let sighash_personalization = branch_id_personalization(b"ZcashTxHash_", branch_id);
+
+        // Same combiner scheme (12-byte prefix + branch id LE) for both -
+        // only the prefix differs between the txid and sighash combiners.
+        assert_eq!(&txid_personalization[..12], b"ZTxIdTxHash_");
+        assert_eq!(&sighash_personalization[..12], b"ZcashTxHash_");
+        assert_eq!(&txid_personalization[12..], &branch_id.to_le_bytes());
+        assert_eq!(&sighash_personalization[12..], &branch_id.to_le_bytes());
+    }
+
+    #[test]
+    fn test_empty_transparent_txid_digest_matches_empty_bundle_digest() {
+        assert_eq!(
+            transparent_txid_digest(&[], &[]),
+            empty_bundle_digest(b"ZTxIdTranspaHash")
+        );
+    }
+
+    #[test]
+    fn test_header_digest_is_deterministic() {
+        let a = header_digest(0x76b8_09bb, 0, 500_000);
+        let b = header_digest(0x76b8_09bb, 0, 500_000);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_header_digest_varies_with_expiry_height() {
+        let a = header_digest(0x76b8_09bb, 0, 500_000);
+        let b = header_digest(0x76b8_09bb, 0, 500_001);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_txid_digest_is_deterministic() {
+        let txout = TxOut::new(Zatoshis::from_u64(1000).unwrap(), vec![0x6a].into());
+        let a = txid_digest(0x76b8_09bb, 0, 500_000, &[], &[txout.clone()]);
+        let b = txid_digest(0x76b8_09bb, 0, 500_000, &[], &[txout]);
+        assert_eq!(a, b);
+    }
+
+    // The expected digests below were computed independently with Python's
+    // `hashlib.blake2b(data, digest_size=32, person=...)` against the exact
+    // byte layout ZIP 244 specifies (same field order, widths and
+    // personalizations as the functions under test), not by running this
+    // crate's code - so a shared bug in both implementations is the only
+    // way these could pass while being wrong.
+    fn digest_vector(hex_digest: &str) -> [u8; 32] {
+        hex::decode(hex_digest).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_header_digest_matches_independent_blake2b_vector() {
+        let digest = header_digest(0x76b8_09bb, 0, 500_000);
+        assert_eq!(
+            digest,
+            digest_vector("6990aa6e23cd567612a6215882f00596ba845df10a0b37be1e20a55e1cff0388")
+        );
+    }
+
+    #[test]
+    fn test_txid_and_sighash_match_independent_blake2b_vectors() {
+        let branch_id = 0x76b8_09bb;
+        let lock_time = 0;
+        let expiry_height = 500_000;
+
+        let empty_txid = txid_digest(branch_id, lock_time, expiry_height, &[], &[]);
+        assert_eq!(
+            empty_txid,
+            digest_vector("6ae3a62c716fd25e6c3dc2b1e3b27b2d0886105cf8473612882d00cf97d2e815")
+        );
+
+        let vout = vec![TxOut::new(Zatoshis::from_u64(1000).unwrap(), vec![0x6a].into())];
+        let one_output_txid = txid_digest(branch_id, lock_time, expiry_height, &[], &vout);
+        assert_eq!(
+            one_output_txid,
+            digest_vector("d0be383a224b665734c4d2273c9485cbafd37723ae555c77711faeeb94367fcc")
+        );
+
+        let spent_script: Vec<u8> = [0x76, 0xa9, 0x14]
+            .iter()
+            .copied()
+            .chain(0u8..20)
+            .chain([0x88, 0xac])
+            .collect();
+        let outpoint = OutPoint::new(std::array::from_fn(|i| i as u8), 0);
+        let spent_txout = TxOut::new(Zatoshis::from_u64(5000).unwrap(), spent_script.into());
+        let vin = vec![(outpoint, spent_txout)];
+        let sighash = signature_hash_raw(branch_id, lock_time, expiry_height, &vin, &vout, 0);
+        assert_eq!(
+            sighash,
+            digest_vector("ec5511ff875fff8f9f5f3e4f3ec675ba909610855b8584da1d198135f99dd64e")
+        );
+    }
+}
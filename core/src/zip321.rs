@@ -0,0 +1,417 @@
+//! ZIP 321 payment-request URIs.
+//!
+//! Parses and builds `zcash:` payment-request URIs: a primary address plus
+//! optional amount/memo/label/message, and indexed `address.N`/`amount.N`/...
+//! parameters (`N >= 1`) for additional recipients. Lets the tool ingest a
+//! payment request (e.g. an invoice) and reconcile it against the notes a
+//! wallet actually received, or emit one of its own for a counterparty to
+//! pay.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use zcash_keys::address::UnifiedAddress;
+use zcash_transparent::address::TransparentAddress;
+
+use crate::scanner::decode_memo;
+use crate::types::{MemoContents, NetworkKind};
+
+const ZATOSHI_PER_ZEC: f64 = 100_000_000.0;
+const MEMO_LEN: usize = 512;
+
+/// Errors that can occur while parsing or building a ZIP 321 request.
+#[derive(Debug)]
+pub enum Zip321Error {
+    /// The URI doesn't start with the `zcash:` scheme.
+    MissingScheme,
+    /// A query parameter wasn't a `key=value` pair.
+    MalformedParam(String),
+    /// An `.N` parameter suffix wasn't a valid non-negative integer.
+    InvalidParamIndex(String),
+    /// Payment slot `index` has a parameter but no paired `address`/`address.N`.
+    MissingAddress(u32),
+    /// A percent-encoded query value was malformed or not valid UTF-8.
+    InvalidPercentEncoding(String),
+    /// An `amount`/`amount.N` value wasn't a valid non-negative decimal ZEC amount.
+    InvalidAmount(String),
+    /// A `memo`/`memo.N` value wasn't valid unpadded base64url, or decoded to
+    /// more than the 512-byte memo field can hold.
+    InvalidMemo(String),
+    /// The request contains no payments at all.
+    EmptyRequest,
+    /// `address` doesn't decode as a unified, Sapling, or transparent
+    /// address on `expected`.
+    AddressNetworkMismatch { address: String, expected: NetworkKind },
+}
+
+impl core::fmt::Display for Zip321Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MissingScheme => write!(f, "URI must start with \"zcash:\""),
+            Self::MalformedParam(pair) => write!(f, "malformed query parameter: {}", pair),
+            Self::InvalidParamIndex(key) => write!(f, "invalid parameter index: {}", key),
+            Self::MissingAddress(index) => {
+                write!(f, "payment index {} is missing a paired address", index)
+            }
+            Self::InvalidPercentEncoding(value) => {
+                write!(f, "invalid percent-encoding: {}", value)
+            }
+            Self::InvalidAmount(value) => write!(f, "invalid amount: {}", value),
+            Self::InvalidMemo(value) => write!(f, "invalid memo: {}", value),
+            Self::EmptyRequest => write!(f, "payment request must contain at least one payment"),
+            Self::AddressNetworkMismatch { address, expected } => write!(
+                f,
+                "address {} is not a valid {} address",
+                address,
+                expected.as_str()
+            ),
+        }
+    }
+}
+
+impl core::error::Error for Zip321Error {}
+
+/// A single payment within a ZIP 321 request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Payment {
+    /// Recipient address (transparent, Sapling, or unified).
+    pub address: String,
+    /// Amount in zatoshis, if specified.
+    pub amount_zat: Option<u64>,
+    /// Decoded memo, if specified.
+    pub memo: Option<MemoContents>,
+    /// Percent-decoded human-readable label, if specified.
+    pub label: Option<String>,
+    /// Percent-decoded human-readable message, if specified.
+    pub message: Option<String>,
+}
+
+/// A parsed, or builder-assembled, ZIP 321 payment request of one or more
+/// payments, scoped to a particular network so its addresses can be
+/// validated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionRequest {
+    /// The network every payment's address must belong to.
+    pub network: NetworkKind,
+    /// Every payment in the request, in ascending index order.
+    pub payments: Vec<Payment>,
+}
+
+impl TransactionRequest {
+    /// Parse a `zcash:` URI's payments, without validating addresses - see
+    /// [`TransactionRequest::parse`].
+    fn parse_payments(uri: &str) -> Result<Vec<Payment>, Zip321Error> {
+        let rest = uri.strip_prefix("zcash:").ok_or(Zip321Error::MissingScheme)?;
+
+        let (leading_addr, query) = match rest.split_once('?') {
+            Some((addr, query)) => (addr, query),
+            None => (rest, ""),
+        };
+
+        // Index 0 is the leading (unindexed) address and any unindexed params.
+        let mut slots: BTreeMap<u32, Payment> = BTreeMap::new();
+
+        if !leading_addr.is_empty() {
+            slots.entry(0).or_default().address = percent_decode(leading_addr)?;
+        }
+
+        if !query.is_empty() {
+            for pair in query.split('&') {
+                if pair.is_empty() {
+                    continue;
+                }
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| Zip321Error::MalformedParam(pair.to_string()))?;
+                let (base, index) = split_param_index(key)?;
+                let slot = slots.entry(index).or_default();
+
+                match base {
+                    "address" => slot.address = percent_decode(value)?,
+                    "amount" => slot.amount_zat = Some(parse_zec_amount(value)?),
+                    "memo" => slot.memo = Some(decode_memo_param(value)?),
+                    "label" => slot.label = Some(percent_decode(value)?),
+                    "message" => slot.message = Some(percent_decode(value)?),
+                    // Unknown non-"req-" params must be ignored per ZIP 321;
+                    // unknown "req-" params would need to be rejected, but
+                    // we don't yet implement the required-param mechanism.
+                    _ => {}
+                }
+            }
+        }
+
+        let mut payments = Vec::with_capacity(slots.len());
+        for (index, payment) in slots {
+            if payment.address.is_empty() {
+                return Err(Zip321Error::MissingAddress(index));
+            }
+            payments.push(payment);
+        }
+
+        if payments.is_empty() {
+            return Err(Zip321Error::EmptyRequest);
+        }
+
+        Ok(payments)
+    }
+
+    /// Parse a ZIP 321 `zcash:` URI, validating that every payment's address
+    /// decodes as a unified, Sapling, or transparent address on `network`.
+    pub fn parse(uri: &str, network: NetworkKind) -> Result<Self, Zip321Error> {
+        let payments = Self::parse_payments(uri)?;
+        for payment in &payments {
+            if !address_matches_network(&payment.address, network) {
+                return Err(Zip321Error::AddressNetworkMismatch {
+                    address: payment.address.clone(),
+                    expected: network,
+                });
+            }
+        }
+        Ok(TransactionRequest { network, payments })
+    }
+
+    /// Sum of every payment's `amount_zat` (treating an unset amount as 0).
+    pub fn total(&self) -> u64 {
+        self.payments.iter().filter_map(|p| p.amount_zat).sum()
+    }
+
+    /// Render this request as a ZIP 321 `zcash:` URI.
+    ///
+    /// A single payment is rendered with unindexed parameters; two or more
+    /// payments use indexed parameters (`address.1`, `amount.1`, ...) for
+    /// every payment after the first.
+    pub fn to_uri(&self) -> Result<String, Zip321Error> {
+        let Some(first) = self.payments.first() else {
+            return Err(Zip321Error::EmptyRequest);
+        };
+
+        let mut uri = format!("zcash:{}", percent_encode(&first.address));
+        let mut params = Vec::new();
+        for (index, payment) in self.payments.iter().enumerate() {
+            params.extend(payment_params(payment, index as u32)?);
+        }
+
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(&params.join("&"));
+        }
+        Ok(uri)
+    }
+}
+
+/// Build the query parameters for one payment at `index` (0 for the leading,
+/// unindexed payment).
+fn payment_params(payment: &Payment, index: u32) -> Result<Vec<String>, Zip321Error> {
+    let suffix = if index == 0 { String::new() } else { format!(".{}", index) };
+
+    let mut params = Vec::new();
+    if index != 0 {
+        params.push(format!("address{}={}", suffix, percent_encode(&payment.address)));
+    }
+    if let Some(amount) = payment.amount_zat {
+        params.push(format!("amount{}={}", suffix, format_zec_amount(amount)));
+    }
+    if let Some(memo) = &payment.memo {
+        params.push(format!("memo{}={}", suffix, encode_memo_param(memo)?));
+    }
+    if let Some(label) = &payment.label {
+        params.push(format!("label{}={}", suffix, percent_encode(label)));
+    }
+    if let Some(message) = &payment.message {
+        params.push(format!("message{}={}", suffix, percent_encode(message)));
+    }
+    Ok(params)
+}
+
+/// Split `"amount.1"` into `("amount", 1)`, or `"amount"` into `("amount", 0)`.
+fn split_param_index(key: &str) -> Result<(&str, u32), Zip321Error> {
+    match key.split_once('.') {
+        Some((base, idx_str)) => {
+            let index: u32 = idx_str
+                .parse()
+                .map_err(|_| Zip321Error::InvalidParamIndex(key.to_string()))?;
+            Ok((base, index))
+        }
+        None => Ok((key, 0)),
+    }
+}
+
+/// Format a zatoshi amount as decimal ZEC with trailing zeros trimmed.
+fn format_zec_amount(zatoshi: u64) -> String {
+    let zec = zatoshi as f64 / ZATOSHI_PER_ZEC;
+    let formatted = format!("{:.8}", zec);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    if trimmed.is_empty() { "0".to_string() } else { trimmed.to_string() }
+}
+
+/// Parse a decimal ZEC amount string into zatoshis.
+fn parse_zec_amount(s: &str) -> Result<u64, Zip321Error> {
+    let value: f64 = s.parse().map_err(|_| Zip321Error::InvalidAmount(s.to_string()))?;
+    if !value.is_finite() || value < 0.0 {
+        return Err(Zip321Error::InvalidAmount(s.to_string()));
+    }
+    Ok((value * ZATOSHI_PER_ZEC).round() as u64)
+}
+
+/// Percent-decode a URI component.
+fn percent_decode(s: &str) -> Result<String, Zip321Error> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .ok_or_else(|| Zip321Error::InvalidPercentEncoding(s.to_string()))?;
+                let hex = std::str::from_utf8(hex)
+                    .map_err(|_| Zip321Error::InvalidPercentEncoding(s.to_string()))?;
+                let byte = u8::from_str_radix(hex, 16)
+                    .map_err(|_| Zip321Error::InvalidPercentEncoding(s.to_string()))?;
+                out.push(byte);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).map_err(|_| Zip321Error::InvalidPercentEncoding(s.to_string()))
+}
+
+/// Percent-encode a string for use in a URI query component.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char);
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+const BASE64URL_ALPHABET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode bytes as unpadded base64url, per ZIP 321's memo encoding.
+fn base64url_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied();
+        let b2 = chunk.get(2).copied();
+
+        out.push(BASE64URL_ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(
+            BASE64URL_ALPHABET[(((b0 & 0x03) << 4) | (b1.unwrap_or(0) >> 4)) as usize] as char,
+        );
+        if let Some(b1) = b1 {
+            out.push(
+                BASE64URL_ALPHABET[(((b1 & 0x0F) << 2) | (b2.unwrap_or(0) >> 6)) as usize] as char,
+            );
+        }
+        if let Some(b2) = b2 {
+            out.push(BASE64URL_ALPHABET[(b2 & 0x3F) as usize] as char);
+        }
+    }
+    out
+}
+
+/// Decode unpadded base64url bytes.
+fn base64url_decode(s: &str) -> Result<Vec<u8>, Zip321Error> {
+    fn value(c: u8) -> Result<u8, Zip321Error> {
+        match c {
+            b'A'..=b'Z' => Ok(c - b'A'),
+            b'a'..=b'z' => Ok(c - b'a' + 26),
+            b'0'..=b'9' => Ok(c - b'0' + 52),
+            b'-' => Ok(62),
+            b'_' => Ok(63),
+            _ => Err(Zip321Error::InvalidMemo(format!(
+                "invalid base64url character: {}",
+                c as char
+            ))),
+        }
+    }
+
+    let chars: Vec<u8> = s.bytes().collect();
+    let mut out = Vec::with_capacity(chars.len() * 3 / 4);
+
+    for chunk in chars.chunks(4) {
+        let v0 = value(chunk[0])?;
+        let v1 = value(*chunk
+            .get(1)
+            .ok_or_else(|| Zip321Error::InvalidMemo("truncated base64url memo".to_string()))?)?;
+        out.push((v0 << 2) | (v1 >> 4));
+
+        if let Some(&c2) = chunk.get(2) {
+            let v2 = value(c2)?;
+            out.push(((v1 & 0x0F) << 4) | (v2 >> 2));
+
+            if let Some(&c3) = chunk.get(3) {
+                let v3 = value(c3)?;
+                out.push(((v2 & 0x03) << 6) | v3);
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+/// Decode a `memo`/`memo.N` query value into the 512-byte ZIP-302 field it
+/// represents, interpreted the same way `scanner::scan_transaction` decodes
+/// a note's on-chain memo.
+fn decode_memo_param(value: &str) -> Result<MemoContents, Zip321Error> {
+    let bytes = base64url_decode(value)?;
+    if bytes.len() > MEMO_LEN {
+        return Err(Zip321Error::InvalidMemo(format!(
+            "memo decodes to {} bytes, more than the 512-byte field allows",
+            bytes.len()
+        )));
+    }
+    let mut padded = [0u8; MEMO_LEN];
+    padded[..bytes.len()].copy_from_slice(&bytes);
+    decode_memo(&padded).ok_or_else(|| Zip321Error::InvalidMemo(value.to_string()))
+}
+
+/// Encode a [`MemoContents`] back into its base64url `memo`/`memo.N` value.
+fn encode_memo_param(memo: &MemoContents) -> Result<String, Zip321Error> {
+    let mut bytes = [0u8; MEMO_LEN];
+    match memo {
+        MemoContents::Empty => bytes[0] = 0xF6,
+        MemoContents::Text(text) => {
+            let text_bytes = text.as_bytes();
+            if text_bytes.len() > MEMO_LEN {
+                return Err(Zip321Error::InvalidMemo(
+                    "text memo is longer than the 512-byte field allows".to_string(),
+                ));
+            }
+            bytes[..text_bytes.len()].copy_from_slice(text_bytes);
+        }
+        MemoContents::Arbitrary(raw) => {
+            if raw.len() > MEMO_LEN {
+                return Err(Zip321Error::InvalidMemo(
+                    "memo is longer than the 512-byte field allows".to_string(),
+                ));
+            }
+            bytes[..raw.len()].copy_from_slice(raw);
+        }
+    }
+    Ok(base64url_encode(&bytes))
+}
+
+/// Check whether `address` decodes as a unified, Sapling, or transparent
+/// address on `network`.
+fn address_matches_network(address: &str, network: NetworkKind) -> bool {
+    let network = network.to_network();
+    UnifiedAddress::decode(&network, address).is_ok()
+        || sapling_crypto::PaymentAddress::decode(&network, address).is_ok()
+        || TransparentAddress::decode(&network, address).is_ok()
+}
@@ -32,8 +32,14 @@
 
 extern crate alloc;
 
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use core::fmt;
+
+mod markdown;
+
+pub use markdown::from_markdown;
 
 /// An HTML element with tag, attributes, and children.
 #[derive(Debug, Clone)]
@@ -44,12 +50,16 @@ pub struct Element {
     self_closing: bool,
 }
 
-/// A node in the HTML tree - either an element or text.
+/// A node in the HTML tree - either an element, text, raw HTML, or an
+/// unfilled template placeholder (see `Element::slot`/`freeze`/`fill`).
 #[derive(Debug, Clone)]
 pub enum Node {
     Element(Element),
     Text(String),
     Raw(String),
+    /// A named placeholder left by `Element::freeze`, replaced with a
+    /// `Text` node by a later `fill` call.
+    Slot(String),
 }
 
 /// HTML builder for constructing HTML documents.
@@ -127,6 +137,14 @@ impl Element {
         self
     }
 
+    /// Add a named placeholder. Used to mark the dynamic parts of a
+    /// template before calling `freeze`, so a later `fill(name, value)`
+    /// knows where to substitute.
+    pub fn slot(mut self, name: impl Into<String>) -> Self {
+        self.children.push(Node::Slot(name.into()));
+        self
+    }
+
     /// Add a child element using a builder function.
     pub fn child<F>(mut self, tag: impl Into<String>, f: F) -> Self
     where
@@ -188,36 +206,285 @@ impl Element {
 
     /// Render this element to an existing string buffer.
     pub fn render_to(&self, output: &mut String) {
-        output.push('<');
-        output.push_str(&self.tag);
+        // Writing into a `String` is infallible, so the only way
+        // `render_to_fmt` can fail is if it's given a sink that rejects
+        // writes, which `String` never does.
+        self.render_to_fmt(output)
+            .expect("writing to a String cannot fail");
+    }
+
+    /// Render this element into any `core::fmt::Write` sink, escaping text
+    /// and attribute values as it goes rather than building intermediate
+    /// `String`s. This is what backs both `render`/`render_to` and lets
+    /// callers stream output straight to a file or socket writer that
+    /// implements `core::fmt::Write`.
+    pub fn render_to_fmt<W: fmt::Write>(&self, output: &mut W) -> fmt::Result {
+        output.write_char('<')?;
+        output.write_str(&self.tag)?;
 
         for (name, value) in &self.attrs {
-            output.push(' ');
-            output.push_str(name);
+            output.write_char(' ')?;
+            output.write_str(name)?;
             if !value.is_empty() {
-                output.push_str("=\"");
-                output.push_str(&escape_attr(value));
-                output.push('"');
+                output.write_str("=\"")?;
+                write_escaped_attr(output, value)?;
+                output.write_char('"')?;
             }
         }
 
         if self.self_closing && self.children.is_empty() {
-            output.push_str(" />");
+            output.write_str(" />")?;
         } else {
-            output.push('>');
+            output.write_char('>')?;
 
             for child in &self.children {
-                child.render_to(output);
+                child.render_to_fmt(output)?;
             }
 
-            output.push_str("</");
-            output.push_str(&self.tag);
-            output.push('>');
+            output.write_str("</")?;
+            output.write_str(&self.tag)?;
+            output.write_char('>')?;
+        }
+        Ok(())
+    }
+
+    /// Sanitize this element's own attributes and, recursively, its
+    /// children against `policy`. This does not check whether `self.tag`
+    /// itself is allowed - that decision (keep, lift children, or drop)
+    /// happens in `Node::sanitize`, which is where the parent/child
+    /// relationship needed to lift children lives.
+    pub fn sanitize(&self, policy: &SanitizePolicy) -> Element {
+        let attrs = self
+            .attrs
+            .iter()
+            .filter(|(name, value)| policy.allows_attr(&self.tag, name, value))
+            .cloned()
+            .collect();
+        let children = self.sanitize_children(policy);
+        Element {
+            tag: self.tag.clone(),
+            attrs,
+            children,
+            self_closing: self.self_closing,
+        }
+    }
+
+    fn sanitize_children(&self, policy: &SanitizePolicy) -> Vec<Node> {
+        self.children
+            .iter()
+            .flat_map(|child| child.sanitize(policy))
+            .collect()
+    }
+
+    /// Does this element match a CSS-like selector: a bare tag name (`p`),
+    /// `#id`, or `.class`?
+    pub fn matches(&self, selector: &str) -> bool {
+        self.matches_selector(&Selector::parse(selector))
+    }
+
+    fn matches_selector(&self, selector: &Selector) -> bool {
+        match selector {
+            Selector::Tag(tag) => self.tag == *tag,
+            Selector::Id(id) => self.attrs.iter().any(|(k, v)| k == "id" && v == id),
+            Selector::Class(class) => self
+                .attrs
+                .iter()
+                .any(|(k, v)| k == "class" && v.split_whitespace().any(|c| c == *class)),
+        }
+    }
+
+    /// Find the first descendant element matching `selector` (depth-first,
+    /// pre-order).
+    pub fn find(&self, selector: &str) -> Option<&Element> {
+        self.find_selector(&Selector::parse(selector))
+    }
+
+    fn find_selector(&self, selector: &Selector) -> Option<&Element> {
+        for child in &self.children {
+            if let Node::Element(elem) = child {
+                if elem.matches_selector(selector) {
+                    return Some(elem);
+                }
+                if let Some(found) = elem.find_selector(selector) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    /// Find every descendant element matching `selector` (depth-first,
+    /// pre-order).
+    pub fn find_all(&self, selector: &str) -> Vec<&Element> {
+        let selector = Selector::parse(selector);
+        let mut out = Vec::new();
+        self.find_all_selector(&selector, &mut out);
+        out
+    }
+
+    fn find_all_selector<'a>(&'a self, selector: &Selector, out: &mut Vec<&'a Element>) {
+        for child in &self.children {
+            if let Node::Element(elem) = child {
+                if elem.matches_selector(selector) {
+                    out.push(elem);
+                }
+                elem.find_all_selector(selector, out);
+            }
+        }
+    }
+
+    /// Find the first descendant element matching `selector`, for in-place
+    /// editing.
+    pub fn find_mut(&mut self, selector: &str) -> Option<&mut Element> {
+        self.find_mut_selector(&Selector::parse(selector))
+    }
+
+    fn find_mut_selector(&mut self, selector: &Selector) -> Option<&mut Element> {
+        for child in &mut self.children {
+            if let Node::Element(elem) = child {
+                if elem.matches_selector(selector) {
+                    return Some(elem);
+                }
+                if let Some(found) = elem.find_mut_selector(selector) {
+                    return Some(found);
+                }
+            }
+        }
+        None
+    }
+
+    /// Remove the first descendant element matching `selector`. Returns
+    /// `true` if an element was removed.
+    pub fn remove(&mut self, selector: &str) -> bool {
+        self.remove_selector(&Selector::parse(selector))
+    }
+
+    fn remove_selector(&mut self, selector: &Selector) -> bool {
+        if let Some(pos) = self.children.iter().position(
+            |child| matches!(child, Node::Element(elem) if elem.matches_selector(selector)),
+        ) {
+            self.children.remove(pos);
+            return true;
+        }
+        for child in &mut self.children {
+            if let Node::Element(elem) = child {
+                if elem.remove_selector(selector) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Append an already-built element as the last child, without
+    /// rebuilding this element.
+    pub fn append_child(&mut self, child: Element) {
+        self.children.push(Node::Element(child));
+    }
+
+    /// Set (or replace) an attribute in place.
+    pub fn set_attr(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let name = name.into();
+        let value = value.into();
+        if let Some(pos) = self.attrs.iter().position(|(k, _)| *k == name) {
+            self.attrs[pos].1 = value;
+        } else {
+            self.attrs.push((name, value));
+        }
+    }
+
+    /// Add a class in place. If a `class` attribute already exists, appends
+    /// to it.
+    pub fn add_class(&mut self, class: impl Into<String>) {
+        let class = class.into();
+        if let Some(pos) = self.attrs.iter().position(|(k, _)| k == "class") {
+            self.attrs[pos].1.push(' ');
+            self.attrs[pos].1.push_str(&class);
+        } else {
+            self.attrs.push(("class".to_string(), class));
+        }
+    }
+
+    /// Does this subtree contain a `slot` anywhere, directly or nested?
+    fn has_slot(&self) -> bool {
+        self.children.iter().any(Node::has_slot)
+    }
+
+    /// Compile this subtree into a cheaper-to-rerender form: a fully static
+    /// subtree (no `slot` anywhere inside it) is rendered once into a
+    /// single `Node::Raw`; a subtree containing slots keeps its structure,
+    /// but every run of static children between slots is collapsed into one
+    /// precomputed `Raw` chunk. Call `fill` on the result to substitute
+    /// slot values without re-walking or re-escaping the static parts.
+    pub fn freeze(&self) -> Node {
+        if !self.has_slot() {
+            return Node::Raw(self.render());
+        }
+
+        let mut frozen = Element {
+            tag: self.tag.clone(),
+            attrs: self.attrs.clone(),
+            children: Vec::new(),
+            self_closing: self.self_closing,
+        };
+        let mut static_run = String::new();
+        for child in &self.children {
+            match child {
+                Node::Slot(name) => {
+                    if !static_run.is_empty() {
+                        frozen
+                            .children
+                            .push(Node::Raw(core::mem::take(&mut static_run)));
+                    }
+                    frozen.children.push(Node::Slot(name.clone()));
+                }
+                Node::Element(elem) if elem.has_slot() => {
+                    if !static_run.is_empty() {
+                        frozen
+                            .children
+                            .push(Node::Raw(core::mem::take(&mut static_run)));
+                    }
+                    frozen.children.push(elem.freeze());
+                }
+                static_child => static_child
+                    .render_to_fmt(&mut static_run)
+                    .expect("writing to a String cannot fail"),
+            }
+        }
+        if !static_run.is_empty() {
+            frozen.children.push(Node::Raw(static_run));
+        }
+        Node::Element(frozen)
+    }
+}
+
+/// A CSS-like selector for `Element::find`/`find_all`/`find_mut`/`remove`:
+/// a bare tag name, `#id`, or `.class`.
+enum Selector<'a> {
+    Tag(&'a str),
+    Id(&'a str),
+    Class(&'a str),
+}
+
+impl<'a> Selector<'a> {
+    fn parse(selector: &'a str) -> Self {
+        if let Some(id) = selector.strip_prefix('#') {
+            Selector::Id(id)
+        } else if let Some(class) = selector.strip_prefix('.') {
+            Selector::Class(class)
+        } else {
+            Selector::Tag(selector)
         }
     }
 }
 
 impl Node {
+    /// Parse a Markdown subset into a tree of nodes. See the `markdown`
+    /// module documentation for which constructs are supported.
+    pub fn from_markdown(markdown: &str) -> Vec<Node> {
+        markdown::from_markdown(markdown)
+    }
+
     /// Render this node to a string.
     pub fn render(&self) -> String {
         let mut output = String::new();
@@ -227,10 +494,82 @@ impl Node {
 
     /// Render this node to an existing string buffer.
     pub fn render_to(&self, output: &mut String) {
+        self.render_to_fmt(output)
+            .expect("writing to a String cannot fail");
+    }
+
+    /// Render this node into any `core::fmt::Write` sink. See
+    /// `Element::render_to_fmt`.
+    pub fn render_to_fmt<W: fmt::Write>(&self, output: &mut W) -> fmt::Result {
+        match self {
+            Node::Element(elem) => elem.render_to_fmt(output),
+            Node::Text(text) => write_escaped_html(output, text),
+            Node::Raw(html) => output.write_str(html),
+            // An unfilled slot renders as nothing; call `fill` before
+            // rendering to give it content.
+            Node::Slot(_) => Ok(()),
+        }
+    }
+
+    /// Sanitize this node against `policy`, returning zero or more
+    /// replacement nodes: an allowed element is kept (with its own
+    /// attributes and children sanitized); a disallowed element is either
+    /// dropped or, when `policy.lift_children` is set, replaced by its own
+    /// sanitized children. `Text` passes through unchanged. `Raw` is
+    /// demoted to escaped `Text` unless `policy.strict` is set, in which
+    /// case it's dropped.
+    pub fn sanitize(&self, policy: &SanitizePolicy) -> Vec<Node> {
+        match self {
+            Node::Text(text) => alloc::vec![Node::Text(text.clone())],
+            Node::Slot(name) => alloc::vec![Node::Slot(name.clone())],
+            Node::Raw(html) => {
+                if policy.strict {
+                    Vec::new()
+                } else {
+                    alloc::vec![Node::Text(html.clone())]
+                }
+            }
+            Node::Element(elem) => {
+                if policy.allowed_tags.contains(elem.tag.as_str()) {
+                    alloc::vec![Node::Element(elem.sanitize(policy))]
+                } else if policy.lift_children {
+                    elem.sanitize_children(policy)
+                } else {
+                    Vec::new()
+                }
+            }
+        }
+    }
+
+    fn has_slot(&self) -> bool {
         match self {
-            Node::Element(elem) => elem.render_to(output),
-            Node::Text(text) => output.push_str(&escape_html(text)),
-            Node::Raw(html) => output.push_str(html),
+            Node::Slot(_) => true,
+            Node::Element(elem) => elem.has_slot(),
+            Node::Text(_) | Node::Raw(_) => false,
+        }
+    }
+
+    /// Substitute the slot named `name` with `value`, leaving every other
+    /// node (including precomputed `Raw` chunks from `freeze`) untouched.
+    /// Chain calls to fill multiple slots in one template.
+    pub fn fill(&self, name: &str, value: impl Into<String>) -> Node {
+        self.fill_str(name, &value.into())
+    }
+
+    fn fill_str(&self, name: &str, value: &str) -> Node {
+        match self {
+            Node::Slot(slot_name) if slot_name == name => Node::Text(value.to_string()),
+            Node::Element(elem) if elem.has_slot() => Node::Element(Element {
+                tag: elem.tag.clone(),
+                attrs: elem.attrs.clone(),
+                children: elem
+                    .children
+                    .iter()
+                    .map(|child| child.fill_str(name, value))
+                    .collect(),
+                self_closing: elem.self_closing,
+            }),
+            other => other.clone(),
         }
     }
 }
@@ -266,41 +605,279 @@ impl Html {
     /// Build the final HTML string.
     pub fn build(&self) -> String {
         let mut output = String::new();
+        self.render_to_fmt(&mut output)
+            .expect("writing to a String cannot fail");
+        output
+    }
+
+    /// Render every root node into any `core::fmt::Write` sink. See
+    /// `Element::render_to_fmt`.
+    pub fn render_to_fmt<W: fmt::Write>(&self, output: &mut W) -> fmt::Result {
         for node in &self.nodes {
-            node.render_to(&mut output);
+            node.render_to_fmt(output)?;
+        }
+        Ok(())
+    }
+
+    /// Sanitize every root node against `policy`. See `SanitizePolicy` for
+    /// what gets kept, stripped, or dropped.
+    pub fn sanitize(&self, policy: &SanitizePolicy) -> Html {
+        Html {
+            nodes: self
+                .nodes
+                .iter()
+                .flat_map(|node| node.sanitize(policy))
+                .collect(),
+        }
+    }
+}
+
+/// A complete HTML document: a `<!DOCTYPE html>` declaration wrapping
+/// `<html>` with a `<head>` and `<body>`.
+///
+/// Unlike `Html`, which only builds a fragment, `Document::build` produces a
+/// standalone page that can be written straight to a `.html` file.
+#[derive(Debug, Clone)]
+pub struct Document {
+    head: Element,
+    body: Element,
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Document {
+    /// Create a new empty document with empty `<head>` and `<body>` elements.
+    pub fn new() -> Self {
+        Document {
+            head: Element::new("head"),
+            body: Element::new("body"),
         }
+    }
+
+    /// Build up the `<head>` element using a builder function.
+    pub fn head<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Element) -> Element,
+    {
+        self.head = f(self.head);
+        self
+    }
+
+    /// Build up the `<body>` element using a builder function.
+    pub fn body<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Element) -> Element,
+    {
+        self.body = f(self.body);
+        self
+    }
+
+    /// Set the document title (`<title>` in `<head>`).
+    pub fn title(self, title: impl Into<String>) -> Self {
+        self.head(|e| e.child("title", |e| e.text(title)))
+    }
+
+    /// Set the document charset via `<meta charset="...">`.
+    pub fn charset(self, charset: impl Into<String>) -> Self {
+        self.head(|e| e.child("meta", |e| e.attr("charset", charset)))
+    }
+
+    /// Link a stylesheet via `<link rel="stylesheet" href="...">`.
+    pub fn stylesheet(self, href: impl Into<String>) -> Self {
+        self.head(|e| {
+            e.child("link", |e| {
+                e.attr("rel", "stylesheet").attr("href", href)
+            })
+        })
+    }
+
+    /// Add a `<meta name="..." content="...">` tag.
+    pub fn meta(self, name: impl Into<String>, content: impl Into<String>) -> Self {
+        self.head(|e| e.child("meta", |e| e.attr("name", name).attr("content", content)))
+    }
+
+    /// Build the full document string, including the doctype.
+    pub fn build(&self) -> String {
+        let mut output = String::new();
+        output.push_str("<!DOCTYPE html>");
+        output.push_str("<html>");
+        self.head.render_to(&mut output);
+        self.body.render_to(&mut output);
+        output.push_str("</html>");
         output
     }
 }
 
+/// An allowlist policy for `sanitize`: which tags are kept, which
+/// attributes each tag may carry, and which URL schemes `href`/`src` may
+/// use. Construct one with `SanitizePolicy::new()` and `allow_tag`/
+/// `allow_scheme`, or start from `SanitizePolicy::relaxed()`.
+#[derive(Debug, Clone)]
+pub struct SanitizePolicy {
+    allowed_tags: BTreeSet<String>,
+    allowed_attrs: BTreeMap<String, BTreeSet<String>>,
+    allowed_schemes: BTreeSet<String>,
+    /// When an element's tag isn't allowed, keep its (sanitized) children in
+    /// its place instead of dropping the whole subtree. Defaults to `true`.
+    pub lift_children: bool,
+    /// When `true`, `Raw` nodes are dropped instead of demoted to escaped
+    /// text. Defaults to `false`.
+    pub strict: bool,
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SanitizePolicy {
+    /// An empty allowlist policy: no tags, attributes, or URL schemes are
+    /// allowed. `lift_children` is on and `strict` is off, as their field
+    /// docs describe.
+    pub fn new() -> Self {
+        SanitizePolicy {
+            allowed_tags: BTreeSet::new(),
+            allowed_attrs: BTreeMap::new(),
+            allowed_schemes: BTreeSet::new(),
+            lift_children: true,
+            strict: false,
+        }
+    }
+
+    /// Allow `tag`, carrying only the given attribute names.
+    pub fn allow_tag(mut self, tag: impl Into<String>, attrs: &[&str]) -> Self {
+        let tag = tag.into();
+        self.allowed_attrs
+            .insert(tag.clone(), attrs.iter().map(|a| a.to_string()).collect());
+        self.allowed_tags.insert(tag);
+        self
+    }
+
+    /// Permit a URL scheme (without the trailing `:`) in `href`/`src` values.
+    pub fn allow_scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.allowed_schemes.insert(scheme.into());
+        self
+    }
+
+    /// Drop disallowed elements entirely, instead of lifting their children
+    /// up to the parent.
+    pub fn drop_children(mut self) -> Self {
+        self.lift_children = false;
+        self
+    }
+
+    /// Drop `Raw` nodes entirely, instead of demoting them to escaped text.
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// A default policy covering common text-formatting and layout tags,
+    /// permitting `http`, `https`, and `mailto` links.
+    pub fn relaxed() -> Self {
+        SanitizePolicy::new()
+            .allow_tag("p", &["class"])
+            .allow_tag("br", &[])
+            .allow_tag("strong", &[])
+            .allow_tag("b", &[])
+            .allow_tag("em", &[])
+            .allow_tag("i", &[])
+            .allow_tag("code", &["class"])
+            .allow_tag("pre", &["class"])
+            .allow_tag("blockquote", &[])
+            .allow_tag("ul", &[])
+            .allow_tag("ol", &[])
+            .allow_tag("li", &[])
+            .allow_tag("h1", &[])
+            .allow_tag("h2", &[])
+            .allow_tag("h3", &[])
+            .allow_tag("h4", &[])
+            .allow_tag("h5", &[])
+            .allow_tag("h6", &[])
+            .allow_tag("span", &["class"])
+            .allow_tag("div", &["class"])
+            .allow_tag("table", &["class"])
+            .allow_tag("thead", &[])
+            .allow_tag("tbody", &[])
+            .allow_tag("tr", &[])
+            .allow_tag("td", &["class"])
+            .allow_tag("th", &["class"])
+            .allow_tag("a", &["href", "title"])
+            .allow_scheme("http")
+            .allow_scheme("https")
+            .allow_scheme("mailto")
+    }
+
+    fn allows_attr(&self, tag: &str, name: &str, value: &str) -> bool {
+        let allowed = self
+            .allowed_attrs
+            .get(tag)
+            .is_some_and(|names| names.contains(name));
+        if !allowed {
+            return false;
+        }
+        if name == "href" || name == "src" {
+            return self.allows_url(value);
+        }
+        true
+    }
+
+    fn allows_url(&self, value: &str) -> bool {
+        match value.split_once(':') {
+            // No scheme, e.g. a relative path or fragment: always allowed.
+            None => true,
+            Some((scheme, _)) => self.allowed_schemes.contains(scheme),
+        }
+    }
+}
+
 /// Escape special HTML characters in text content.
 pub fn escape_html(s: &str) -> String {
     let mut output = String::with_capacity(s.len());
-    for c in s.chars() {
-        match c {
-            '&' => output.push_str("&amp;"),
-            '<' => output.push_str("&lt;"),
-            '>' => output.push_str("&gt;"),
-            _ => output.push(c),
-        }
-    }
+    write_escaped_html(&mut output, s).expect("writing to a String cannot fail");
     output
 }
 
 /// Escape special characters in attribute values.
 pub fn escape_attr(s: &str) -> String {
     let mut output = String::with_capacity(s.len());
+    write_escaped_attr(&mut output, s).expect("writing to a String cannot fail");
+    output
+}
+
+/// Write `s` to `output` with HTML text content escaped, without allocating
+/// an intermediate `String`.
+fn write_escaped_html<W: fmt::Write>(output: &mut W, s: &str) -> fmt::Result {
     for c in s.chars() {
         match c {
-            '&' => output.push_str("&amp;"),
-            '<' => output.push_str("&lt;"),
-            '>' => output.push_str("&gt;"),
-            '"' => output.push_str("&quot;"),
-            '\'' => output.push_str("&#x27;"),
-            _ => output.push(c),
+            '&' => output.write_str("&amp;")?,
+            '<' => output.write_str("&lt;")?,
+            '>' => output.write_str("&gt;")?,
+            _ => output.write_char(c)?,
         }
     }
-    output
+    Ok(())
+}
+
+/// Write `s` to `output` with HTML attribute-value characters escaped,
+/// without allocating an intermediate `String`.
+fn write_escaped_attr<W: fmt::Write>(output: &mut W, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '&' => output.write_str("&amp;")?,
+            '<' => output.write_str("&lt;")?,
+            '>' => output.write_str("&gt;")?,
+            '"' => output.write_str("&quot;")?,
+            '\'' => output.write_str("&#x27;")?,
+            _ => output.write_char(c)?,
+        }
+    }
+    Ok(())
 }
 
 // Convenience functions for common elements
@@ -479,4 +1056,210 @@ mod tests {
         assert!(html.contains("<code>t1abc123</code>"));
         assert!(html.contains("<code>u1xyz789</code>"));
     }
+
+    #[test]
+    fn test_document_minimal() {
+        let html = Document::new().build();
+        assert_eq!(
+            html,
+            "<!DOCTYPE html><html><head></head><body></body></html>"
+        );
+    }
+
+    #[test]
+    fn test_document_title_and_charset() {
+        let html = Document::new()
+            .charset("utf-8")
+            .title("Report")
+            .build();
+
+        assert!(html.starts_with("<!DOCTYPE html><html>"));
+        assert!(html.contains(r#"<meta charset="utf-8" />"#));
+        assert!(html.contains("<title>Report</title>"));
+    }
+
+    #[test]
+    fn test_document_stylesheet_and_meta() {
+        let html = Document::new()
+            .stylesheet("styles.css")
+            .meta("author", "zcash-wallet")
+            .build();
+
+        assert!(html.contains(r#"<link rel="stylesheet" href="styles.css" />"#));
+        assert!(html.contains(r#"<meta name="author" content="zcash-wallet" />"#));
+    }
+
+    #[test]
+    fn test_document_body() {
+        let html = Document::new()
+            .body(|e| e.child("h1", |e| e.text("Balance Report")))
+            .build();
+
+        assert!(html.contains("<body><h1>Balance Report</h1></body>"));
+    }
+
+    #[test]
+    fn test_sanitize_lifts_children_of_disallowed_tag() {
+        let html = Html::new().elem("div", |e| {
+            e.child("script", |e| e.text("alert(1)"))
+                .child("p", |e| e.text("kept"))
+        });
+
+        let sanitized = html.sanitize(&SanitizePolicy::relaxed());
+        assert_eq!(sanitized.build(), "<div>alert(1)<p>kept</p></div>");
+    }
+
+    #[test]
+    fn test_sanitize_drops_disallowed_tag_without_lifting() {
+        let policy = SanitizePolicy::relaxed().drop_children();
+        let html = Html::new().elem("div", |e| {
+            e.child("script", |e| e.text("alert(1)"))
+                .child("p", |e| e.text("kept"))
+        });
+
+        let sanitized = html.sanitize(&policy);
+        assert_eq!(sanitized.build(), "<div><p>kept</p></div>");
+    }
+
+    #[test]
+    fn test_sanitize_strips_disallowed_attributes() {
+        let html = Html::new().elem("p", |e| e.attr("onclick", "evil()").text("hi"));
+        let sanitized = html.sanitize(&SanitizePolicy::relaxed());
+        assert_eq!(sanitized.build(), "<p>hi</p>");
+    }
+
+    #[test]
+    fn test_sanitize_rejects_unsafe_url_schemes() {
+        let policy = SanitizePolicy::relaxed();
+
+        let evil = Html::new().elem("a", |e| e.attr("href", "javascript:alert(1)").text("click"));
+        assert_eq!(evil.sanitize(&policy).build(), "<a>click</a>");
+
+        let safe = Html::new().elem("a", |e| e.attr("href", "https://example.com").text("click"));
+        assert_eq!(
+            safe.sanitize(&policy).build(),
+            r#"<a href="https://example.com">click</a>"#
+        );
+    }
+
+    #[test]
+    fn test_sanitize_demotes_raw_to_escaped_text_by_default() {
+        let html = Html::new().raw("<script>alert(1)</script>");
+        let sanitized = html.sanitize(&SanitizePolicy::relaxed());
+        assert_eq!(sanitized.build(), "&lt;script&gt;alert(1)&lt;/script&gt;");
+    }
+
+    #[test]
+    fn test_sanitize_drops_raw_in_strict_mode() {
+        let html = Html::new().raw("<script>alert(1)</script>");
+        let sanitized = html.sanitize(&SanitizePolicy::relaxed().strict());
+        assert_eq!(sanitized.build(), "");
+    }
+
+    #[test]
+    fn test_render_to_fmt_matches_build() {
+        let html = Html::new().elem("p", |e| e.text("Hello & goodbye"));
+
+        let mut buf = String::new();
+        html.render_to_fmt(&mut buf).unwrap();
+
+        assert_eq!(buf, html.build());
+    }
+
+    #[test]
+    fn test_render_to_fmt_streams_into_arbitrary_write_sink() {
+        struct CountingSink {
+            bytes_written: usize,
+        }
+
+        impl fmt::Write for CountingSink {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.bytes_written += s.len();
+                Ok(())
+            }
+        }
+
+        let html = Html::new().elem("div", |e| e.child("p", |e| e.text("report")));
+        let mut sink = CountingSink { bytes_written: 0 };
+        html.render_to_fmt(&mut sink).unwrap();
+
+        assert_eq!(sink.bytes_written, html.build().len());
+    }
+
+    #[test]
+    fn test_find_by_tag_id_and_class() {
+        let root = Element::new("div").child("table", |e| {
+            e.child("tr", |e| {
+                e.id("row-0")
+                    .class("highlight")
+                    .child("td", |e| e.text("a"))
+                    .child("td", |e| e.text("b"))
+            })
+        });
+
+        assert!(root.find("tr").is_some());
+        assert!(root.find("#row-0").is_some());
+        assert!(root.find(".highlight").is_some());
+        assert!(root.find("th").is_none());
+        assert_eq!(root.find_all("td").len(), 2);
+    }
+
+    #[test]
+    fn test_find_mut_and_set_attr() {
+        let mut root = Element::new("table").child("tr", |e| e.child("td", |e| e.text("0")));
+
+        let row = root.find_mut("tr").unwrap();
+        row.set_attr("data-row", "0");
+        row.add_class("highlight");
+
+        let html = root.render();
+        assert!(html.contains(r#"data-row="0""#));
+        assert!(html.contains(r#"class="highlight""#));
+    }
+
+    #[test]
+    fn test_remove_descendant() {
+        let mut root = Element::new("ul")
+            .child("li", |e| e.class("keep").text("keep"))
+            .child("li", |e| e.class("drop").text("drop"));
+
+        assert!(root.remove(".drop"));
+        assert_eq!(root.render(), r#"<ul><li class="keep">keep</li></ul>"#);
+        assert!(!root.remove(".drop"));
+    }
+
+    #[test]
+    fn test_append_child() {
+        let mut root = Element::new("ul").child("li", |e| e.text("one"));
+        root.append_child(Element::new("li").text("two"));
+
+        assert_eq!(root.render(), "<ul><li>one</li><li>two</li></ul>");
+    }
+
+    #[test]
+    fn test_freeze_fully_static_becomes_single_raw() {
+        let frozen = Element::new("p").text("Hello & goodbye").freeze();
+        assert!(matches!(frozen, Node::Raw(_)));
+        assert_eq!(frozen.render(), "<p>Hello &amp; goodbye</p>");
+    }
+
+    #[test]
+    fn test_freeze_and_fill_template() {
+        let template = Element::new("tr")
+            .child("td", |e| e.slot("index"))
+            .child("td", |e| e.slot("address"))
+            .freeze();
+
+        let row0 = template.fill("index", "0").fill("address", "t1abc");
+        let row1 = template.fill("index", "1").fill("address", "t1xyz");
+
+        assert_eq!(row0.render(), "<tr><td>0</td><td>t1abc</td></tr>");
+        assert_eq!(row1.render(), "<tr><td>1</td><td>t1xyz</td></tr>");
+    }
+
+    #[test]
+    fn test_unfilled_slot_renders_empty() {
+        let template = Element::new("span").slot("value").freeze();
+        assert_eq!(template.render(), "<span></span>");
+    }
 }
@@ -0,0 +1,316 @@
+//! Minimal CommonMark-subset to `Node` tree conversion.
+//!
+//! No markdown-parsing crate is available in this build, so this is a
+//! small hand-rolled, line-based scanner rather than a pull parser: it
+//! groups input lines into blocks (fenced code block, ATX heading, block
+//! quote, bullet or numbered list, paragraph) and builds each block's
+//! element directly, then parses the block's text for inline spans
+//! (emphasis/strong, inline code, links) recursively. It keeps no stack of
+//! open elements, so block constructs do not nest inside one another (no
+//! list inside a block quote, no nested lists). It covers those constructs
+//! well enough to turn notes and descriptions into report markup; it is not
+//! a full CommonMark implementation (no nested blockquotes, tables, or
+//! reference links).
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{Element, Node};
+
+/// Parse a Markdown subset into a `Node` tree.
+pub fn from_markdown(input: &str) -> Vec<Node> {
+    let mut blocks: Vec<Node> = Vec::new();
+    let mut lines = input.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        // Fenced code block.
+        if let Some(info) = trimmed.strip_prefix("```") {
+            let lang = info.trim();
+            let mut code = String::new();
+            for code_line in lines.by_ref() {
+                if code_line.trim_start().starts_with("```") {
+                    break;
+                }
+                if !code.is_empty() {
+                    code.push('\n');
+                }
+                code.push_str(code_line);
+            }
+            let mut code_elem = Element::new("code").text(code);
+            if !lang.is_empty() {
+                code_elem = code_elem.class(format!("language-{}", lang));
+            }
+            blocks.push(Node::Element(
+                Element::new("pre").node(Node::Element(code_elem)),
+            ));
+            continue;
+        }
+
+        // ATX heading: 1-6 '#' followed by a space.
+        if let Some(level) = heading_level(trimmed) {
+            let content = trimmed[level + 1..].trim();
+            let elem = push_inline(Element::new(format!("h{}", level)), content);
+            blocks.push(Node::Element(elem));
+            continue;
+        }
+
+        // Block quote: consecutive "> "-prefixed lines become one paragraph
+        // inside a <blockquote>.
+        if let Some(rest) = trimmed.strip_prefix('>') {
+            let mut text = rest.trim_start().to_string();
+            while let Some(next) = lines.peek() {
+                let next_trimmed = next.trim();
+                if let Some(rest) = next_trimmed.strip_prefix('>') {
+                    text.push(' ');
+                    text.push_str(rest.trim_start());
+                    lines.next();
+                } else {
+                    break;
+                }
+            }
+            let paragraph = push_inline(Element::new("p"), &text);
+            blocks.push(Node::Element(
+                Element::new("blockquote").node(Node::Element(paragraph)),
+            ));
+            continue;
+        }
+
+        // List: consecutive bullet or numbered lines become one <ul>/<ol>.
+        if let Some((ordered, item_text)) = list_item(trimmed) {
+            let mut items = Vec::new();
+            items.push(item_text.to_string());
+            while let Some(next) = lines.peek() {
+                match list_item(next.trim()) {
+                    Some((next_ordered, next_item)) if next_ordered == ordered => {
+                        items.push(next_item.to_string());
+                        lines.next();
+                    }
+                    _ => break,
+                }
+            }
+            let list_tag = if ordered { "ol" } else { "ul" };
+            let mut list_elem = Element::new(list_tag);
+            for item in items {
+                let li = push_inline(Element::new("li"), &item);
+                list_elem = list_elem.node(Node::Element(li));
+            }
+            blocks.push(Node::Element(list_elem));
+            continue;
+        }
+
+        // Paragraph: consume consecutive plain lines as one block.
+        let mut text = trimmed.to_string();
+        while let Some(next) = lines.peek() {
+            let next_trimmed = next.trim();
+            if next_trimmed.is_empty()
+                || heading_level(next_trimmed).is_some()
+                || next_trimmed.starts_with('>')
+                || next_trimmed.starts_with("```")
+                || list_item(next_trimmed).is_some()
+            {
+                break;
+            }
+            text.push(' ');
+            text.push_str(next_trimmed);
+            lines.next();
+        }
+        let paragraph = push_inline(Element::new("p"), &text);
+        blocks.push(Node::Element(paragraph));
+    }
+
+    blocks
+}
+
+/// Returns the heading level (1-6) if `line` is an ATX heading.
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    if (1..=6).contains(&level) && line.as_bytes().get(level) == Some(&b' ') {
+        Some(level)
+    } else {
+        None
+    }
+}
+
+/// Returns `(is_ordered, item_text)` if `line` is a list item.
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    if let Some(rest) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+        return Some((false, rest));
+    }
+    let digits = line.bytes().take_while(|b| b.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(". ") {
+            return Some((true, rest));
+        }
+    }
+    None
+}
+
+/// Parse inline spans (emphasis, strong, inline code, links, plain text)
+/// from `text` and append them as children of `elem`.
+fn push_inline(mut elem: Element, text: &str) -> Element {
+    let chars: Vec<char> = text.chars().collect();
+    let mut i = 0;
+    let mut plain = String::new();
+
+    macro_rules! flush_plain {
+        () => {
+            if !plain.is_empty() {
+                elem = elem.text(core::mem::take(&mut plain));
+            }
+        };
+    }
+
+    while i < chars.len() {
+        if chars[i] == '`' {
+            if let Some(end) = find_char(&chars, i + 1, '`') {
+                flush_plain!();
+                let code: String = chars[i + 1..end].iter().collect();
+                elem = elem.child("code", |e| e.text(code));
+                i = end + 1;
+                continue;
+            }
+        }
+
+        if (chars[i] == '*' || chars[i] == '_') && chars.get(i + 1) == Some(&chars[i]) {
+            let marker = chars[i];
+            if let Some(end) = find_pair(&chars, i + 2, marker) {
+                flush_plain!();
+                let inner: String = chars[i + 2..end].iter().collect();
+                elem = elem.child("strong", |e| push_inline(e, &inner));
+                i = end + 2;
+                continue;
+            }
+        }
+
+        if chars[i] == '*' || chars[i] == '_' {
+            let marker = chars[i];
+            if let Some(end) = find_char(&chars, i + 1, marker) {
+                flush_plain!();
+                let inner: String = chars[i + 1..end].iter().collect();
+                elem = elem.child("em", |e| push_inline(e, &inner));
+                i = end + 1;
+                continue;
+            }
+        }
+
+        if chars[i] == '[' {
+            if let Some(close_bracket) = find_char(&chars, i + 1, ']') {
+                if chars.get(close_bracket + 1) == Some(&'(') {
+                    if let Some(close_paren) = find_char(&chars, close_bracket + 2, ')') {
+                        flush_plain!();
+                        let link_text: String = chars[i + 1..close_bracket].iter().collect();
+                        let href: String = chars[close_bracket + 2..close_paren].iter().collect();
+                        elem = elem.child("a", |e| e.attr("href", href).text(link_text));
+                        i = close_paren + 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        plain.push(chars[i]);
+        i += 1;
+    }
+
+    flush_plain!();
+    elem
+}
+
+fn find_char(chars: &[char], from: usize, target: char) -> Option<usize> {
+    chars[from..].iter().position(|&c| c == target).map(|p| p + from)
+}
+
+/// Find the index of a closing two-character marker (e.g. `**`) starting at
+/// or after `from`.
+fn find_pair(chars: &[char], from: usize, marker: char) -> Option<usize> {
+    let mut i = from;
+    while i + 1 < chars.len() {
+        if chars[i] == marker && chars[i + 1] == marker {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(nodes: Vec<Node>) -> String {
+        nodes.iter().map(Node::render).collect()
+    }
+
+    #[test]
+    fn test_paragraph() {
+        let nodes = from_markdown("Hello, world.");
+        assert_eq!(render(nodes), "<p>Hello, world.</p>");
+    }
+
+    #[test]
+    fn test_headings() {
+        let nodes = from_markdown("# Title\n\n### Subtitle");
+        assert_eq!(render(nodes), "<h1>Title</h1><h3>Subtitle</h3>");
+    }
+
+    #[test]
+    fn test_emphasis_and_strong() {
+        let nodes = from_markdown("This is *italic* and **bold**.");
+        assert_eq!(
+            render(nodes),
+            "<p>This is <em>italic</em> and <strong>bold</strong>.</p>"
+        );
+    }
+
+    #[test]
+    fn test_inline_code_and_link() {
+        let nodes = from_markdown("See `cargo build` or [the docs](https://example.com).");
+        assert_eq!(
+            render(nodes),
+            r#"<p>See <code>cargo build</code> or <a href="https://example.com">the docs</a>.</p>"#
+        );
+    }
+
+    #[test]
+    fn test_unordered_list() {
+        let nodes = from_markdown("- one\n- two\n- three");
+        assert_eq!(render(nodes), "<ul><li>one</li><li>two</li><li>three</li></ul>");
+    }
+
+    #[test]
+    fn test_ordered_list() {
+        let nodes = from_markdown("1. first\n2. second");
+        assert_eq!(render(nodes), "<ol><li>first</li><li>second</li></ol>");
+    }
+
+    #[test]
+    fn test_blockquote() {
+        let nodes = from_markdown("> quoted text\n> continues here");
+        assert_eq!(render(nodes), "<blockquote><p>quoted text continues here</p></blockquote>");
+    }
+
+    #[test]
+    fn test_code_block_with_language() {
+        let nodes = from_markdown("```rust\nfn main() {}\n```");
+        assert_eq!(
+            render(nodes),
+            r#"<pre><code class="language-rust">fn main() {}</code></pre>"#
+        );
+    }
+
+    #[test]
+    fn test_html_in_markdown_is_escaped() {
+        let nodes = from_markdown("<script>alert(1)</script>");
+        assert_eq!(
+            render(nodes),
+            "<p>&lt;script&gt;alert(1)&lt;/script&gt;</p>"
+        );
+    }
+}
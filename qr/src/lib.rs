@@ -22,9 +22,13 @@
 //! ```text
 //! lib.rs
 //! +-- Public API
-//! |   +-- QrCode::encode()     Entry point: data -> QR code
+//! |   +-- QrCode::encode()           Entry point: data -> QR code
+//! |   +-- QrCode::encode_advanced()  Entry point with version/mask/ECL control
+//! |   +-- QrCode::encode_structured_append()  Split data across up to 16 symbols
+//! |   +-- QrCode::encode_kanji()      Entry point for Shift-JIS Kanji-mode data
 //! |   +-- QrCode::to_svg()     Render as SVG
 //! |   +-- QrCode::to_ascii()   Render as terminal ASCII art
+//! |   +-- QrCode::render()     Builder for custom colors/quiet zone/module size
 //! |
 //! +-- Data Encoding (Section 7)
 //! |   +-- find_min_version()   Select smallest QR version for data
@@ -35,7 +39,7 @@
 //! |   +-- add_error_correction()      Compute and interleave EC codewords
 //! |   +-- reed_solomon_generator()    Build generator polynomial
 //! |   +-- reed_solomon_encode()       Polynomial division in GF(2^8)
-//! |   +-- GF256                       Galois Field arithmetic (log/exp tables)
+//! |   +-- GF<POLY>                    Galois Field arithmetic (log/exp tables), generic over the reducing polynomial
 //! |
 //! +-- Matrix Construction (Section 6, 9)
 //! |   +-- place_function_patterns()   Finder, timing, alignment patterns
@@ -46,8 +50,26 @@
 //! |   +-- calculate_penalty()         Score pattern quality
 //! |
 //! +-- Format Info (Annex C)
-//!     +-- place_format_info()         BCH(15,5) encoded EC level + mask
-//!     +-- place_version_info()        BCH(18,6) encoded version (v7+)
+//! |   +-- place_format_info()         BCH(15,5) encoded EC level + mask
+//! |   +-- place_version_info()        BCH(18,6) encoded version (v7+)
+//! |
+//! +-- Decoding
+//! |   +-- QrCode::decode()             Entry point: matrix -> original string
+//! |   +-- reed_solomon_correct()       Syndrome decoding (Berlekamp-Massey, Chien, Forney)
+//! |
+//! +-- GaloisField / Gf256 (public GF(2^8) arithmetic)
+//! |   +-- Gf256::new(), Gf256::value()  Wrap/unwrap a field element
+//! |   +-- Gf256::nonzero_elements()     Iterate all 255 nonzero elements
+//! |   +-- Gf256::mul_with_strategy()    Good/Better/Best multiply strategies
+//! |   +-- Gf256::mul_slice()            Bulk-multiply a buffer by a constant
+//! |
+//! +-- shamir (Secret Sharing)
+//! |   +-- shamir::split()              Split a secret into n QR-codeable shares
+//! |   +-- shamir::reconstruct()        Recover the secret from k shares
+//! |
+//! +-- data_matrix (Data Matrix ECC200)
+//!     +-- DataMatrix::encode()         Entry point: data -> Data Matrix symbol
+//!     +-- DataMatrix::to_svg()         Render as SVG
 //! ```
 //!
 //! ## QR Code Structure
@@ -223,6 +245,48 @@ pub enum Mode {
     Alphanumeric = 0b0010,
     /// Byte: any 8-bit data. 8 bits/char
     Byte = 0b0100,
+    /// Kanji: Shift-JIS double-byte characters. 13 bits/char - see
+    /// `append_kanji_segment`. Only used via `QrCode::encode_kanji`, not
+    /// the general `segment_data` DP (which operates on arbitrary bytes
+    /// and can't tell Shift-JIS pairs from unrelated binary data).
+    Kanji = 0b1000,
+}
+
+/// The QR alphanumeric character set, in encoding order (value 0-44).
+const ALPHANUMERIC_CHARS: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// The alphanumeric-mode value (0-44) of `byte`, or `None` if it isn't in
+/// the QR alphanumeric character set.
+fn alphanumeric_value(byte: u8) -> Option<u32> {
+    ALPHANUMERIC_CHARS
+        .iter()
+        .position(|&c| c == byte)
+        .map(|pos| pos as u32)
+}
+
+/// A contiguous run of `data`'s bytes to be encoded in a single `Mode`, as
+/// produced by `QrCode::segment_data`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    mode: Mode,
+    start: usize,
+    end: usize,
+}
+
+/// Which symbol family a `QrCode` represents.
+///
+/// A standard QR Code (Model 2) has three finder patterns, versions 1-40,
+/// and 8 mask patterns to choose from. A
+/// Micro QR Code trades that range for a much smaller footprint: a single
+/// finder pattern, versions M1-M4, and only 4 mask patterns. See
+/// `QrCode::encode_micro` for the entry point and its doc comment for the
+/// simplifications this implementation makes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// Standard QR Code. `version` is 1-40 and size = 4*version + 17.
+    Full,
+    /// Micro QR Code. `version` is 1 (M1) - 4 (M4) and size = 2*version + 9.
+    Micro,
 }
 
 /// A QR code represented as a 2D matrix of modules.
@@ -233,12 +297,17 @@ pub struct QrCode {
     /// Tracks which modules are "function patterns" (finder, timing, etc.)
     /// These cannot be masked.
     is_function: Vec<Vec<bool>>,
-    /// QR version (1-40). Determines size: (version * 4 + 17) modules per side.
+    /// Symbol version. For `SymbolKind::Full` this is 1-40 (size =
+    /// version*4 + 17); for `SymbolKind::Micro` this is 1-4, i.e. M1-M4
+    /// (size = version*2 + 9).
     version: u8,
     /// Error correction level used.
     error_correction: ErrorCorrectionLevel,
-    /// Mask pattern applied (0-7).
+    /// Mask pattern applied. For `SymbolKind::Full` this is 0-7; for
+    /// `SymbolKind::Micro` this is 0-3 (see `MICRO_MASK_PATTERNS`).
     mask: u8,
+    /// Which symbol family this code was built as.
+    kind: SymbolKind,
 }
 
 impl QrCode {
@@ -247,36 +316,74 @@ impl QrCode {
     /// Uses byte mode encoding which supports any UTF-8 string.
     /// Automatically selects the smallest version that fits the data.
     pub fn encode(data: &str, ecl: ErrorCorrectionLevel) -> Result<Self, String> {
+        Self::encode_advanced(data, ecl, 1, 40, None, false)
+    }
+
+    /// Generate a QR code with explicit control over version range, mask
+    /// pattern, and EC-level boosting - the knobs Nayuki's reference
+    /// encoder exposes beyond the convenience `encode()` entry point.
+    ///
+    /// - `min_version`/`max_version` (1-40) bound the version search; an
+    ///   error is returned if the data doesn't fit within `max_version`.
+    /// - `mask`, if `Some(m)`, forces pattern `m` (0-7) instead of running
+    ///   the eight-mask penalty search.
+    /// - `boost_ecl`, if true, upgrades `min_ecl` to the highest level
+    ///   (L -> M -> Q -> H) whose data capacity still fits the encoded
+    ///   bitstream at the chosen version, for free extra damage
+    ///   resistance at no size cost.
+    pub fn encode_advanced(
+        data: &str,
+        min_ecl: ErrorCorrectionLevel,
+        min_version: u8,
+        max_version: u8,
+        mask: Option<u8>,
+        boost_ecl: bool,
+    ) -> Result<Self, String> {
         let bytes = data.as_bytes();
 
-        // Step 1: Determine the minimum version needed
-        let version = Self::find_min_version(bytes.len(), ecl)?;
+        // Step 1: Determine the minimum version needed, using the bit
+        // length of the optimally-segmented data rather than raw byte count
+        let version = Self::find_min_version(bytes, min_ecl, min_version, max_version)?;
         let size = version as usize * 4 + 17;
 
-        // Step 2: Encode data into codewords
+        // Step 2: Pick the final EC level, boosting it for free if asked
+        let ecl = if boost_ecl {
+            Self::boost_ec_level(bytes, version, min_ecl)
+        } else {
+            min_ecl
+        };
+
+        // Step 3: Encode data into codewords
         let data_codewords = Self::encode_data(bytes, version, ecl)?;
 
-        // Step 3: Add error correction
+        // Step 4: Add error correction
         let all_codewords = Self::add_error_correction(&data_codewords, version, ecl);
 
-        // Step 4: Create the matrix and place function patterns
+        // Step 5: Create the matrix and place function patterns
         let mut qr = Self {
             modules: vec![vec![false; size]; size],
             is_function: vec![vec![false; size]; size],
             version,
             error_correction: ecl,
             mask: 0,
+            kind: SymbolKind::Full,
         };
 
         qr.place_function_patterns();
 
-        // Step 5: Place data bits
+        // Step 6: Place data bits
         qr.place_data_bits(&all_codewords);
 
-        // Step 6: Apply best mask
-        qr.apply_best_mask();
+        // Step 7: Apply the requested mask, or search for the best one
+        match mask {
+            Some(m) => {
+                qr.mask = m;
+                qr.apply_mask(m);
+            }
+            None => qr.apply_best_mask(),
+        }
 
-        // Step 7: Add format information
+        // Step 8: Add format information
         qr.place_format_info();
 
         if version >= 7 {
@@ -286,94 +393,486 @@ impl QrCode {
         Ok(qr)
     }
 
-    /// Find minimum QR version that can hold the data.
+    /// Generate a QR code whose byte-mode payload is tagged with an
+    /// Extended Channel Interpretation (ECI) designator, so conformant
+    /// readers decode it with the declared character set instead of
+    /// guessing (typically Latin-1 when no ECI is present). `eci` is the
+    /// AIM ECI assignment number, e.g. 26 for UTF-8 or 20 for Shift-JIS.
     ///
-    /// Each version has a specific data capacity depending on error correction.
-    fn find_min_version(data_len: usize, ecl: ErrorCorrectionLevel) -> Result<u8, String> {
-        // Data capacity table for byte mode (version 1-40)
-        // Format: [L, M, Q, H] capacities for each version
-        let capacities: [(usize, usize, usize, usize); 40] = [
-            (17, 14, 11, 7),          // Version 1
-            (32, 26, 20, 14),         // Version 2
-            (53, 42, 32, 24),         // Version 3
-            (78, 62, 46, 34),         // Version 4
-            (106, 84, 60, 44),        // Version 5
-            (134, 106, 74, 58),       // Version 6
-            (154, 122, 86, 64),       // Version 7
-            (192, 152, 108, 84),      // Version 8
-            (230, 180, 130, 98),      // Version 9
-            (271, 213, 151, 119),     // Version 10
-            (321, 251, 177, 137),     // Version 11
-            (367, 287, 203, 155),     // Version 12
-            (425, 331, 241, 177),     // Version 13
-            (458, 362, 258, 194),     // Version 14
-            (520, 412, 292, 220),     // Version 15
-            (586, 450, 322, 250),     // Version 16
-            (644, 504, 364, 280),     // Version 17
-            (718, 560, 394, 310),     // Version 18
-            (792, 624, 442, 338),     // Version 19
-            (858, 666, 482, 382),     // Version 20
-            (929, 711, 509, 403),     // Version 21
-            (1003, 779, 565, 439),    // Version 22
-            (1091, 857, 611, 461),    // Version 23
-            (1171, 911, 661, 511),    // Version 24
-            (1273, 997, 715, 535),    // Version 25
-            (1367, 1059, 751, 593),   // Version 26
-            (1465, 1125, 805, 625),   // Version 27
-            (1528, 1190, 868, 658),   // Version 28
-            (1628, 1264, 908, 698),   // Version 29
-            (1732, 1370, 982, 742),   // Version 30
-            (1840, 1452, 1030, 790),  // Version 31
-            (1952, 1538, 1112, 842),  // Version 32
-            (2068, 1628, 1168, 898),  // Version 33
-            (2188, 1722, 1228, 958),  // Version 34
-            (2303, 1809, 1283, 983),  // Version 35
-            (2431, 1911, 1351, 1051), // Version 36
-            (2563, 1989, 1423, 1093), // Version 37
-            (2699, 2099, 1499, 1139), // Version 38
-            (2809, 2213, 1579, 1219), // Version 39
-            (2953, 2331, 1663, 1273), // Version 40
-        ];
+    /// Unlike `encode`/`encode_advanced`, this always uses Byte mode for
+    /// `data` (the designated charset only has meaning for byte data) and
+    /// does not run the numeric/alphanumeric segmentation DP.
+    pub fn encode_with_eci(data: &[u8], eci: u32, ecl: ErrorCorrectionLevel) -> Result<Self, String> {
+        if eci > 999_999 {
+            return Err(format!(
+                "ECI assignment number {} exceeds the maximum of 999999",
+                eci
+            ));
+        }
+
+        let version = Self::find_min_version_eci(data, eci, ecl, 1, 40)?;
+        let size = version as usize * 4 + 17;
+
+        let data_codewords = Self::encode_data_with_eci(data, version, ecl, eci)?;
+        let all_codewords = Self::add_error_correction(&data_codewords, version, ecl);
+
+        let mut qr = Self {
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+            version,
+            error_correction: ecl,
+            mask: 0,
+            kind: SymbolKind::Full,
+        };
+
+        qr.place_function_patterns();
+        qr.place_data_bits(&all_codewords);
+        qr.apply_best_mask();
+        qr.place_format_info();
+
+        if version >= 7 {
+            qr.place_version_info();
+        }
+
+        Ok(qr)
+    }
+
+    /// Generate a QR code whose payload is a single Kanji-mode segment,
+    /// for Shift-JIS double-byte text - 13 bits/character versus 16
+    /// bits/character in Byte mode, per ISO/IEC 18004 Section 7.4.6.
+    ///
+    /// `data` must be raw Shift-JIS bytes (not UTF-8) with an even length,
+    /// each consecutive pair falling in the 0x8140-0x9FFC or
+    /// 0xE040-0xEBBF double-byte ranges. Readers that want the declared
+    /// charset made explicit can pair this with an ECI designator via
+    /// `encode_with_eci`-style wrapping; this crate doesn't combine the
+    /// two in one call since Kanji mode already implies Shift-JIS.
+    pub fn encode_kanji(data: &[u8], ecl: ErrorCorrectionLevel) -> Result<Self, String> {
+        if !data.len().is_multiple_of(2) {
+            return Err("Kanji data must be an even number of Shift-JIS bytes".to_string());
+        }
+
+        let version = Self::find_min_version_kanji(data, ecl, 1, 40)?;
+        let size = version as usize * 4 + 17;
+
+        let data_codewords = Self::encode_data_kanji(data, version, ecl)?;
+        let all_codewords = Self::add_error_correction(&data_codewords, version, ecl);
+
+        let mut qr = Self {
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+            version,
+            error_correction: ecl,
+            mask: 0,
+            kind: SymbolKind::Full,
+        };
 
-        for (version_idx, cap) in capacities.iter().enumerate() {
-            let capacity = match ecl {
-                ErrorCorrectionLevel::L => cap.0,
-                ErrorCorrectionLevel::M => cap.1,
-                ErrorCorrectionLevel::Q => cap.2,
-                ErrorCorrectionLevel::H => cap.3,
+        qr.place_function_patterns();
+        qr.place_data_bits(&all_codewords);
+        qr.apply_best_mask();
+        qr.place_format_info();
+
+        if version >= 7 {
+            qr.place_version_info();
+        }
+
+        Ok(qr)
+    }
+
+    /// Split `data` across up to 16 symbols using Structured Append
+    /// (ISO/IEC 18004 Section 8), for payloads too large for a single
+    /// symbol. Each returned `QrCode` carries a Structured Append header
+    /// (mode indicator, its position and the total symbol count, and a
+    /// parity byte shared by every symbol in the set - see
+    /// `append_structured_append_header`) followed by a Byte-mode segment
+    /// for its slice of `data`. A compliant reader reassembles the
+    /// original bytes by concatenating the symbols in position order and
+    /// checking the parity byte.
+    pub fn encode_structured_append(
+        data: &[u8],
+        ecl: ErrorCorrectionLevel,
+    ) -> Result<Vec<Self>, String> {
+        const MAX_SYMBOLS: usize = 16;
+        if data.is_empty() {
+            return Err("Cannot Structured-Append empty data".to_string());
+        }
+
+        let parity = data.iter().fold(0u8, |acc, &b| acc ^ b);
+
+        for symbol_count in 1..=MAX_SYMBOLS {
+            let chunk_len = data.len().div_ceil(symbol_count);
+            let slices: Vec<&[u8]> = data.chunks(chunk_len).collect();
+            if slices.len() > MAX_SYMBOLS {
+                continue;
+            }
+            if let Some(symbols) = Self::try_structured_append(&slices, ecl, parity) {
+                return Ok(symbols);
+            }
+        }
+
+        Err(format!(
+            "Data too large for Structured Append across up to {} symbols",
+            MAX_SYMBOLS
+        ))
+    }
+
+    /// Encode each of `slices` as its own Structured Append symbol, or
+    /// return `None` if any slice doesn't fit within version 40 at `ecl`
+    /// (the caller retries with more, smaller slices in that case).
+    fn try_structured_append(
+        slices: &[&[u8]],
+        ecl: ErrorCorrectionLevel,
+        parity: u8,
+    ) -> Option<Vec<Self>> {
+        let total = slices.len() as u8;
+        let mut symbols = Vec::with_capacity(slices.len());
+
+        for (index, slice) in slices.iter().enumerate() {
+            let version =
+                Self::find_min_version_structured_append(slice.len(), ecl, 1, 40).ok()?;
+            let size = version as usize * 4 + 17;
+
+            let data_codewords = Self::encode_data_structured_append(
+                slice,
+                version,
+                ecl,
+                index as u8,
+                total,
+                parity,
+            )
+            .ok()?;
+            let all_codewords = Self::add_error_correction(&data_codewords, version, ecl);
+
+            let mut qr = Self {
+                modules: vec![vec![false; size]; size],
+                is_function: vec![vec![false; size]; size],
+                version,
+                error_correction: ecl,
+                mask: 0,
+                kind: SymbolKind::Full,
             };
 
-            if data_len <= capacity {
-                return Ok((version_idx + 1) as u8);
+            qr.place_function_patterns();
+            qr.place_data_bits(&all_codewords);
+            qr.apply_best_mask();
+            qr.place_format_info();
+
+            if version >= 7 {
+                qr.place_version_info();
+            }
+
+            symbols.push(qr);
+        }
+
+        Some(symbols)
+    }
+
+    /// Find the minimum QR version in `min_version..=max_version` that can
+    /// hold `data`, using the bit length of the optimally-segmented
+    /// encoding (see `segment_data`) rather than a raw byte count - a
+    /// mostly-numeric or alphanumeric payload fits in a smaller version
+    /// than treating it as byte mode.
+    fn find_min_version(
+        data: &[u8],
+        ecl: ErrorCorrectionLevel,
+        min_version: u8,
+        max_version: u8,
+    ) -> Result<u8, String> {
+        for version in min_version..=max_version {
+            let (_, bit_len) = Self::segment_data(data, version);
+            let capacity_bits = Self::get_data_codewords(version, ecl) * 8;
+            if bit_len as usize <= capacity_bits {
+                return Ok(version);
             }
         }
 
         Err("Data too large for QR code".to_string())
     }
 
-    /// Encode data into codewords using byte mode.
+    /// Find the highest EC level from `min_ecl` upward (L -> M -> Q -> H)
+    /// whose data capacity at `version` still fits `data`'s encoded bit
+    /// length, so the caller gets extra damage resistance for free.
+    fn boost_ec_level(
+        data: &[u8],
+        version: u8,
+        min_ecl: ErrorCorrectionLevel,
+    ) -> ErrorCorrectionLevel {
+        const ORDER: [ErrorCorrectionLevel; 4] = [
+            ErrorCorrectionLevel::L,
+            ErrorCorrectionLevel::M,
+            ErrorCorrectionLevel::Q,
+            ErrorCorrectionLevel::H,
+        ];
+        let (_, bit_len) = Self::segment_data(data, version);
+        let start = ORDER.iter().position(|&l| l == min_ecl).unwrap_or(0);
+
+        let mut best = min_ecl;
+        for &level in &ORDER[start..] {
+            let capacity_bits = Self::get_data_codewords(version, level) * 8;
+            if bit_len as usize <= capacity_bits {
+                best = level;
+            }
+        }
+        best
+    }
+
+    /// The character-count-indicator width (bits) for `mode` at `version`.
+    ///
+    /// Per ISO/IEC 18004 Table 3, the width depends on which version band
+    /// (1-9, 10-26, 27-40) the symbol falls into.
+    fn mode_count_bits(mode: Mode, version: u8) -> u32 {
+        match mode {
+            Mode::Numeric => {
+                if version <= 9 {
+                    10
+                } else if version <= 26 {
+                    12
+                } else {
+                    14
+                }
+            }
+            Mode::Alphanumeric => {
+                if version <= 9 {
+                    9
+                } else if version <= 26 {
+                    11
+                } else {
+                    13
+                }
+            }
+            Mode::Byte => {
+                if version <= 9 {
+                    8
+                } else {
+                    16
+                }
+            }
+            Mode::Kanji => {
+                if version <= 9 {
+                    8
+                } else if version <= 26 {
+                    10
+                } else {
+                    12
+                }
+            }
+        }
+    }
+
+    /// Exact header + data bit length for one segment, using the grouped
+    /// numeric ("10 bits per 3 digits") and alphanumeric ("11 bits per
+    /// pair") formulas - not the averaged cost `segment_data`'s DP uses to
+    /// choose boundaries.
+    fn segment_bit_len(segment: &Segment, version: u8) -> u32 {
+        let len = (segment.end - segment.start) as u32;
+        let header = 4 + Self::mode_count_bits(segment.mode, version);
+        let data_bits = match segment.mode {
+            Mode::Numeric => {
+                10 * (len / 3)
+                    + match len % 3 {
+                        0 => 0,
+                        1 => 4,
+                        _ => 7,
+                    }
+            }
+            Mode::Alphanumeric => 11 * (len / 2) + if len % 2 == 1 { 6 } else { 0 },
+            Mode::Byte => 8 * len,
+            // Kanji segments are never produced by `segment_data`'s DP
+            // (see `Mode::Kanji`'s doc comment); `len` here would be a
+            // byte count, so this arm exists only for exhaustiveness.
+            Mode::Kanji => 13 * (len / 2),
+        };
+        header + data_bits
+    }
+
+    /// Split `data` into an optimal sequence of mode segments for `version`,
+    /// and return them along with the total header+data bit length they
+    /// require (excluding terminator/padding).
+    ///
+    /// Runs a dynamic program `dp[i][mode]` = minimum cost to encode the
+    /// first `i` bytes with the last segment in `mode`, where every
+    /// transition's cost is an *average* per-character bit cost (Numeric
+    /// 10/3, Alphanumeric 11/2, Byte 8) scaled by 6 - the LCD of the two
+    /// fractional denominators - so every comparison stays an exact
+    /// integer, as in Nayuki's and speedata's QR encoders. This picks good
+    /// segment boundaries; the exact bit length per boundary (accounting
+    /// for the "trailing pair"/"trailing single" group remainders) is then
+    /// recomputed via `segment_bit_len`.
+    fn segment_data(data: &[u8], version: u8) -> (Vec<Segment>, u32) {
+        const SCALE: i64 = 6;
+        const MODES: [Mode; 3] = [Mode::Numeric, Mode::Alphanumeric, Mode::Byte];
+        const INF: i64 = i64::MAX / 2;
+
+        fn eligible(mode: Mode, byte: u8) -> bool {
+            match mode {
+                Mode::Numeric => byte.is_ascii_digit(),
+                Mode::Alphanumeric => alphanumeric_value(byte).is_some(),
+                Mode::Byte => true,
+                // Not a candidate in this DP - see `Mode::Kanji`'s doc comment.
+                Mode::Kanji => false,
+            }
+        }
+
+        // Average per-character data cost, scaled by SCALE.
+        fn data_cost(mode: Mode) -> i64 {
+            match mode {
+                Mode::Numeric => 10 * SCALE / 3,
+                Mode::Alphanumeric => 11 * SCALE / 2,
+                Mode::Byte => 8 * SCALE,
+                Mode::Kanji => 13 * SCALE / 2,
+            }
+        }
+
+        #[derive(Clone, Copy)]
+        enum Choice {
+            Continue,
+            Start(usize),
+        }
+
+        let n = data.len();
+        if n == 0 {
+            return (Vec::new(), 0);
+        }
+
+        let mut dp: Vec<[i64; 3]> = Vec::with_capacity(n + 1);
+        dp.push([0; 3]);
+        let mut choices: Vec<[Choice; 3]> = Vec::with_capacity(n);
+
+        for i in 1..=n {
+            let byte = data[i - 1];
+            let prev = dp[i - 1];
+            let mut row = [INF; 3];
+            let mut row_choice = [Choice::Start(0); 3];
+
+            let (best_prev_idx, &best_prev_cost) =
+                prev.iter().enumerate().min_by_key(|&(_, &c)| c).unwrap();
+
+            for (m_idx, &mode) in MODES.iter().enumerate() {
+                if !eligible(mode, byte) {
+                    continue;
+                }
+                let cost = data_cost(mode);
+                let header = (4 + Self::mode_count_bits(mode, version) as i64) * SCALE;
+
+                let continue_cost = prev[m_idx] + cost;
+                let start_cost = best_prev_cost + header + cost;
+
+                if continue_cost <= start_cost {
+                    row[m_idx] = continue_cost;
+                    row_choice[m_idx] = Choice::Continue;
+                } else {
+                    row[m_idx] = start_cost;
+                    row_choice[m_idx] = Choice::Start(best_prev_idx);
+                }
+            }
+
+            dp.push(row);
+            choices.push(row_choice);
+        }
+
+        let mut mode_idx = (0..3).min_by_key(|&m| dp[n][m]).unwrap();
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut i = n;
+        let mut seg_end = n;
+        while i > 0 {
+            match choices[i - 1][mode_idx] {
+                Choice::Continue => i -= 1,
+                Choice::Start(prev_idx) => {
+                    segments.push(Segment {
+                        mode: MODES[mode_idx],
+                        start: i - 1,
+                        end: seg_end,
+                    });
+                    i -= 1;
+                    seg_end = i;
+                    mode_idx = prev_idx;
+                }
+            }
+        }
+        segments.push(Segment {
+            mode: MODES[mode_idx],
+            start: 0,
+            end: seg_end,
+        });
+        segments.reverse();
+
+        let total_bits = segments
+            .iter()
+            .map(|seg| Self::segment_bit_len(seg, version))
+            .sum();
+        (segments, total_bits)
+    }
+
+    /// Pack a numeric segment's digits 3-at-a-time into 10-bit groups (7
+    /// bits for a trailing pair, 4 bits for a trailing single digit).
+    fn append_numeric_segment(bits: &mut BitBuffer, digits: &[u8]) {
+        let mut i = 0;
+        while i < digits.len() {
+            let remaining = digits.len() - i;
+            let digit = |offset: usize| (digits[i + offset] - b'0') as u32;
+            if remaining >= 3 {
+                bits.append_bits(digit(0) * 100 + digit(1) * 10 + digit(2), 10);
+                i += 3;
+            } else if remaining == 2 {
+                bits.append_bits(digit(0) * 10 + digit(1), 7);
+                i += 2;
+            } else {
+                bits.append_bits(digit(0), 4);
+                i += 1;
+            }
+        }
+    }
+
+    /// Pack an alphanumeric segment's characters 2-at-a-time into 11-bit
+    /// groups (6 bits for a trailing single character).
+    fn append_alphanumeric_segment(bits: &mut BitBuffer, chars: &[u8]) {
+        let mut i = 0;
+        while i < chars.len() {
+            let value = |offset: usize| {
+                alphanumeric_value(chars[i + offset])
+                    .expect("segment byte must be in the alphanumeric set")
+            };
+            if chars.len() - i >= 2 {
+                bits.append_bits(value(0) * 45 + value(1), 11);
+                i += 2;
+            } else {
+                bits.append_bits(value(0), 6);
+                i += 1;
+            }
+        }
+    }
+
+    /// Encode data into codewords using an optimal sequence of mode
+    /// segments (see `segment_data`).
     ///
     /// The encoding format is:
-    /// - Mode indicator (4 bits): 0100 for byte mode
-    /// - Character count (8 or 16 bits depending on version)
-    /// - Data bytes
+    /// - Per segment: mode indicator (4 bits), character count (width
+    ///   depends on mode and version), then the segment's packed data
     /// - Terminator (up to 4 zero bits)
     /// - Pad to byte boundary
     /// - Pad codewords (0xEC, 0x11 alternating)
     fn encode_data(data: &[u8], version: u8, ecl: ErrorCorrectionLevel) -> Result<Vec<u8>, String> {
         let mut bits = BitBuffer::new();
 
-        // Mode indicator: 0100 for byte mode
-        bits.append_bits(0b0100, 4);
-
-        // Character count indicator
-        // Versions 1-9: 8 bits, 10-26: 16 bits, 27-40: 16 bits for byte mode
-        let count_bits = if version <= 9 { 8 } else { 16 };
-        bits.append_bits(data.len() as u32, count_bits);
-
-        // Data bytes
-        for &byte in data {
-            bits.append_bits(byte as u32, 8);
+        let (segments, _) = Self::segment_data(data, version);
+        for segment in &segments {
+            let chunk = &data[segment.start..segment.end];
+            bits.append_bits(segment.mode as u32, 4);
+            bits.append_bits(
+                chunk.len() as u32,
+                Self::mode_count_bits(segment.mode, version) as usize,
+            );
+            match segment.mode {
+                Mode::Numeric => Self::append_numeric_segment(&mut bits, chunk),
+                Mode::Alphanumeric => Self::append_alphanumeric_segment(&mut bits, chunk),
+                Mode::Byte => {
+                    for &byte in chunk {
+                        bits.append_bits(byte as u32, 8);
+                    }
+                }
+                // Never produced by `segment_data` - see `Mode::Kanji`'s doc comment.
+                Mode::Kanji => unreachable!("segment_data never selects Kanji mode"),
+            }
         }
 
         // Get total data codewords capacity
@@ -400,93 +899,392 @@ impl QrCode {
         Ok(codewords)
     }
 
-    /// Add Reed-Solomon error correction codewords.
-    ///
-    /// ## Reed-Solomon Error Correction
-    ///
-    /// Reed-Solomon codes work over GF(2^8) - a finite field with 256 elements.
-    /// Key concepts:
-    ///
-    /// 1. **Generator Polynomial**: A polynomial whose roots are consecutive
-    ///    powers of alpha (a primitive element of GF(2^8)).
-    ///    For n EC codewords: g(x) = (x - alpha^0)(x - alpha^1)...(x - alpha^(n-1))
-    ///
-    /// 2. **Encoding**: Treat data as polynomial coefficients, divide by
-    ///    generator polynomial. The remainder becomes EC codewords.
-    ///
-    /// 3. **Decoding**: Check if received polynomial is divisible by generator.
-    ///    If not, solve for error locations and values using syndromes.
-    fn add_error_correction(data: &[u8], version: u8, ecl: ErrorCorrectionLevel) -> Vec<u8> {
-        let (num_blocks, ec_per_block) = Self::get_ec_params(version, ecl);
-        let total_codewords = Self::get_total_codewords(version);
-        let data_codewords = Self::get_data_codewords(version, ecl);
-        let short_block_len = data_codewords / num_blocks;
-        let long_blocks = data_codewords % num_blocks;
-
-        let generator = Self::reed_solomon_generator(ec_per_block);
-
-        let mut data_blocks: Vec<Vec<u8>> = Vec::new();
-        let mut ec_blocks: Vec<Vec<u8>> = Vec::new();
-
-        let mut offset = 0;
-        for i in 0..num_blocks {
-            let block_len = short_block_len + if i >= num_blocks - long_blocks { 1 } else { 0 };
-            let block: Vec<u8> = data[offset..offset + block_len].to_vec();
-            offset += block_len;
-
-            let ec = Self::reed_solomon_encode(&block, &generator, ec_per_block);
-            data_blocks.push(block);
-            ec_blocks.push(ec);
+    /// The 4-bit ECI mode indicator, per ISO/IEC 18004 Section 7.4.2.
+    const ECI_MODE_INDICATOR: u32 = 0b0111;
+
+    /// Bit length of an ECI assignment number's designator encoding (see
+    /// `append_eci_designator`), needed by callers sizing capacity before
+    /// actually writing the bits.
+    fn eci_designator_bit_len(eci: u32) -> u32 {
+        if eci <= 127 {
+            8
+        } else if eci <= 16_383 {
+            16
+        } else {
+            24
         }
+    }
 
-        // Interleave blocks
-        let mut result = Vec::with_capacity(total_codewords);
+    /// Append an ECI assignment number using the banded 1/2/3-byte
+    /// encoding from ISO/IEC 18004 Section 7.4.2 / Annex D:
+    /// - 0-127: one byte, top bit 0.
+    /// - 128-16383: two bytes, `10` prefix + 14-bit value.
+    /// - 16384-999999: three bytes, `110` prefix + 21-bit value.
+    fn append_eci_designator(bits: &mut BitBuffer, eci: u32) {
+        if eci <= 127 {
+            bits.append_bits(eci, 8);
+        } else if eci <= 16_383 {
+            bits.append_bits((0b10 << 14) | eci, 16);
+        } else {
+            bits.append_bits((0b110 << 21) | eci, 24);
+        }
+    }
 
-        // Interleave data codewords
-        let max_data_len = short_block_len + 1;
-        for i in 0..max_data_len {
-            for block in &data_blocks {
-                if i < block.len() {
-                    result.push(block[i]);
-                }
-            }
+    /// Advance `*pos` past an ECI designator written by
+    /// `append_eci_designator`, reading just enough bits to tell its
+    /// length (1/2/3 bytes) from the leading prefix bits.
+    fn skip_eci_designator(bits: &[bool], pos: &mut usize) -> Result<(), String> {
+        if *pos >= bits.len() {
+            return Err("Truncated ECI designator".to_string());
         }
+        let remaining_len = if !bits[*pos] {
+            8
+        } else if *pos + 1 < bits.len() && !bits[*pos + 1] {
+            16
+        } else {
+            24
+        };
+        if *pos + remaining_len > bits.len() {
+            return Err("Truncated ECI designator".to_string());
+        }
+        *pos += remaining_len;
+        Ok(())
+    }
 
-        // Interleave EC codewords
-        for i in 0..ec_per_block {
-            for block in &ec_blocks {
-                result.push(block[i]);
+    /// Find the minimum version that fits an ECI designator followed by
+    /// `data` encoded as a single Byte-mode segment (see
+    /// `encode_data_with_eci`).
+    fn find_min_version_eci(
+        data: &[u8],
+        eci: u32,
+        ecl: ErrorCorrectionLevel,
+        min_version: u8,
+        max_version: u8,
+    ) -> Result<u8, String> {
+        for version in min_version..=max_version {
+            let bit_len = Self::eci_designator_bit_len(eci)
+                + 4
+                + Self::mode_count_bits(Mode::Byte, version)
+                + data.len() as u32 * 8;
+            let capacity_bits = Self::get_data_codewords(version, ecl) * 8;
+            if bit_len as usize <= capacity_bits {
+                return Ok(version);
             }
         }
 
-        result
+        Err("Data too large for QR code".to_string())
     }
 
-    /// Generate Reed-Solomon generator polynomial.
-    ///
-    /// The generator polynomial for n EC codewords is:
-    /// g(x) = (x - alpha^0)(x - alpha^1)...(x - alpha^(n-1))
-    ///
-    /// We store coefficients in decreasing degree order.
-    fn reed_solomon_generator(degree: usize) -> Vec<u8> {
-        let mut result = vec![1u8];
-
-        for i in 0..degree {
-            let mut new_result = vec![0u8; result.len() + 1];
-            let alpha_i = GF256::exp(i as u8);
+    /// Encode `data` as codewords prefixed with an ECI designator and a
+    /// single Byte-mode segment - `encode_data`'s pipeline specialized to
+    /// skip segmentation, since a declared character set only makes sense
+    /// applied to the whole byte payload.
+    fn encode_data_with_eci(
+        data: &[u8],
+        version: u8,
+        ecl: ErrorCorrectionLevel,
+        eci: u32,
+    ) -> Result<Vec<u8>, String> {
+        let mut bits = BitBuffer::new();
 
-            for (j, &coef) in result.iter().enumerate() {
-                new_result[j] ^= GF256::mul(coef, alpha_i);
-                new_result[j + 1] ^= coef;
-            }
+        bits.append_bits(Self::ECI_MODE_INDICATOR, 4);
+        Self::append_eci_designator(&mut bits, eci);
 
-            result = new_result;
+        bits.append_bits(Mode::Byte as u32, 4);
+        bits.append_bits(
+            data.len() as u32,
+            Self::mode_count_bits(Mode::Byte, version) as usize,
+        );
+        for &byte in data {
+            bits.append_bits(byte as u32, 8);
         }
 
-        result
-    }
+        let total_codewords = Self::get_data_codewords(version, ecl);
+        let capacity_bits = total_codewords * 8;
+        let terminator_len = std::cmp::min(4, capacity_bits.saturating_sub(bits.len()));
+        bits.append_bits(0, terminator_len);
 
-    /// Compute Reed-Solomon error correction codewords.
+        while !bits.len().is_multiple_of(8) {
+            bits.append_bits(0, 1);
+        }
+
+        let mut codewords = bits.to_bytes();
+        let mut pad_toggle = true;
+        while codewords.len() < total_codewords {
+            codewords.push(if pad_toggle { 0xEC } else { 0x11 });
+            pad_toggle = !pad_toggle;
+        }
+
+        Ok(codewords)
+    }
+
+    /// The 4-bit Kanji mode indicator, per ISO/IEC 18004 Section 7.4.6.
+    const KANJI_MODE_INDICATOR: u32 = 0b1000;
+
+    /// Pack one Shift-JIS double-byte character into its 13-bit Kanji
+    /// value: subtract the range's base (`0x8140` or `0xC140`), then
+    /// combine the shifted high byte with the low byte.
+    fn kanji_char_value(hi: u8, lo: u8) -> Result<u32, String> {
+        let pair = ((hi as u32) << 8) | lo as u32;
+        let subtracted = if (0x8140..=0x9FFC).contains(&pair) {
+            pair - 0x8140
+        } else if (0xE040..=0xEBBF).contains(&pair) {
+            pair - 0xC140
+        } else {
+            return Err(format!(
+                "Byte pair {:#06x} is not a valid Shift-JIS Kanji character",
+                pair
+            ));
+        };
+        Ok(((subtracted >> 8) * 0xC0) + (subtracted & 0xFF))
+    }
+
+    /// Append `data` (raw Shift-JIS bytes, already validated to have an
+    /// even length) to `bits` as a sequence of 13-bit Kanji values.
+    fn append_kanji_segment(bits: &mut BitBuffer, data: &[u8]) -> Result<(), String> {
+        for pair in data.chunks(2) {
+            let value = Self::kanji_char_value(pair[0], pair[1])?;
+            bits.append_bits(value, 13);
+        }
+        Ok(())
+    }
+
+    /// Find the minimum version that fits a single Kanji-mode segment
+    /// encoding `data` (see `encode_data_kanji`).
+    fn find_min_version_kanji(
+        data: &[u8],
+        ecl: ErrorCorrectionLevel,
+        min_version: u8,
+        max_version: u8,
+    ) -> Result<u8, String> {
+        for version in min_version..=max_version {
+            let bit_len = 4
+                + Self::mode_count_bits(Mode::Kanji, version)
+                + (data.len() / 2) as u32 * 13;
+            let capacity_bits = Self::get_data_codewords(version, ecl) * 8;
+            if bit_len as usize <= capacity_bits {
+                return Ok(version);
+            }
+        }
+
+        Err("Data too large for QR code".to_string())
+    }
+
+    /// Encode `data` as codewords holding a single Kanji-mode segment -
+    /// `encode_data_with_eci`'s pipeline with the ECI designator and
+    /// Byte-mode segment swapped for a Kanji mode indicator and segment.
+    fn encode_data_kanji(
+        data: &[u8],
+        version: u8,
+        ecl: ErrorCorrectionLevel,
+    ) -> Result<Vec<u8>, String> {
+        let mut bits = BitBuffer::new();
+
+        bits.append_bits(Self::KANJI_MODE_INDICATOR, 4);
+        bits.append_bits(
+            (data.len() / 2) as u32,
+            Self::mode_count_bits(Mode::Kanji, version) as usize,
+        );
+        Self::append_kanji_segment(&mut bits, data)?;
+
+        let total_codewords = Self::get_data_codewords(version, ecl);
+        let capacity_bits = total_codewords * 8;
+        let terminator_len = std::cmp::min(4, capacity_bits.saturating_sub(bits.len()));
+        bits.append_bits(0, terminator_len);
+
+        while !bits.len().is_multiple_of(8) {
+            bits.append_bits(0, 1);
+        }
+
+        let mut codewords = bits.to_bytes();
+        let mut pad_toggle = true;
+        while codewords.len() < total_codewords {
+            codewords.push(if pad_toggle { 0xEC } else { 0x11 });
+            pad_toggle = !pad_toggle;
+        }
+
+        Ok(codewords)
+    }
+
+    /// The 4-bit Structured Append mode indicator, per ISO/IEC 18004
+    /// Section 8.
+    const STRUCTURED_APPEND_MODE_INDICATOR: u32 = 0b0011;
+
+    /// Bit length of a Structured Append header: mode indicator (4 bits)
+    /// + symbol sequence indicator (4-bit position, 4-bit total count
+    /// minus one) + an 8-bit parity byte shared by every symbol in the
+    /// set.
+    const STRUCTURED_APPEND_HEADER_BITS: u32 = 4 + 4 + 4 + 8;
+
+    /// Write a Structured Append header: mode indicator, `position`
+    /// (0-based) and `total` symbol count (1-16, encoded as `total - 1`),
+    /// then `parity` (the XOR of every byte of the complete, unsplit
+    /// payload - identical across all symbols in the set, letting a
+    /// reader confirm they belong together).
+    fn append_structured_append_header(bits: &mut BitBuffer, position: u8, total: u8, parity: u8) {
+        bits.append_bits(Self::STRUCTURED_APPEND_MODE_INDICATOR, 4);
+        bits.append_bits(position as u32, 4);
+        bits.append_bits((total - 1) as u32, 4);
+        bits.append_bits(parity as u32, 8);
+    }
+
+    /// Find the minimum version that fits a Structured Append header
+    /// followed by a single Byte-mode segment of `slice_len` bytes (see
+    /// `encode_data_structured_append`).
+    fn find_min_version_structured_append(
+        slice_len: usize,
+        ecl: ErrorCorrectionLevel,
+        min_version: u8,
+        max_version: u8,
+    ) -> Result<u8, String> {
+        for version in min_version..=max_version {
+            let bit_len = Self::STRUCTURED_APPEND_HEADER_BITS
+                + 4
+                + Self::mode_count_bits(Mode::Byte, version)
+                + slice_len as u32 * 8;
+            let capacity_bits = Self::get_data_codewords(version, ecl) * 8;
+            if bit_len as usize <= capacity_bits {
+                return Ok(version);
+            }
+        }
+
+        Err("Structured Append slice too large for QR code".to_string())
+    }
+
+    /// Encode one Structured Append symbol's codewords: header, then a
+    /// single Byte-mode segment for `data` (this slice's share of the
+    /// original payload) - `encode_data_with_eci`'s pipeline with a
+    /// Structured Append header in place of an ECI designator.
+    fn encode_data_structured_append(
+        data: &[u8],
+        version: u8,
+        ecl: ErrorCorrectionLevel,
+        position: u8,
+        total: u8,
+        parity: u8,
+    ) -> Result<Vec<u8>, String> {
+        let mut bits = BitBuffer::new();
+
+        Self::append_structured_append_header(&mut bits, position, total, parity);
+
+        bits.append_bits(Mode::Byte as u32, 4);
+        bits.append_bits(
+            data.len() as u32,
+            Self::mode_count_bits(Mode::Byte, version) as usize,
+        );
+        for &byte in data {
+            bits.append_bits(byte as u32, 8);
+        }
+
+        let total_codewords = Self::get_data_codewords(version, ecl);
+        let capacity_bits = total_codewords * 8;
+        if bits.len() > capacity_bits {
+            return Err("Structured Append slice does not fit in the selected version".to_string());
+        }
+        let terminator_len = std::cmp::min(4, capacity_bits.saturating_sub(bits.len()));
+        bits.append_bits(0, terminator_len);
+
+        while !bits.len().is_multiple_of(8) {
+            bits.append_bits(0, 1);
+        }
+
+        let mut codewords = bits.to_bytes();
+        let mut pad_toggle = true;
+        while codewords.len() < total_codewords {
+            codewords.push(if pad_toggle { 0xEC } else { 0x11 });
+            pad_toggle = !pad_toggle;
+        }
+
+        Ok(codewords)
+    }
+
+    /// Add Reed-Solomon error correction codewords.
+    ///
+    /// ## Reed-Solomon Error Correction
+    ///
+    /// Reed-Solomon codes work over GF(2^8) - a finite field with 256 elements.
+    /// Key concepts:
+    ///
+    /// 1. **Generator Polynomial**: A polynomial whose roots are consecutive
+    ///    powers of alpha (a primitive element of GF(2^8)).
+    ///    For n EC codewords: g(x) = (x - alpha^0)(x - alpha^1)...(x - alpha^(n-1))
+    ///
+    /// 2. **Encoding**: Treat data as polynomial coefficients, divide by
+    ///    generator polynomial. The remainder becomes EC codewords.
+    ///
+    /// 3. **Decoding**: Check if received polynomial is divisible by generator.
+    ///    If not, solve for error locations and values using syndromes.
+    fn add_error_correction(data: &[u8], version: u8, ecl: ErrorCorrectionLevel) -> Vec<u8> {
+        let (num_blocks, ec_per_block) = Self::get_ec_params(version, ecl);
+        let total_codewords = Self::get_total_codewords(version);
+        let data_codewords = Self::get_data_codewords(version, ecl);
+        let short_block_len = data_codewords / num_blocks;
+        let long_blocks = data_codewords % num_blocks;
+
+        let generator = Self::reed_solomon_generator(ec_per_block);
+
+        let mut data_blocks: Vec<Vec<u8>> = Vec::new();
+        let mut ec_blocks: Vec<Vec<u8>> = Vec::new();
+
+        let mut offset = 0;
+        for i in 0..num_blocks {
+            let block_len = short_block_len + if i >= num_blocks - long_blocks { 1 } else { 0 };
+            let block: Vec<u8> = data[offset..offset + block_len].to_vec();
+            offset += block_len;
+
+            let ec = Self::reed_solomon_encode(&block, &generator, ec_per_block);
+            data_blocks.push(block);
+            ec_blocks.push(ec);
+        }
+
+        // Interleave blocks
+        let mut result = Vec::with_capacity(total_codewords);
+
+        // Interleave data codewords
+        let max_data_len = short_block_len + 1;
+        for i in 0..max_data_len {
+            for block in &data_blocks {
+                if i < block.len() {
+                    result.push(block[i]);
+                }
+            }
+        }
+
+        // Interleave EC codewords
+        for i in 0..ec_per_block {
+            for block in &ec_blocks {
+                result.push(block[i]);
+            }
+        }
+
+        result
+    }
+
+    /// Generate Reed-Solomon generator polynomial.
+    ///
+    /// The generator polynomial for n EC codewords is:
+    /// g(x) = (x - alpha^0)(x - alpha^1)...(x - alpha^(n-1))
+    ///
+    /// We store coefficients in decreasing degree order.
+    fn reed_solomon_generator(degree: usize) -> Vec<u8> {
+        let mut result = vec![1u8];
+
+        for i in 0..degree {
+            let mut new_result = vec![0u8; result.len() + 1];
+            let alpha_i = GF256::exp(i as u8);
+
+            for (j, &coef) in result.iter().enumerate() {
+                new_result[j] ^= coef;
+                new_result[j + 1] ^= GF256::mul(coef, alpha_i);
+            }
+
+            result = new_result;
+        }
+
+        result
+    }
+
+    /// Compute Reed-Solomon error correction codewords.
     ///
     /// This performs polynomial division in GF(2^8):
     /// data(x) * x^n mod generator(x) = remainder(x)
@@ -510,6 +1308,19 @@ impl QrCode {
         remainder
     }
 
+    /// Public entry point for correcting a single Reed-Solomon block: given
+    /// `codewords` (data codewords followed by `num_ec` EC codewords, the
+    /// same layout `reed_solomon_encode` produces), recover up to `num_ec /
+    /// 2` corrupted byte errors and return the full corrected block. This is
+    /// the same syndrome/Berlekamp-Massey/Chien/Forney pipeline
+    /// `rs_decode_blocks` already runs internally per-block while decoding a
+    /// full symbol (see `reed_solomon_correct`); this wrapper exposes it for
+    /// callers working with raw codeword blocks directly, e.g. to correct a
+    /// partially-damaged scan before re-running higher-level parsing.
+    pub fn reed_solomon_decode(codewords: &[u8], num_ec: usize) -> Result<Vec<u8>, String> {
+        Self::reed_solomon_correct(codewords, num_ec)
+    }
+
     /// Place finder patterns, timing patterns, and other function patterns.
     fn place_function_patterns(&mut self) {
         let size = self.modules.len();
@@ -947,17 +1758,7 @@ impl QrCode {
 
     /// Calculate BCH(15,5) format bits.
     fn calculate_format_bits(data: u32) -> u32 {
-        let mut bits = data << 10;
-        let generator = 0b10100110111; // BCH generator polynomial
-
-        for i in (0..=4).rev() {
-            if (bits >> (i + 10)) & 1 == 1 {
-                bits ^= generator << i;
-            }
-        }
-
-        let format = (data << 10) | bits;
-        format ^ 0b101010000010010 // XOR with mask pattern
+        Self::calculate_format_bits_with_mask(data, 0b101010000010010)
     }
 
     /// Place version information for version 7+.
@@ -996,44 +1797,25 @@ impl QrCode {
         self.modules.len()
     }
 
+    /// Whether this is a standard QR Code or a Micro QR Code.
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
     /// Get the module value at (row, col). true = black, false = white.
     pub fn get(&self, row: usize, col: usize) -> bool {
         self.modules[row][col]
     }
 
+    /// Start a [`Renderer`] for configuring custom colors, quiet-zone
+    /// width, or non-square module scaling before rendering.
+    pub fn render(&self) -> Renderer<'_> {
+        Renderer::new(self)
+    }
+
     /// Render the QR code as an SVG string.
     pub fn to_svg(&self, module_size: u32) -> String {
-        let size = self.size();
-        let quiet_zone = 4; // Standard quiet zone is 4 modules
-        let total_size = (size + 2 * quiet_zone) * module_size as usize;
-
-        let mut svg = format!(
-            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" width="{}" height="{}">"#,
-            total_size, total_size, total_size, total_size
-        );
-
-        // White background
-        svg.push_str(&format!(
-            r#"<rect width="{}" height="{}" fill="white"/>"#,
-            total_size, total_size
-        ));
-
-        // Black modules
-        for row in 0..size {
-            for col in 0..size {
-                if self.modules[row][col] {
-                    let x = (col + quiet_zone) * module_size as usize;
-                    let y = (row + quiet_zone) * module_size as usize;
-                    svg.push_str(&format!(
-                        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="black"/>"#,
-                        x, y, module_size, module_size
-                    ));
-                }
-            }
-        }
-
-        svg.push_str("</svg>");
-        svg
+        self.render().module_dimensions(module_size, module_size).to_svg()
     }
 
     /// Render the QR code as a PNG image.
@@ -1042,6 +1824,7 @@ impl QrCode {
     ///
     /// # Arguments
     /// * `module_size` - Size of each module in pixels
+    /// * `quiet_zone` - Border width in modules (4 is the spec-recommended minimum)
     ///
     /// # Example
     /// ```
@@ -1049,49 +1832,16 @@ impl QrCode {
     /// # {
     /// use qr::{QrCode, ErrorCorrectionLevel};
     /// let qr = QrCode::encode("Hello", ErrorCorrectionLevel::M).unwrap();
-    /// let png_data = qr.to_png(10);
+    /// let png_data = qr.to_png(10, 4);
     /// // std::fs::write("qr.png", png_data).unwrap();
     /// # }
     /// ```
     #[cfg(feature = "png")]
-    pub fn to_png(&self, module_size: u32) -> Vec<u8> {
-        let size = self.size();
-        let quiet_zone = 4usize; // Standard quiet zone is 4 modules
-        let total_size = (size + 2 * quiet_zone) * module_size as usize;
-
-        // Create grayscale image buffer (0 = black, 255 = white)
-        let mut pixels = vec![255u8; total_size * total_size];
-
-        // Draw black modules
-        for row in 0..size {
-            for col in 0..size {
-                if self.modules[row][col] {
-                    let px = (col + quiet_zone) * module_size as usize;
-                    let py = (row + quiet_zone) * module_size as usize;
-
-                    // Fill the module area with black pixels
-                    for dy in 0..module_size as usize {
-                        for dx in 0..module_size as usize {
-                            let idx = (py + dy) * total_size + (px + dx);
-                            pixels[idx] = 0;
-                        }
-                    }
-                }
-            }
-        }
-
-        // Encode as PNG
-        let mut png_data = Vec::new();
-        {
-            let mut encoder =
-                png::Encoder::new(&mut png_data, total_size as u32, total_size as u32);
-            encoder.set_color(png::ColorType::Grayscale);
-            encoder.set_depth(png::BitDepth::Eight);
-            let mut writer = encoder.write_header().expect("PNG header write failed");
-            writer.write_image_data(&pixels).expect("PNG data write failed");
-        }
-
-        png_data
+    pub fn to_png(&self, module_size: u32, quiet_zone: usize) -> Vec<u8> {
+        self.render()
+            .quiet_zone(quiet_zone)
+            .module_dimensions(module_size, module_size)
+            .to_png()
     }
 
     /// Render the QR code as ASCII art for terminal display.
@@ -1102,66 +1852,30 @@ impl QrCode {
     ///
     /// Each module is represented by 2 characters wide for better aspect ratio.
     pub fn to_ascii(&self) -> String {
-        let size = self.size();
-        let quiet_zone = 2; // Smaller quiet zone for terminal
-        let mut result = String::new();
-
-        // Top quiet zone
-        for _ in 0..quiet_zone {
-            for _ in 0..(size + 2 * quiet_zone) * 2 {
-                result.push(' ');
-            }
-            result.push('\n');
-        }
-
-        // QR code rows
-        for row in 0..size {
-            // Left quiet zone
-            for _ in 0..quiet_zone * 2 {
-                result.push(' ');
-            }
-
-            // Modules
-            for col in 0..size {
-                if self.modules[row][col] {
-                    // Black module: use full block characters
-                    result.push_str("\u{2588}\u{2588}");
-                } else {
-                    // White module: use spaces
-                    result.push_str("  ");
-                }
-            }
-
-            // Right quiet zone
-            for _ in 0..quiet_zone * 2 {
-                result.push(' ');
-            }
-            result.push('\n');
-        }
-
-        // Bottom quiet zone
-        for _ in 0..quiet_zone {
-            for _ in 0..(size + 2 * quiet_zone) * 2 {
-                result.push(' ');
-            }
-            result.push('\n');
-        }
-
-        result
+        self.render().quiet_zone(2).module_dimensions(2, 1).to_ascii()
     }
 
     /// Render the QR code as compact ASCII using half-block characters.
     ///
-    /// Uses Unicode half-block characters to display 2 rows per line:
-    /// - Upper half block for top black, bottom white
-    /// - Lower half block for top white, bottom black
-    /// - Full block for both black
-    /// - Space for both white
-    ///
-    /// This produces a more compact output with better proportions.
+    /// Retained as an alias of `to_unicode` with the original default
+    /// quiet zone (2 modules) for source compatibility.
     pub fn to_ascii_compact(&self) -> String {
+        self.to_unicode(2)
+    }
+
+    /// Render the QR code using Unicode half-block characters, packing
+    /// two vertical modules into each character cell:
+    /// - Full block (`█`) for both modules dark
+    /// - Upper half block (`▀`) for top dark, bottom light
+    /// - Lower half block (`▄`) for top light, bottom dark
+    /// - Space for both light
+    ///
+    /// This halves the output's height versus `to_ascii` (which uses one
+    /// line per module row), giving a correctly-proportioned image
+    /// in terminals where character cells are roughly twice as tall as
+    /// wide. `quiet_zone` sets the border width in modules.
+    pub fn to_unicode(&self, quiet_zone: usize) -> String {
         let size = self.size();
-        let quiet_zone = 2;
         let mut result = String::new();
 
         // Process rows in pairs
@@ -1314,136 +2028,2115 @@ impl QrCode {
         let ecl_idx = ecl as usize;
         params[idx][ecl_idx]
     }
-}
 
-/// Bit buffer for accumulating bits before converting to bytes.
-struct BitBuffer {
+    // ------------------------------------------------------------------
+    // Micro QR Code (M1-M4), ISO/IEC 18004:2015 Annex.
+    //
+    // Micro QR symbols are a compact variant of the standard symbol: a
+    // single finder pattern instead of three, a single-block (never
+    // split) error-correction codeword layout, shorter mode indicators
+    // (0-3 bits), narrower character-count fields, and only 4 mask
+    // patterns chosen by a different (maximized rather than minimized)
+    // penalty rule. They reuse `GF256`, `reed_solomon_generator`, and
+    // `reed_solomon_encode` from the full-size pipeline.
+    //
+    // Known simplifications versus the full spec (in the spirit of the
+    // `get_ec_params` table's own "simplified... needs complete table"
+    // note above):
+    // - M1 is not generated. Its final codeword is 4 bits wide (a GF(16)
+    //   Reed-Solomon code), while this crate's EC machinery is byte
+    //   (GF(256)) oriented throughout.
+    // - Every payload is encoded as a single mode segment (no mixed-mode
+    //   segmentation within one Micro symbol).
+    // - Data-codeword capacities below are approximate, rounded to whole
+    //   bytes; consult ISO/IEC 18004 Table 7 for the exact bit-level
+    //   layout.
+    // ------------------------------------------------------------------
+
+    /// Generate a Micro QR Code (M2-M4) for small numeric/alphanumeric/byte
+    /// payloads. See the "Micro QR Code" section above for the
+    /// simplifications this implementation makes relative to the full
+    /// ISO/IEC 18004 spec (notably: M1 is unsupported).
+    /// M1 is intentionally out of scope: every other Micro version (and
+    /// every full-size version) pads its data to a whole number of 8-bit
+    /// codewords, which is what `BitBuffer::to_bytes`, the Reed-Solomon
+    /// encoder, and the data-placement walk all assume. M1's final
+    /// codeword is only 4 bits wide, so supporting it would mean
+    /// special-casing that byte-oriented pipeline for a single version
+    /// that only ever carries up to 5 numeric digits - not worth it for
+    /// this crate's use case of encoding addresses and amounts.
+    pub fn encode_micro(data: &str, ecl: ErrorCorrectionLevel) -> Result<Self, String> {
+        let bytes = data.as_bytes();
+        let mode = Self::micro_select_mode(bytes);
+
+        let (micro_version, data_codewords) = (2u8..=4)
+            .find_map(|v| Self::encode_micro_data(bytes, v, ecl, mode).ok().map(|cw| (v, cw)))
+            .ok_or_else(|| {
+                "Data too large for any supported Micro QR version/EC level \
+                 (M1 is unsupported by this implementation)"
+                    .to_string()
+            })?;
+
+        let size = 2 * micro_version as usize + 9;
+        let ec_codewords = Self::micro_ec_codewords(micro_version, ecl)
+            .expect("encode_micro_data already validated this version/ECL combination");
+        let all_codewords = Self::add_error_correction_micro(&data_codewords, ec_codewords);
+
+        let mut qr = Self {
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+            version: micro_version,
+            error_correction: ecl,
+            mask: 0,
+            kind: SymbolKind::Micro,
+        };
+
+        qr.place_micro_function_patterns();
+        qr.place_data_bits(&all_codewords);
+        qr.apply_best_micro_mask();
+        qr.place_micro_format_info();
+
+        Ok(qr)
+    }
+
+    /// Pick the single mode that covers all of `data`: Numeric if every
+    /// byte is a digit, Alphanumeric if every byte is in the QR
+    /// alphanumeric set, otherwise Byte.
+    fn micro_select_mode(data: &[u8]) -> Mode {
+        if data.iter().all(|&b| b.is_ascii_digit()) {
+            Mode::Numeric
+        } else if data.iter().all(|&b| alphanumeric_value(b).is_some()) {
+            Mode::Alphanumeric
+        } else {
+            Mode::Byte
+        }
+    }
+
+    /// Mode indicator width (bits) at Micro version `micro_version`
+    /// (1=M1..4=M4). Per ISO/IEC 18004 Table 2, M1 has no mode indicator
+    /// at all (it's implicitly Numeric).
+    fn micro_mode_indicator_bits(micro_version: u8) -> u32 {
+        (micro_version - 1) as u32
+    }
+
+    /// The mode indicator's value at `micro_version`, or an error if
+    /// `mode` isn't available at that version (M1-M3 don't support Byte).
+    fn micro_mode_indicator_value(mode: Mode, micro_version: u8) -> Result<u32, String> {
+        match (micro_version, mode) {
+            (1, Mode::Numeric) => Ok(0),
+            (2, Mode::Numeric) => Ok(0b0),
+            (2, Mode::Alphanumeric) => Ok(0b1),
+            (3, Mode::Numeric) => Ok(0b00),
+            (3, Mode::Alphanumeric) => Ok(0b01),
+            (4, Mode::Numeric) => Ok(0b000),
+            (4, Mode::Alphanumeric) => Ok(0b001),
+            (4, Mode::Byte) => Ok(0b010),
+            _ => Err(format!(
+                "{:?} mode is not available at Micro QR version M{}",
+                mode, micro_version
+            )),
+        }
+    }
+
+    /// Character-count-indicator width (bits) per ISO/IEC 18004 Table 3
+    /// for Micro QR symbols (narrower than the full-size table since
+    /// these symbols hold far less data).
+    fn micro_count_bits(mode: Mode, micro_version: u8) -> u32 {
+        match (micro_version, mode) {
+            (1, Mode::Numeric) => 3,
+            (2, Mode::Numeric) => 4,
+            (2, Mode::Alphanumeric) => 3,
+            (3, Mode::Numeric) => 5,
+            (3, Mode::Alphanumeric) => 4,
+            (4, Mode::Numeric) => 6,
+            (4, Mode::Alphanumeric) => 5,
+            (4, Mode::Byte) => 4,
+            _ => unreachable!("unsupported mode/version combination"),
+        }
+    }
+
+    /// Approximate data-codeword capacity for (Micro version, EC level),
+    /// or `None` if that combination doesn't exist (M1 only offers the
+    /// single "Detection" level; M2/M3 don't offer Q/H; M4 doesn't offer
+    /// H). See the simplification note above the Micro QR section.
+    fn micro_data_codewords(micro_version: u8, ecl: ErrorCorrectionLevel) -> Option<usize> {
+        match (micro_version, ecl) {
+            (2, ErrorCorrectionLevel::L) => Some(5),
+            (2, ErrorCorrectionLevel::M) => Some(4),
+            (3, ErrorCorrectionLevel::L) => Some(11),
+            (3, ErrorCorrectionLevel::M) => Some(9),
+            (4, ErrorCorrectionLevel::L) => Some(16),
+            (4, ErrorCorrectionLevel::M) => Some(14),
+            (4, ErrorCorrectionLevel::Q) => Some(10),
+            _ => None,
+        }
+    }
+
+    /// Approximate EC-codeword count for (Micro version, EC level); see
+    /// `micro_data_codewords`.
+    fn micro_ec_codewords(micro_version: u8, ecl: ErrorCorrectionLevel) -> Option<usize> {
+        match (micro_version, ecl) {
+            (2, ErrorCorrectionLevel::L) => Some(5),
+            (2, ErrorCorrectionLevel::M) => Some(6),
+            (3, ErrorCorrectionLevel::L) => Some(6),
+            (3, ErrorCorrectionLevel::M) => Some(8),
+            (4, ErrorCorrectionLevel::L) => Some(8),
+            (4, ErrorCorrectionLevel::M) => Some(10),
+            (4, ErrorCorrectionLevel::Q) => Some(14),
+            _ => None,
+        }
+    }
+
+    /// Encode `data` as a single mode segment for Micro version
+    /// `micro_version`, returning padded data codewords, or an error if
+    /// `mode` isn't available at that version or the data doesn't fit.
+    fn encode_micro_data(
+        data: &[u8],
+        micro_version: u8,
+        ecl: ErrorCorrectionLevel,
+        mode: Mode,
+    ) -> Result<Vec<u8>, String> {
+        let data_codewords = Self::micro_data_codewords(micro_version, ecl).ok_or_else(|| {
+            format!(
+                "Micro QR M{} does not support EC level {:?}",
+                micro_version, ecl
+            )
+        })?;
+
+        let mut bits = BitBuffer::new();
+        let mode_bits = Self::micro_mode_indicator_bits(micro_version);
+        if mode_bits > 0 {
+            let value = Self::micro_mode_indicator_value(mode, micro_version)?;
+            bits.append_bits(value, mode_bits as usize);
+        } else if mode != Mode::Numeric {
+            return Err("M1 only supports Numeric mode".to_string());
+        }
+
+        let count_bits = Self::micro_count_bits(mode, micro_version);
+        bits.append_bits(data.len() as u32, count_bits as usize);
+        match mode {
+            Mode::Numeric => Self::append_numeric_segment(&mut bits, data),
+            Mode::Alphanumeric => Self::append_alphanumeric_segment(&mut bits, data),
+            Mode::Byte => {
+                for &byte in data {
+                    bits.append_bits(byte as u32, 8);
+                }
+            }
+            // Micro QR doesn't support Kanji mode in this implementation.
+            Mode::Kanji => unreachable!("micro_select_mode never selects Kanji mode"),
+        }
+
+        let capacity_bits = data_codewords * 8;
+        if bits.len() > capacity_bits {
+            return Err("Data too large for this Micro QR version/EC level".to_string());
+        }
+
+        let terminator_len = std::cmp::min(4, capacity_bits.saturating_sub(bits.len()));
+        bits.append_bits(0, terminator_len);
+        while !bits.len().is_multiple_of(8) {
+            bits.append_bits(0, 1);
+        }
+
+        let mut codewords = bits.to_bytes();
+        let mut pad_toggle = true;
+        while codewords.len() < data_codewords {
+            codewords.push(if pad_toggle { 0xEC } else { 0x11 });
+            pad_toggle = !pad_toggle;
+        }
+
+        Ok(codewords)
+    }
+
+    /// Add Reed-Solomon error correction for a Micro QR symbol. Unlike
+    /// `add_error_correction`, Micro symbols never split data into
+    /// multiple blocks, so this is a single generator/encode pass with no
+    /// interleaving.
+    fn add_error_correction_micro(data: &[u8], ec_codewords: usize) -> Vec<u8> {
+        let generator = Self::reed_solomon_generator(ec_codewords);
+        let ec = Self::reed_solomon_encode(data, &generator, ec_codewords);
+        let mut result = data.to_vec();
+        result.extend(ec);
+        result
+    }
+
+    /// Place the single finder pattern, timing patterns, and reserved
+    /// format-info area for a Micro QR symbol.
+    fn place_micro_function_patterns(&mut self) {
+        let size = self.modules.len();
+
+        self.place_finder_pattern(0, 0);
+
+        // Timing patterns run from just past the finder+separator all the
+        // way to the far edge (there's no second finder to stop short of).
+        for i in 8..size {
+            if !self.is_function[6][i] {
+                self.modules[6][i] = i % 2 == 0;
+                self.is_function[6][i] = true;
+            }
+            if !self.is_function[i][6] {
+                self.modules[i][6] = i % 2 == 0;
+                self.is_function[i][6] = true;
+            }
+        }
+
+        // Reserve the format-info area along row 8 and column 8.
+        for i in 1..=7 {
+            self.is_function[i][8] = true;
+        }
+        for i in 1..=8 {
+            self.is_function[8][i] = true;
+        }
+    }
+
+    /// Evaluate a mask for a Micro QR symbol using the spec's edge-based
+    /// rule: count dark modules along the right-most column and
+    /// bottom-most row (the two edges opposite the single finder
+    /// pattern), then combine as `16 * max(sum1, sum2) + min(sum1, sum2)`.
+    fn calculate_micro_penalty(&self) -> u32 {
+        let size = self.modules.len();
+        let sum1 = (0..size).filter(|&row| self.modules[row][size - 1]).count() as u32;
+        let sum2 = (0..size).filter(|&col| self.modules[size - 1][col]).count() as u32;
+        16 * sum1.max(sum2) + sum1.min(sum2)
+    }
+
+    /// The 4 mask reference patterns available to Micro QR symbols are,
+    /// per ISO/IEC 18004 Annex, the same formulas as full-size patterns
+    /// 1, 4, 6, and 7 - just renumbered 0-3 for the (narrower) Micro
+    /// format-info field.
+    const MICRO_MASK_PATTERNS: [u8; 4] = [1, 4, 6, 7];
+
+    /// Try all 4 Micro mask patterns and keep the one with the *highest*
+    /// edge penalty score (unlike full QR, which minimizes its 4-rule
+    /// penalty - see `calculate_micro_penalty`).
+    fn apply_best_micro_mask(&mut self) {
+        let mut best_mask = 0u8;
+        let mut best_score = None;
+
+        for (micro_idx, &full_idx) in Self::MICRO_MASK_PATTERNS.iter().enumerate() {
+            self.apply_mask(full_idx);
+            let score = self.calculate_micro_penalty();
+            if best_score.is_none_or(|best| score > best) {
+                best_score = Some(score);
+                best_mask = micro_idx as u8;
+            }
+            // Undo mask to try next
+            self.apply_mask(full_idx);
+        }
+
+        self.mask = best_mask;
+        self.apply_mask(Self::MICRO_MASK_PATTERNS[best_mask as usize]);
+    }
+
+    /// Place the 15-bit BCH(15,5) format info for a Micro QR symbol along
+    /// column 8 (rows 1-7) and row 8 (columns 8 down to 1).
+    ///
+    /// The 5-bit format data is `[symbol number (3 bits)][mask (2 bits)]`,
+    /// where the symbol number jointly encodes the Micro version and EC
+    /// level per ISO/IEC 18004 Table 10 (0=M1, 1=M2-L, 2=M2-M, 3=M3-L,
+    /// 4=M3-M, 5=M4-L, 6=M4-M, 7=M4-Q).
+    fn place_micro_format_info(&mut self) {
+        let symbol_number = match (self.version, self.error_correction) {
+            (1, ErrorCorrectionLevel::L) => 0,
+            (2, ErrorCorrectionLevel::L) => 1,
+            (2, ErrorCorrectionLevel::M) => 2,
+            (3, ErrorCorrectionLevel::L) => 3,
+            (3, ErrorCorrectionLevel::M) => 4,
+            (4, ErrorCorrectionLevel::L) => 5,
+            (4, ErrorCorrectionLevel::M) => 6,
+            (4, ErrorCorrectionLevel::Q) => 7,
+            _ => unreachable!("encode_micro only builds supported version/ECL combinations"),
+        };
+        let data = (symbol_number << 2) | (self.mask as u32);
+        let format_bits = Self::calculate_format_bits_with_mask(data, Self::MICRO_FORMAT_XOR);
+
+        for i in 1..=7 {
+            self.modules[i][8] = (format_bits >> (i - 1)) & 1 == 1;
+        }
+        for i in 1..=8 {
+            self.modules[8][9 - i] = (format_bits >> (i + 6)) & 1 == 1;
+        }
+    }
+
+    /// XOR mask applied to Micro QR format info, distinct from the
+    /// full-size symbol's mask (ISO/IEC 18004 Annex C).
+    const MICRO_FORMAT_XOR: u32 = 0b100010001000101;
+
+    /// Calculate BCH(15,5) format bits with a caller-supplied XOR mask
+    /// (full-size and Micro QR symbols use the same generator polynomial
+    /// but a different XOR mask - see `calculate_format_bits`).
+    fn calculate_format_bits_with_mask(data: u32, xor_mask: u32) -> u32 {
+        let mut bits = data << 10;
+        let generator = 0b10100110111; // BCH generator polynomial
+
+        for i in (0..=4).rev() {
+            if (bits >> (i + 10)) & 1 == 1 {
+                bits ^= generator << i;
+            }
+        }
+
+        let format = (data << 10) | bits;
+        format ^ xor_mask
+    }
+
+    // ------------------------------------------------------------------
+    // Decoding: reverses the full-size encoding pipeline above, giving the
+    // crate a built-in round-trip self-check. Not implemented for Micro
+    // QR symbols.
+    // ------------------------------------------------------------------
+
+    /// Decode a full-size QR Code's module matrix back into its original
+    /// string, reversing `encode`/`encode_advanced` end to end: verify the
+    /// three finder patterns and derive the version from the matrix size,
+    /// BCH-correct the format info to recover the EC level and mask,
+    /// un-mask and walk the zigzag data placement in reverse to recover
+    /// interleaved codewords, de-interleave into data/EC blocks,
+    /// Reed-Solomon-correct each block, then parse the corrected data
+    /// codewords back into mode/count/payload segments.
+    ///
+    /// Micro QR matrices (`encode_micro`'s output) are rejected: they have
+    /// a single finder pattern, a different format-info field and XOR
+    /// mask, and their own mode-indicator/character-count widths, none of
+    /// which this reverses.
+    pub fn decode(modules: &[Vec<bool>]) -> Result<String, String> {
+        let size = modules.len();
+        if size < 21 || size > 177 || (size - 17) % 4 != 0 {
+            return Err("Matrix size is not a valid full-size QR Code".to_string());
+        }
+        if modules.iter().any(|row| row.len() != size) {
+            return Err("Matrix is not square".to_string());
+        }
+        let version = ((size - 17) / 4) as u8;
+
+        Self::verify_finder_pattern(modules, 0, 0)?;
+        Self::verify_finder_pattern(modules, size - 7, 0)?;
+        Self::verify_finder_pattern(modules, 0, size - 7)?;
+
+        let (ecl, mask) = Self::decode_format_info(modules)?;
+        let codewords = Self::read_codewords(modules, version, mask);
+        let data_codewords = Self::rs_decode_blocks(&codewords, version, ecl)?;
+
+        Self::parse_data_codewords(&data_codewords, version)
+    }
+
+    /// Check that a 7x7 finder pattern sits at `(row, col)`, per the same
+    /// shape `place_finder_pattern` draws. The separator border around it
+    /// is not checked.
+    fn verify_finder_pattern(modules: &[Vec<bool>], row: usize, col: usize) -> Result<(), String> {
+        for dr in 0..7 {
+            for dc in 0..7 {
+                let is_edge = dr == 0 || dr == 6 || dc == 0 || dc == 6;
+                let is_center = (2..=4).contains(&dr) && (2..=4).contains(&dc);
+                let expected = is_edge || is_center;
+                if modules[row + dr][col + dc] != expected {
+                    return Err(format!(
+                        "Finder pattern mismatch at ({}, {})",
+                        row + dr,
+                        col + dc
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the 15 format-info modules around the top-left finder pattern
+    /// (the same positions `place_format_info` writes) and BCH-correct
+    /// them to recover the EC level and mask pattern.
+    fn decode_format_info(modules: &[Vec<bool>]) -> Result<(ErrorCorrectionLevel, u8), String> {
+        let mut raw = 0u32;
+        for i in 0..6 {
+            if modules[8][i] {
+                raw |= 1 << i;
+            }
+            if modules[5 - i][8] {
+                raw |= 1 << (i + 9);
+            }
+        }
+        if modules[8][7] {
+            raw |= 1 << 6;
+        }
+        if modules[8][8] {
+            raw |= 1 << 7;
+        }
+        if modules[7][8] {
+            raw |= 1 << 8;
+        }
+
+        let data = Self::correct_format_bits(raw)?;
+        let ecl = match (data >> 3) & 0b11 {
+            0b01 => ErrorCorrectionLevel::L,
+            0b00 => ErrorCorrectionLevel::M,
+            0b11 => ErrorCorrectionLevel::Q,
+            0b10 => ErrorCorrectionLevel::H,
+            _ => unreachable!("2-bit field"),
+        };
+        let mask = (data & 0b111) as u8;
+        Ok((ecl, mask))
+    }
+
+    /// Brute-force BCH(15,5) correction: try all 32 possible 5-bit data
+    /// values, re-encode each to its 15-bit codeword via
+    /// `calculate_format_bits`, and return whichever data value's
+    /// codeword is closest (by Hamming distance) to `received` - this
+    /// recovers the original format info even if a few modules were
+    /// misread, the same guarantee the BCH(15,5) code is designed to give.
+    fn correct_format_bits(received: u32) -> Result<u32, String> {
+        let mut best_data = 0u32;
+        let mut best_distance = u32::MAX;
+        for data in 0..32u32 {
+            let candidate = Self::calculate_format_bits(data);
+            let distance = (candidate ^ received).count_ones();
+            if distance < best_distance {
+                best_distance = distance;
+                best_data = data;
+            }
+        }
+        if best_distance > 3 {
+            return Err("Format info has too many errors to correct".to_string());
+        }
+        Ok(best_data)
+    }
+
+    /// Rebuild a version's function-pattern mask (by placing them on a
+    /// blank symbol via `place_function_patterns`), then walk the exact
+    /// same zigzag order `place_data_bits` writes to read the data region
+    /// back out, undoing `mask` as we go.
+    fn read_codewords(modules: &[Vec<bool>], version: u8, mask: u8) -> Vec<u8> {
+        let size = modules.len();
+        let mut skeleton = Self {
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+            version,
+            error_correction: ErrorCorrectionLevel::L,
+            mask: 0,
+            kind: SymbolKind::Full,
+        };
+        skeleton.place_function_patterns();
+        let is_function = skeleton.is_function;
+
+        let mut bits: Vec<bool> = Vec::new();
+        let mut col = size - 1;
+        let mut going_up = true;
+
+        while col > 0 {
+            if col == 6 {
+                col -= 1;
+            }
+
+            let rows: Vec<usize> = if going_up {
+                (0..size).rev().collect()
+            } else {
+                (0..size).collect()
+            };
+
+            for row in rows {
+                for dc in 0..2 {
+                    let c = col - dc;
+                    if c < size && !is_function[row][c] {
+                        bits.push(modules[row][c] ^ Self::mask_bit(mask, row, c));
+                    }
+                }
+            }
+
+            going_up = !going_up;
+            col = col.saturating_sub(2);
+        }
+
+        BitBuffer { bits }.to_bytes()
+    }
+
+    /// De-interleave `codewords` into its data/EC blocks (reversing the
+    /// interleaving `add_error_correction` performs) and Reed-Solomon
+    /// correct each block, returning the corrected data codewords (EC
+    /// codewords discarded).
+    fn rs_decode_blocks(
+        codewords: &[u8],
+        version: u8,
+        ecl: ErrorCorrectionLevel,
+    ) -> Result<Vec<u8>, String> {
+        let (num_blocks, ec_per_block) = Self::get_ec_params(version, ecl);
+        let data_codewords = Self::get_data_codewords(version, ecl);
+        let short_block_len = data_codewords / num_blocks;
+        let long_blocks = data_codewords % num_blocks;
+
+        let block_lens: Vec<usize> = (0..num_blocks)
+            .map(|i| short_block_len + usize::from(i >= num_blocks - long_blocks))
+            .collect();
+
+        let mut data_blocks: Vec<Vec<u8>> =
+            block_lens.iter().map(|&len| Vec::with_capacity(len)).collect();
+        let mut idx = 0;
+        let max_data_len = short_block_len + 1;
+        for i in 0..max_data_len {
+            for (b, &len) in block_lens.iter().enumerate() {
+                if i < len {
+                    data_blocks[b].push(codewords[idx]);
+                    idx += 1;
+                }
+            }
+        }
+
+        let mut ec_blocks: Vec<Vec<u8>> = vec![Vec::with_capacity(ec_per_block); num_blocks];
+        for _ in 0..ec_per_block {
+            for block in &mut ec_blocks {
+                block.push(codewords[idx]);
+                idx += 1;
+            }
+        }
+
+        let mut result = Vec::with_capacity(data_codewords);
+        for b in 0..num_blocks {
+            let mut block = data_blocks[b].clone();
+            block.extend_from_slice(&ec_blocks[b]);
+            let corrected = Self::reed_solomon_correct(&block, ec_per_block)?;
+            result.extend_from_slice(&corrected[..data_blocks[b].len()]);
+        }
+        Ok(result)
+    }
+
+    /// Reed-Solomon-correct one interleaved block (data codewords
+    /// followed by its EC codewords): compute syndromes, run
+    /// Berlekamp-Massey to find the error-locator polynomial, Chien
+    /// search for its roots (the error positions), and Forney's formula
+    /// for the error magnitudes. Can fix up to `ec_count/2` byte errors.
+    fn reed_solomon_correct(block: &[u8], ec_count: usize) -> Result<Vec<u8>, String> {
+        let n = block.len();
+
+        // Syndromes S_0..S_(ec_count-1): S_i = block(alpha^i), treating
+        // `block` as a polynomial with block[0] as the highest-degree
+        // coefficient (the same convention `reed_solomon_encode` uses).
+        // `reed_solomon_generator`'s roots are alpha^0..alpha^(ec_count-1),
+        // so the syndromes are evaluated at the same powers.
+        let syndromes: Vec<u8> = (0..ec_count)
+            .map(|i| Self::poly_eval_gf(block, GF256::exp(i as u8)))
+            .collect();
+
+        if syndromes.iter().all(|&s| s == 0) {
+            return Ok(block.to_vec());
+        }
+
+        let locator_full = Self::berlekamp_massey(&syndromes);
+        let degree = locator_full.iter().rposition(|&c| c != 0).unwrap_or(0);
+        let locator = &locator_full[..=degree];
+
+        let error_positions = Self::chien_search(locator, n);
+        if error_positions.len() != degree {
+            return Err("Reed-Solomon decode failed: too many errors to correct".to_string());
+        }
+
+        // Error evaluator Omega(x) = S(x)*sigma(x) mod x^ec_count,
+        // where S(x) = S_0 + S_1 x + S_2 x^2 + ... (ascending degree).
+        let omega = Self::poly_mul_truncated(&syndromes, locator, ec_count - 1);
+        let sigma_deriv = Self::poly_derivative(locator);
+
+        let mut corrected = block.to_vec();
+        for &k in &error_positions {
+            // Forney: since the generator's roots start at alpha^0 (not
+            // alpha^1), the error magnitude carries an extra factor of
+            // X_k: magnitude = X_k * Omega(X_k^-1) / sigma'(X_k^-1),
+            // where X_k = alpha^(n-1-k) is position k's error locator value.
+            let exponent = (n - 1 - k) as u32 % 255;
+            let inv_exponent = (255 - exponent) % 255;
+            let x_k = GF256::exp(exponent as u8);
+            let x_inv = GF256::exp(inv_exponent as u8);
+
+            let omega_val = Self::poly_eval_ascending(&omega, x_inv);
+            let deriv_val = Self::poly_eval_ascending(&sigma_deriv, x_inv);
+            if deriv_val == 0 {
+                return Err(
+                    "Reed-Solomon decode failed: zero error-locator derivative".to_string(),
+                );
+            }
+            let magnitude = GF256::mul(GF256::mul(x_k, omega_val), GF256::inv(deriv_val));
+            corrected[k] ^= magnitude;
+        }
+
+        let verify: Vec<u8> = (0..ec_count)
+            .map(|i| Self::poly_eval_gf(&corrected, GF256::exp(i as u8)))
+            .collect();
+        if verify.iter().any(|&s| s != 0) {
+            return Err(
+                "Reed-Solomon decode failed: correction did not resolve all syndromes".to_string(),
+            );
+        }
+
+        Ok(corrected)
+    }
+
+    /// Evaluate `coeffs` (descending degree, `coeffs[0]` highest) at `x`
+    /// via Horner's method.
+    fn poly_eval_gf(coeffs: &[u8], x: u8) -> u8 {
+        let mut result = 0u8;
+        for &c in coeffs {
+            result = GF256::mul(result, x) ^ c;
+        }
+        result
+    }
+
+    /// Evaluate `coeffs` (ascending degree, `coeffs[0]` the constant term)
+    /// at `x` via Horner's method.
+    fn poly_eval_ascending(coeffs: &[u8], x: u8) -> u8 {
+        let mut result = 0u8;
+        for &c in coeffs.iter().rev() {
+            result = GF256::mul(result, x) ^ c;
+        }
+        result
+    }
+
+    /// Multiply two ascending-degree polynomials over GF(2^8), keeping
+    /// only terms up to and including `x^max_degree`.
+    fn poly_mul_truncated(a: &[u8], b: &[u8], max_degree: usize) -> Vec<u8> {
+        let mut result = vec![0u8; max_degree + 1];
+        for (i, &ai) in a.iter().enumerate() {
+            if ai == 0 {
+                continue;
+            }
+            for (j, &bj) in b.iter().enumerate() {
+                if i + j > max_degree {
+                    break;
+                }
+                result[i + j] ^= GF256::mul(ai, bj);
+            }
+        }
+        result
+    }
+
+    /// Formal derivative of an ascending-degree polynomial over GF(2):
+    /// only odd-degree terms survive (their coefficient multiplier is
+    /// odd), each shifted down one degree.
+    fn poly_derivative(coeffs: &[u8]) -> Vec<u8> {
+        // In characteristic 2, i*c_i vanishes for even i, so only odd-i
+        // terms survive - each landing at degree i-1, an even number.
+        // Degrees are not consecutive (every other one is an implicit
+        // zero), so the result must keep those gaps rather than packing
+        // the surviving coefficients next to each other.
+        if coeffs.len() <= 1 {
+            return Vec::new();
+        }
+        let mut result = vec![0u8; coeffs.len() - 1];
+        for (i, &c) in coeffs.iter().enumerate().skip(1).step_by(2) {
+            result[i - 1] = c;
+        }
+        result
+    }
+
+    /// Berlekamp-Massey: find the shortest LFSR (the error-locator
+    /// polynomial, ascending degree, constant term 1) that generates the
+    /// syndrome sequence `syndromes`.
+    fn berlekamp_massey(syndromes: &[u8]) -> Vec<u8> {
+        let n = syndromes.len();
+        let mut c = vec![1u8];
+        let mut b = vec![1u8];
+        let mut l = 0usize;
+        let mut m = 1usize;
+        let mut b_coef = 1u8;
+
+        for i in 0..n {
+            let mut discrepancy = syndromes[i];
+            for j in 1..=l {
+                if j < c.len() {
+                    discrepancy ^= GF256::mul(c[j], syndromes[i - j]);
+                }
+            }
+
+            if discrepancy == 0 {
+                m += 1;
+                continue;
+            }
+
+            // Save the pre-update locator: if we grow `l` below, it becomes
+            // the new `b` (the previous best-fitting shorter LFSR).
+            let prev_c = c.clone();
+
+            let scale = GF256::mul(discrepancy, GF256::inv(b_coef));
+            while c.len() < b.len() + m {
+                c.push(0);
+            }
+            for (k, &bc) in b.iter().enumerate() {
+                c[k + m] ^= GF256::mul(scale, bc);
+            }
+
+            if 2 * l <= i {
+                l = i + 1 - l;
+                b = prev_c;
+                b_coef = discrepancy;
+                m = 1;
+            } else {
+                m += 1;
+            }
+        }
+
+        c
+    }
+
+    /// Chien search: test every position `k` in a block of length `n` as
+    /// a candidate error location by evaluating `locator` at that
+    /// position's inverse locator value `X_k^-1`.
+    fn chien_search(locator: &[u8], n: usize) -> Vec<usize> {
+        let mut positions = Vec::new();
+        for k in 0..n {
+            let exponent = (n - 1 - k) as u32 % 255;
+            let inv_exponent = (255 - exponent) % 255;
+            let x_inv = GF256::exp(inv_exponent as u8);
+            if Self::poly_eval_ascending(locator, x_inv) == 0 {
+                positions.push(k);
+            }
+        }
+        positions
+    }
+
+    /// Parse corrected data codewords back into segments (mode indicator,
+    /// character count, packed data) and concatenate their decoded text,
+    /// reversing `encode_data`/`segment_data`.
+    fn parse_data_codewords(data: &[u8], version: u8) -> Result<String, String> {
+        let mut bits: Vec<bool> = Vec::with_capacity(data.len() * 8);
+        for &byte in data {
+            for i in (0..8).rev() {
+                bits.push((byte >> i) & 1 == 1);
+            }
+        }
+
+        let mut pos = 0usize;
+        let mut out: Vec<u8> = Vec::new();
+
+        while pos + 4 <= bits.len() {
+            let mode_bits = Self::read_bits(&bits, &mut pos, 4);
+            if mode_bits == 0 {
+                break; // Terminator.
+            }
+            if mode_bits == Self::ECI_MODE_INDICATOR {
+                // Consume and discard the designator: this crate always
+                // decodes to a Rust `String`, so there's no non-UTF-8
+                // charset to switch into - the subsequent byte segment is
+                // still interpreted as UTF-8 regardless of which ECI was
+                // declared.
+                Self::skip_eci_designator(&bits, &mut pos)?;
+                continue;
+            }
+            let mode = match mode_bits {
+                0b0001 => Mode::Numeric,
+                0b0010 => Mode::Alphanumeric,
+                0b0100 => Mode::Byte,
+                other => return Err(format!("Unsupported mode indicator {:04b}", other)),
+            };
+
+            let count_bits = Self::mode_count_bits(mode, version) as usize;
+            if pos + count_bits > bits.len() {
+                return Err("Truncated character count".to_string());
+            }
+            let mut remaining = Self::read_bits(&bits, &mut pos, count_bits) as usize;
+
+            match mode {
+                Mode::Numeric => {
+                    while remaining > 0 {
+                        let digits = remaining.min(3);
+                        let group_bits = match digits {
+                            3 => 10,
+                            2 => 7,
+                            _ => 4,
+                        };
+                        if pos + group_bits > bits.len() {
+                            return Err("Truncated numeric data".to_string());
+                        }
+                        let value = Self::read_bits(&bits, &mut pos, group_bits);
+                        out.extend(format!("{:0width$}", value, width = digits).into_bytes());
+                        remaining -= digits;
+                    }
+                }
+                Mode::Alphanumeric => {
+                    while remaining > 0 {
+                        if remaining >= 2 {
+                            if pos + 11 > bits.len() {
+                                return Err("Truncated alphanumeric data".to_string());
+                            }
+                            let value = Self::read_bits(&bits, &mut pos, 11);
+                            out.push(ALPHANUMERIC_CHARS[(value / 45) as usize]);
+                            out.push(ALPHANUMERIC_CHARS[(value % 45) as usize]);
+                            remaining -= 2;
+                        } else {
+                            if pos + 6 > bits.len() {
+                                return Err("Truncated alphanumeric data".to_string());
+                            }
+                            let value = Self::read_bits(&bits, &mut pos, 6);
+                            out.push(ALPHANUMERIC_CHARS[value as usize]);
+                            remaining -= 1;
+                        }
+                    }
+                }
+                Mode::Byte => {
+                    for _ in 0..remaining {
+                        if pos + 8 > bits.len() {
+                            return Err("Truncated byte data".to_string());
+                        }
+                        out.push(Self::read_bits(&bits, &mut pos, 8) as u8);
+                    }
+                }
+                // `mode` above is only ever built from 0b0001/0b0010/0b0100;
+                // Kanji's 0b1000 indicator already returned an "unsupported
+                // mode" error before reaching this match.
+                Mode::Kanji => unreachable!("mode indicator lookup above never produces Kanji"),
+            }
+        }
+
+        String::from_utf8(out).map_err(|_| "Decoded data is not valid UTF-8".to_string())
+    }
+
+    /// Read `count` bits starting at `*pos` (MSB-first), advancing `*pos`.
+    fn read_bits(bits: &[bool], pos: &mut usize, count: usize) -> u32 {
+        let mut value = 0u32;
+        for _ in 0..count {
+            value = (value << 1) | u32::from(bits[*pos]);
+            *pos += 1;
+        }
+        value
+    }
+}
+
+/// Render configuration builder returned by [`QrCode::render`].
+///
+/// Carries the foreground/background colors, quiet-zone width, and
+/// module dimensions shared by `to_svg`/`to_png`/`to_ascii`, so callers
+/// that need non-default styling - a branded color scheme, no quiet
+/// zone, or non-square modules to correct a terminal's character aspect
+/// ratio - don't have to hand-roll their own renderer. `QrCode::to_svg`,
+/// `to_png`, and `to_ascii` stay as thin wrappers over this builder's
+/// defaults, so existing callers keep working unchanged.
+pub struct Renderer<'a> {
+    qr: &'a QrCode,
+    dark_color: String,
+    light_color: String,
+    quiet_zone: usize,
+    module_width: u32,
+    module_height: u32,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(qr: &'a QrCode) -> Self {
+        Renderer {
+            qr,
+            dark_color: "black".to_string(),
+            light_color: "white".to_string(),
+            quiet_zone: 4,
+            module_width: 10,
+            module_height: 10,
+        }
+    }
+
+    /// Set the foreground (dark module) color. Accepts any valid SVG
+    /// color string (`"black"`, `"#1a1a2e"`) for `to_svg`; `to_png`
+    /// requires a `#rrggbb` hex string, since PNG has no named-color
+    /// table.
+    pub fn dark_color(mut self, color: impl Into<String>) -> Self {
+        self.dark_color = color.into();
+        self
+    }
+
+    /// Set the background (light module) color. See [`Self::dark_color`].
+    pub fn light_color(mut self, color: impl Into<String>) -> Self {
+        self.light_color = color.into();
+        self
+    }
+
+    /// Set the quiet-zone border width, in modules. `0` disables it.
+    pub fn quiet_zone(mut self, modules: usize) -> Self {
+        self.quiet_zone = modules;
+        self
+    }
+
+    /// Set independent horizontal/vertical module scaling, e.g.
+    /// `module_dimensions(2, 1)` to correct a terminal's roughly 1:2
+    /// character aspect ratio in `to_ascii`.
+    pub fn module_dimensions(mut self, width: u32, height: u32) -> Self {
+        self.module_width = width;
+        self.module_height = height;
+        self
+    }
+
+    /// Render as an SVG string using this builder's colors, quiet zone,
+    /// and module dimensions.
+    pub fn to_svg(&self) -> String {
+        let size = self.qr.size();
+        let qz = self.quiet_zone;
+        let total_width = (size + 2 * qz) * self.module_width as usize;
+        let total_height = (size + 2 * qz) * self.module_height as usize;
+
+        let mut svg = format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}" width="{}" height="{}">"#,
+            total_width, total_height, total_width, total_height
+        );
+
+        svg.push_str(&format!(
+            r#"<rect width="{}" height="{}" fill="{}"/>"#,
+            total_width, total_height, self.light_color
+        ));
+
+        for row in 0..size {
+            for col in 0..size {
+                if self.qr.modules[row][col] {
+                    let x = (col + qz) * self.module_width as usize;
+                    let y = (row + qz) * self.module_height as usize;
+                    svg.push_str(&format!(
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                        x, y, self.module_width, self.module_height, self.dark_color
+                    ));
+                }
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Render as a PNG image using this builder's colors, quiet zone, and
+    /// module dimensions. `dark_color`/`light_color` must be `#rrggbb`
+    /// hex strings; malformed values fall back to black.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> Vec<u8> {
+        let size = self.qr.size();
+        let qz = self.quiet_zone;
+        let total_width = (size + 2 * qz) * self.module_width as usize;
+        let total_height = (size + 2 * qz) * self.module_height as usize;
+
+        let dark = parse_hex_color(&self.dark_color);
+        let light = parse_hex_color(&self.light_color);
+
+        let mut pixels = Vec::with_capacity(total_width * total_height * 3);
+        for _ in 0..total_width * total_height {
+            pixels.extend_from_slice(&[light.0, light.1, light.2]);
+        }
+
+        for row in 0..size {
+            for col in 0..size {
+                if self.qr.modules[row][col] {
+                    let px = (col + qz) * self.module_width as usize;
+                    let py = (row + qz) * self.module_height as usize;
+                    for dy in 0..self.module_height as usize {
+                        for dx in 0..self.module_width as usize {
+                            let idx = ((py + dy) * total_width + (px + dx)) * 3;
+                            pixels[idx] = dark.0;
+                            pixels[idx + 1] = dark.1;
+                            pixels[idx + 2] = dark.2;
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut png_data = Vec::new();
+        {
+            let mut encoder =
+                png::Encoder::new(&mut png_data, total_width as u32, total_height as u32);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().expect("PNG header write failed");
+            writer.write_image_data(&pixels).expect("PNG data write failed");
+        }
+
+        png_data
+    }
+
+    /// Render as ASCII art for terminal display, using this builder's
+    /// quiet zone and module dimensions. Colors are not applicable to
+    /// plain-text output.
+    pub fn to_ascii(&self) -> String {
+        let size = self.qr.size();
+        let qz = self.quiet_zone;
+        let cell_width = self.module_width as usize;
+        let cell_height = self.module_height as usize;
+        let mut result = String::new();
+
+        let blank_row = (size + 2 * qz) * cell_width;
+
+        for _ in 0..qz * cell_height {
+            result.push_str(&" ".repeat(blank_row));
+            result.push('\n');
+        }
+
+        for row in 0..size {
+            for _ in 0..cell_height {
+                result.push_str(&" ".repeat(qz * cell_width));
+                for col in 0..size {
+                    let ch = if self.qr.modules[row][col] { '\u{2588}' } else { ' ' };
+                    for _ in 0..cell_width {
+                        result.push(ch);
+                    }
+                }
+                result.push_str(&" ".repeat(qz * cell_width));
+                result.push('\n');
+            }
+        }
+
+        for _ in 0..qz * cell_height {
+            result.push_str(&" ".repeat(blank_row));
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+/// Parse a `#rrggbb` hex color string into an `(r, g, b)` tuple, falling
+/// back to black if malformed.
+#[cfg(feature = "png")]
+fn parse_hex_color(s: &str) -> (u8, u8, u8) {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 {
+        return (0, 0, 0);
+    }
+    let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(0);
+    let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(0);
+    let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(0);
+    (r, g, b)
+}
+
+/// Bit buffer for accumulating bits before converting to bytes.
+struct BitBuffer {
     bits: Vec<bool>,
 }
 
-impl BitBuffer {
-    fn new() -> Self {
-        Self { bits: Vec::new() }
+impl BitBuffer {
+    fn new() -> Self {
+        Self { bits: Vec::new() }
+    }
+
+    fn append_bits(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            self.bits.push((value >> i) & 1 == 1);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.bits
+            .chunks(8)
+            .map(|chunk| {
+                let mut byte = 0u8;
+                for (i, &bit) in chunk.iter().enumerate() {
+                    if bit {
+                        byte |= 1 << (7 - i);
+                    }
+                }
+                byte
+            })
+            .collect()
+    }
+}
+
+/// GF(2^8) arithmetic for Reed-Solomon encoding, parameterized over the
+/// reducing (primitive) polynomial so the same log/exp machinery can
+/// serve more than one 2D symbology - QR uses `0x11D`
+/// (`x^8+x^4+x^3+x^2+1`); Data Matrix's ECC200 uses `0x12D`
+/// (`x^8+x^5+x^3+x^2+1`, see `data_matrix`).
+///
+/// ## Galois Field GF(2^8)
+///
+/// A finite field with 256 elements used for Reed-Solomon codes.
+///
+/// Elements are represented as polynomials over GF(2) modulo `POLY`.
+///
+/// - Addition: XOR (polynomial addition mod 2)
+/// - Multiplication: Polynomial multiplication mod the irreducible polynomial
+///
+/// We use log/antilog tables for efficient multiplication:
+/// a * b = exp(log(a) + log(b))
+struct GF<const POLY: u16>;
+
+/// QR's field: reducing polynomial `0x11D`, generator `alpha = 2`.
+type GF256 = GF<0x11D>;
+
+impl<const POLY: u16> GF<POLY> {
+    /// Logarithm table (index 1-255 -> exponent)
+    const LOG: [u8; 256] = Self::generate_log_table();
+
+    /// Antilogarithm table (exponent 0-254 -> value)
+    const EXP: [u8; 256] = Self::generate_exp_table();
+
+    const fn generate_exp_table() -> [u8; 256] {
+        let mut table = [0u8; 256];
+        let mut x = 1u16;
+
+        let mut i = 0;
+        while i < 255 {
+            table[i] = x as u8;
+            x <<= 1;
+            if x >= 256 {
+                x ^= POLY; // Reduce by primitive polynomial
+            }
+            i += 1;
+        }
+
+        table[255] = table[0]; // Wrap around for convenience
+        table
+    }
+
+    const fn generate_log_table() -> [u8; 256] {
+        let exp = Self::generate_exp_table();
+        let mut table = [0u8; 256];
+
+        let mut i = 0;
+        while i < 255 {
+            table[exp[i] as usize] = i as u8;
+            i += 1;
+        }
+
+        table
+    }
+
+    /// Multiply two elements in GF(2^8).
+    ///
+    /// This is the "good" strategy: two log lookups, a modular add, and an
+    /// exp lookup. It's the reference implementation every other strategy
+    /// is checked against, and the cheapest in memory (the 256-entry
+    /// `LOG`/`EXP` tables already exist for `inv`/`div`).
+    fn mul(a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            let log_sum = (Self::LOG[a as usize] as u16 + Self::LOG[b as usize] as u16) % 255;
+            Self::EXP[log_sum as usize]
+        }
+    }
+
+    /// Full 256x256 multiplication table (64 KiB), built once from `LOG`/
+    /// `EXP`. Backs the "best" strategy: a single indexed load instead of
+    /// two log lookups per multiply, at the cost of holding the table.
+    const MUL: [[u8; 256]; 256] = Self::generate_mul_table();
+
+    const fn generate_mul_table() -> [[u8; 256]; 256] {
+        let mut table = [[0u8; 256]; 256];
+        let mut a = 0usize;
+        while a < 256 {
+            let mut b = 0usize;
+            while b < 256 {
+                table[a][b] = if a == 0 || b == 0 {
+                    0
+                } else {
+                    let log_sum = (Self::LOG[a] as u16 + Self::LOG[b] as u16) % 255;
+                    Self::EXP[log_sum as usize]
+                };
+                b += 1;
+            }
+            a += 1;
+        }
+        table
+    }
+
+    /// Multiply two elements via the precomputed full table - a single
+    /// indexed load, no arithmetic on the hot path.
+    fn mul_table(a: u8, b: u8) -> u8 {
+        Self::MUL[a as usize][b as usize]
+    }
+
+    /// Get alpha^n in GF(2^8).
+    fn exp(n: u8) -> u8 {
+        Self::EXP[n as usize]
+    }
+
+    /// Compute multiplicative inverse in GF(2^8).
+    ///
+    /// For a != 0: inv(a) = alpha^(255 - log(a))
+    /// Since alpha^255 = 1, we have a * inv(a) = alpha^log(a) * alpha^(255-log(a)) = alpha^255 = 1
+    fn inv(a: u8) -> u8 {
+        assert!(a != 0, "Cannot invert zero in GF(2^8)");
+        let log_a = Self::LOG[a as usize];
+        Self::EXP[(255 - log_a as u16) as usize]
+    }
+
+    /// Divide two elements in GF(2^8): a / b = a * inv(b)
+    fn div(a: u8, b: u8) -> u8 {
+        assert!(b != 0, "Cannot divide by zero in GF(2^8)");
+        if a == 0 {
+            0
+        } else {
+            // a / b = exp(log(a) - log(b)) mod 255
+            let log_a = Self::LOG[a as usize] as i16;
+            let log_b = Self::LOG[b as usize] as i16;
+            let log_result = ((log_a - log_b) % 255 + 255) % 255;
+            Self::EXP[log_result as usize]
+        }
+    }
+}
+
+/// Shared interface for Galois field arithmetic. `Gf256` is the only
+/// implementor today, but this is the seam `reed_solomon_correct` and
+/// `shamir` could be generalized over if a second field newtype (e.g.
+/// for Data Matrix's `GF<0x12D>`) is ever needed.
+pub trait GaloisField: Sized + Copy {
+    /// The additive identity (0).
+    fn zero() -> Self;
+    /// The multiplicative identity (1).
+    fn one() -> Self;
+    /// Field addition - XOR, in characteristic 2.
+    fn add(self, other: Self) -> Self;
+    /// Field multiplication.
+    fn mul(self, other: Self) -> Self;
+    /// Field division. Panics if `other` is zero.
+    fn div(self, other: Self) -> Self;
+    /// The multiplicative inverse. Panics if `self` is zero.
+    fn inv(self) -> Self;
+    /// Raise `self` to `exponent` via repeated squaring.
+    fn pow(self, exponent: u32) -> Self;
+}
+
+/// A public GF(2^8) field element under the QR reducing polynomial
+/// (`0x11D`), wrapping the same log/exp tables `GF256` uses internally.
+/// Implements `Add`/`Sub`/`Mul`/`Div`/`Neg` so callers can write ordinary
+/// arithmetic expressions (`a * b`, `a + b`) over field elements instead
+/// of calling `GF256::mul` directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Gf256(u8);
+
+impl Gf256 {
+    /// Build a field element directly from its byte representation.
+    pub const fn new(value: u8) -> Self {
+        Gf256(value)
     }
 
-    fn append_bits(&mut self, value: u32, count: usize) {
-        for i in (0..count).rev() {
-            self.bits.push((value >> i) & 1 == 1);
+    /// The byte representation of this element.
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+
+    /// Iterate over all 255 nonzero elements of the field, in ascending
+    /// byte order.
+    pub fn nonzero_elements() -> impl Iterator<Item = Gf256> {
+        (1u8..=255).map(Gf256)
+    }
+}
+
+impl GaloisField for Gf256 {
+    fn zero() -> Self {
+        Gf256(0)
+    }
+
+    fn one() -> Self {
+        Gf256(1)
+    }
+
+    fn add(self, other: Self) -> Self {
+        Gf256(self.0 ^ other.0)
+    }
+
+    fn mul(self, other: Self) -> Self {
+        Gf256(GF256::mul(self.0, other.0))
+    }
+
+    fn div(self, other: Self) -> Self {
+        Gf256(GF256::div(self.0, other.0))
+    }
+
+    fn inv(self) -> Self {
+        Gf256(GF256::inv(self.0))
+    }
+
+    fn pow(self, exponent: u32) -> Self {
+        let mut result = Gf256(1);
+        let mut base = self;
+        let mut exp = exponent;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = Gf256(GF256::mul(result.0, base.0));
+            }
+            base = Gf256(GF256::mul(base.0, base.0));
+            exp >>= 1;
         }
+        result
     }
+}
 
-    fn len(&self) -> usize {
-        self.bits.len()
+/// Selects how `Gf256::mul_with_strategy` computes a product, trading
+/// memory for speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MulStrategy {
+    /// Log/exp lookup tables (512 bytes total). Cheapest in memory;
+    /// what `GaloisField::mul` uses.
+    Good,
+    /// A 256-entry table for one scalar, built on the fly. Amortizes well
+    /// when reused across many multiplies by the same scalar (see
+    /// `Gf256::mul_slice`), but wasteful for a single multiply.
+    Better,
+    /// The full precomputed 256x256 table (64 KiB): a single indexed
+    /// load, no arithmetic at all. Fastest, at the cost of the table.
+    Best,
+}
+
+impl Gf256 {
+    /// Multiply using an explicit strategy, for benchmarking or for
+    /// constrained targets that want to opt out of the 64 KiB table.
+    pub fn mul_with_strategy(self, other: Self, strategy: MulStrategy) -> Self {
+        let product = match strategy {
+            MulStrategy::Good => GF256::mul(self.0, other.0),
+            MulStrategy::Better => {
+                let mut table = [0u8; 256];
+                for (b, entry) in table.iter_mut().enumerate() {
+                    *entry = GF256::mul(self.0, b as u8);
+                }
+                table[other.0 as usize]
+            }
+            MulStrategy::Best => GF256::mul_table(self.0, other.0),
+        };
+        Gf256(product)
     }
 
-    fn to_bytes(&self) -> Vec<u8> {
-        self.bits
-            .chunks(8)
-            .map(|chunk| {
-                let mut byte = 0u8;
-                for (i, &bit) in chunk.iter().enumerate() {
-                    if bit {
-                        byte |= 1 << (7 - i);
+    /// Multiply every byte of `input` by the constant `scalar`, writing the
+    /// results into `output`. Builds one 256-entry table for `scalar` and
+    /// reuses it for the whole buffer, rather than doing a fresh log/exp
+    /// (or full-table) lookup per byte - this is the hot loop when
+    /// multiplying a Reed-Solomon message polynomial by each generator
+    /// coefficient.
+    ///
+    /// Panics if `input` and `output` have different lengths.
+    pub fn mul_slice(scalar: u8, input: &[u8], output: &mut [u8]) {
+        assert_eq!(input.len(), output.len(), "mul_slice: length mismatch");
+        if scalar == 0 {
+            output.fill(0);
+            return;
+        }
+        let mut table = [0u8; 256];
+        for (b, entry) in table.iter_mut().enumerate() {
+            *entry = GF256::mul_table(scalar, b as u8);
+        }
+        for (o, &i) in output.iter_mut().zip(input.iter()) {
+            *o = table[i as usize];
+        }
+    }
+}
+
+impl std::ops::Add for Gf256 {
+    type Output = Gf256;
+    fn add(self, rhs: Self) -> Self::Output {
+        Gf256(self.0 ^ rhs.0)
+    }
+}
+
+impl std::ops::Sub for Gf256 {
+    type Output = Gf256;
+    /// Characteristic 2, so subtraction is the same as addition (XOR).
+    fn sub(self, rhs: Self) -> Self::Output {
+        Gf256(self.0 ^ rhs.0)
+    }
+}
+
+impl std::ops::Mul for Gf256 {
+    type Output = Gf256;
+    fn mul(self, rhs: Self) -> Self::Output {
+        Gf256(GF256::mul(self.0, rhs.0))
+    }
+}
+
+impl std::ops::Div for Gf256 {
+    type Output = Gf256;
+    /// Panics if `rhs` is zero.
+    fn div(self, rhs: Self) -> Self::Output {
+        Gf256(GF256::div(self.0, rhs.0))
+    }
+}
+
+impl std::ops::Neg for Gf256 {
+    type Output = Gf256;
+    /// Characteristic 2, so negation is a no-op.
+    fn neg(self) -> Self::Output {
+        self
+    }
+}
+
+#[cfg(test)]
+mod gf256_newtype_tests {
+    use super::*;
+
+    #[test]
+    fn test_operator_overloads_match_table_driven_gf256() {
+        for a in 1u8..=255 {
+            for b in 1u8..=255 {
+                let (ga, gb) = (Gf256::new(a), Gf256::new(b));
+                assert_eq!((ga + gb).value(), a ^ b);
+                assert_eq!((ga * gb).value(), GF256::mul(a, b));
+                assert_eq!((ga / gb).value(), GF256::div(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_neg_is_identity() {
+        for a in 0u8..=255 {
+            assert_eq!(-Gf256::new(a), Gf256::new(a));
+        }
+    }
+
+    #[test]
+    fn test_inv_roundtrips_to_one() {
+        for a in 1u8..=255 {
+            let elem = Gf256::new(a);
+            assert_eq!(elem * elem.inv(), Gf256::one());
+        }
+    }
+
+    #[test]
+    fn test_pow_matches_repeated_multiplication() {
+        let elem = Gf256::new(0x03);
+        let mut expected = Gf256::one();
+        for _ in 0..7 {
+            expected = expected * elem;
+        }
+        assert_eq!(elem.pow(7), expected);
+    }
+
+    #[test]
+    fn test_pow_zero_exponent_is_one() {
+        assert_eq!(Gf256::new(0x42).pow(0), Gf256::one());
+    }
+
+    #[test]
+    fn test_nonzero_elements_covers_all_255_values() {
+        let values: Vec<u8> = Gf256::nonzero_elements().map(Gf256::value).collect();
+        assert_eq!(values.len(), 255);
+        assert_eq!(values[0], 1);
+        assert_eq!(values[254], 255);
+    }
+
+    #[test]
+    fn test_mul_table_agrees_with_log_exp_reference() {
+        for a in 0u8..=255 {
+            for b in 0u8..=255 {
+                assert_eq!(GF256::mul_table(a, b), GF256::mul(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_with_strategy_all_agree_with_log_exp_reference() {
+        for a in 0u8..=255 {
+            for b in 0u8..=255 {
+                let (ga, gb) = (Gf256::new(a), Gf256::new(b));
+                let expected = Gf256::new(GF256::mul(a, b));
+                assert_eq!(ga.mul_with_strategy(gb, MulStrategy::Good), expected);
+                assert_eq!(ga.mul_with_strategy(gb, MulStrategy::Better), expected);
+                assert_eq!(ga.mul_with_strategy(gb, MulStrategy::Best), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mul_slice_matches_per_byte_multiplication() {
+        let input: Vec<u8> = (0u8..=255).collect();
+        let mut output = vec![0u8; input.len()];
+        for &scalar in &[0u8, 1, 2, 42, 255] {
+            Gf256::mul_slice(scalar, &input, &mut output);
+            for (i, &byte) in input.iter().enumerate() {
+                assert_eq!(output[i], GF256::mul(scalar, byte));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "length mismatch")]
+    fn test_mul_slice_rejects_mismatched_lengths() {
+        let input = [1u8, 2, 3];
+        let mut output = [0u8; 2];
+        Gf256::mul_slice(7, &input, &mut output);
+    }
+}
+
+/// Shamir's Secret Sharing over this crate's `GF256` field, for splitting
+/// sensitive material (a seed phrase, unified spending key, or viewing
+/// key) into `n` shares, any `k` of which reconstruct the original
+/// secret - each share rendered as its own QR code via `QrCode::encode`.
+///
+/// Each secret byte is the constant term of an independent degree `k-1`
+/// polynomial with random higher coefficients; share `i` collects
+/// `f(i)` for every byte, at the distinct nonzero x-coordinate `i`.
+/// Reconstruction recovers each byte via Lagrange interpolation
+/// evaluated at `x = 0`.
+pub mod shamir {
+    use super::{ErrorCorrectionLevel, GF256, QrCode};
+    use rand::RngCore;
+    use rand::rngs::OsRng;
+    use std::collections::BTreeSet;
+
+    /// Version byte for `Share::to_bytes`'s wire format, bumped if the
+    /// layout ever changes so old and new shares can't be silently
+    /// misread as each other.
+    const SHARE_FORMAT_VERSION: u8 = 1;
+
+    /// One share of a Shamir-split secret.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct Share {
+        /// This share's nonzero x-coordinate (1..=n).
+        pub index: u8,
+        /// The reconstruction threshold `k` this secret was split with.
+        pub threshold: u8,
+        /// `f(index)` for each byte of the secret, in order.
+        pub data: Vec<u8>,
+    }
+
+    impl Share {
+        /// Serialize as `[version][threshold][index][data...]`, so a
+        /// share scanned back from a QR code is self-describing.
+        pub fn to_bytes(&self) -> Vec<u8> {
+            let mut out = Vec::with_capacity(3 + self.data.len());
+            out.push(SHARE_FORMAT_VERSION);
+            out.push(self.threshold);
+            out.push(self.index);
+            out.extend_from_slice(&self.data);
+            out
+        }
+
+        /// Parse a share previously produced by `to_bytes`.
+        pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+            if bytes.len() < 3 {
+                return Err("Share data is too short".to_string());
+            }
+            if bytes[0] != SHARE_FORMAT_VERSION {
+                return Err(format!("Unsupported share format version {}", bytes[0]));
+            }
+            let threshold = bytes[1];
+            let index = bytes[2];
+            if index == 0 {
+                return Err("Share index must be nonzero".to_string());
+            }
+            Ok(Share {
+                index,
+                threshold,
+                data: bytes[3..].to_vec(),
+            })
+        }
+
+        /// Render this share as a QR code holding its base64url-encoded
+        /// wire format, ready to print or scan. Going through text keeps
+        /// this symmetric with `QrCode::encode`'s `&str` input rather
+        /// than adding a raw-bytes entry point to the core QR API.
+        pub fn to_qr_code(&self, ecl: ErrorCorrectionLevel) -> Result<QrCode, String> {
+            QrCode::encode(&base64url_encode(&self.to_bytes()), ecl)
+        }
+
+        /// Recover a share from a QR code produced by `to_qr_code`.
+        pub fn from_qr_code(qr: &QrCode) -> Result<Self, String> {
+            let text = QrCode::decode(&qr.modules)?;
+            Self::from_bytes(&base64url_decode(&text)?)
+        }
+    }
+
+    /// Split `secret` into `n` shares, any `k` of which can reconstruct
+    /// it. `n` and `k` must both be at least 1, and `k` cannot exceed
+    /// `n`.
+    pub fn split(secret: &[u8], n: u8, k: u8) -> Result<Vec<Share>, String> {
+        if n == 0 || k == 0 {
+            return Err("n and k must both be at least 1".to_string());
+        }
+        if k > n {
+            return Err("Threshold k cannot exceed the number of shares n".to_string());
+        }
+        if secret.is_empty() {
+            return Err("Cannot split an empty secret".to_string());
+        }
+
+        let mut coefficients = vec![vec![0u8; k as usize - 1]; secret.len()];
+        let mut rng = OsRng;
+        for row in &mut coefficients {
+            rng.fill_bytes(row);
+        }
+
+        let mut shares: Vec<Share> = (1..=n)
+            .map(|index| Share {
+                index,
+                threshold: k,
+                data: Vec::with_capacity(secret.len()),
+            })
+            .collect();
+
+        for (byte_idx, &secret_byte) in secret.iter().enumerate() {
+            for share in &mut shares {
+                share
+                    .data
+                    .push(eval_polynomial(secret_byte, &coefficients[byte_idx], share.index));
+            }
+        }
+
+        Ok(shares)
+    }
+
+    /// Reconstruct the secret from any `k` (or more) `shares`, via
+    /// Lagrange interpolation evaluated at `x = 0`:
+    /// `s = sum_j y_j * prod_{m != j} x_m / (x_m XOR x_j)`.
+    pub fn reconstruct(shares: &[Share]) -> Result<Vec<u8>, String> {
+        if shares.is_empty() {
+            return Err("At least one share is required".to_string());
+        }
+
+        let threshold = shares[0].threshold;
+        if shares.len() < threshold as usize {
+            return Err(format!(
+                "Need at least {} shares to reconstruct, got {}",
+                threshold,
+                shares.len()
+            ));
+        }
+
+        let share_len = shares[0].data.len();
+        if shares.iter().any(|s| s.data.len() != share_len) {
+            return Err("Shares have mismatched data lengths".to_string());
+        }
+
+        let mut seen_indices = BTreeSet::new();
+        for share in shares {
+            if share.index == 0 {
+                return Err("Share index must be nonzero".to_string());
+            }
+            if !seen_indices.insert(share.index) {
+                return Err("Shares must have distinct x-indices".to_string());
+            }
+        }
+
+        let mut secret = Vec::with_capacity(share_len);
+        for byte_idx in 0..share_len {
+            let mut value = 0u8;
+            for (j, share_j) in shares.iter().enumerate() {
+                let mut numerator = 1u8;
+                let mut denominator = 1u8;
+                for (m, share_m) in shares.iter().enumerate() {
+                    if m == j {
+                        continue;
                     }
+                    numerator = GF256::mul(numerator, share_m.index);
+                    denominator = GF256::mul(denominator, share_m.index ^ share_j.index);
                 }
-                byte
-            })
-            .collect()
+                let lagrange_coefficient = GF256::div(numerator, denominator);
+                value ^= GF256::mul(share_j.data[byte_idx], lagrange_coefficient);
+            }
+            secret.push(value);
+        }
+
+        Ok(secret)
+    }
+
+    /// Evaluate `f(x) = secret_byte + coefficients[0]*x +
+    /// coefficients[1]*x^2 + ...` via Horner's method in GF(256).
+    fn eval_polynomial(secret_byte: u8, coefficients: &[u8], x: u8) -> u8 {
+        let mut result = 0u8;
+        for &coef in coefficients.iter().rev() {
+            result = GF256::mul(result, x) ^ coef;
+        }
+        GF256::mul(result, x) ^ secret_byte
+    }
+
+    const BASE64URL_ALPHABET: &[u8] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    /// Encode bytes as unpadded base64url text, so a `Share`'s binary
+    /// wire format can travel through `QrCode::encode`'s `&str` input.
+    fn base64url_encode(data: &[u8]) -> String {
+        let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+        for chunk in data.chunks(3) {
+            let b0 = chunk[0];
+            let b1 = chunk.get(1).copied();
+            let b2 = chunk.get(2).copied();
+
+            out.push(BASE64URL_ALPHABET[(b0 >> 2) as usize] as char);
+            out.push(BASE64URL_ALPHABET[(((b0 & 0x03) << 4) | (b1.unwrap_or(0) >> 4)) as usize] as char);
+            if let Some(b1) = b1 {
+                out.push(
+                    BASE64URL_ALPHABET[(((b1 & 0x0F) << 2) | (b2.unwrap_or(0) >> 6)) as usize] as char,
+                );
+            }
+            if let Some(b2) = b2 {
+                out.push(BASE64URL_ALPHABET[(b2 & 0x3F) as usize] as char);
+            }
+        }
+        out
+    }
+
+    /// Decode unpadded base64url text produced by `base64url_encode`.
+    fn base64url_decode(s: &str) -> Result<Vec<u8>, String> {
+        fn value(c: u8) -> Result<u8, String> {
+            BASE64URL_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .map(|p| p as u8)
+                .ok_or_else(|| format!("Invalid base64url character: {}", c as char))
+        }
+
+        let chars: Vec<u8> = s.bytes().collect();
+        let mut out = Vec::with_capacity(chars.len() * 3 / 4);
+        for chunk in chars.chunks(4) {
+            let v0 = value(chunk[0])?;
+            let v1 = value(*chunk.get(1).ok_or("Truncated base64url data")?)?;
+            out.push((v0 << 2) | (v1 >> 4));
+
+            if let Some(&c2) = chunk.get(2) {
+                let v2 = value(c2)?;
+                out.push((v1 << 4) | (v2 >> 2));
+
+                if let Some(&c3) = chunk.get(3) {
+                    let v3 = value(c3)?;
+                    out.push((v2 << 6) | v3);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_split_rejects_zero_n_or_k() {
+            assert!(split(b"secret", 0, 1).is_err());
+            assert!(split(b"secret", 5, 0).is_err());
+        }
+
+        #[test]
+        fn test_split_rejects_threshold_above_share_count() {
+            assert!(split(b"secret", 3, 4).is_err());
+        }
+
+        #[test]
+        fn test_split_rejects_empty_secret() {
+            assert!(split(&[], 3, 2).is_err());
+        }
+
+        #[test]
+        fn test_split_and_reconstruct_roundtrip_with_exact_threshold() {
+            let secret = b"unified spending key material";
+            let shares = split(secret, 5, 3).unwrap();
+            let subset = vec![shares[1].clone(), shares[3].clone(), shares[4].clone()];
+            let recovered = reconstruct(&subset).unwrap();
+            assert_eq!(recovered, secret);
+        }
+
+        #[test]
+        fn test_split_and_reconstruct_roundtrip_with_extra_shares() {
+            let secret = b"another secret payload";
+            let shares = split(secret, 6, 4).unwrap();
+            let recovered = reconstruct(&shares).unwrap();
+            assert_eq!(recovered, secret);
+        }
+
+        #[test]
+        fn test_reconstruct_rejects_too_few_shares() {
+            let shares = split(b"secret", 5, 3).unwrap();
+            let subset = vec![shares[0].clone(), shares[1].clone()];
+            assert!(reconstruct(&subset).is_err());
+        }
+
+        #[test]
+        fn test_reconstruct_rejects_duplicate_indices() {
+            let shares = split(b"secret", 5, 3).unwrap();
+            let subset = vec![shares[0].clone(), shares[0].clone(), shares[1].clone()];
+            assert!(reconstruct(&subset).is_err());
+        }
+
+        #[test]
+        fn test_share_to_bytes_from_bytes_roundtrip() {
+            let share = Share {
+                index: 3,
+                threshold: 2,
+                data: vec![0x01, 0x02, 0x03],
+            };
+            let bytes = share.to_bytes();
+            let parsed = Share::from_bytes(&bytes).unwrap();
+            assert_eq!(parsed, share);
+        }
+
+        #[test]
+        fn test_share_to_qr_code_roundtrips_through_decode() {
+            let shares = split(b"seed phrase bytes", 4, 2).unwrap();
+            let qr = shares[0].to_qr_code(ErrorCorrectionLevel::M).unwrap();
+            let recovered = Share::from_qr_code(&qr).unwrap();
+            assert_eq!(recovered, shares[0]);
+        }
     }
 }
 
-/// GF(2^8) arithmetic for Reed-Solomon encoding.
-///
-/// ## Galois Field GF(2^8)
-///
-/// A finite field with 256 elements used for Reed-Solomon codes.
+/// Data Matrix (ECC200) encoding, reusing this crate's `GF` Reed-Solomon
+/// machinery with the reducing polynomial ECC200 specifies (`0x12D`,
+/// `x^8+x^5+x^3+x^2+1`) in place of QR's `0x11D`.
 ///
-/// Elements are represented as polynomials over GF(2) modulo an
-/// irreducible polynomial: x^8 + x^4 + x^3 + x^2 + 1 (0x11D).
-///
-/// - Addition: XOR (polynomial addition mod 2)
-/// - Multiplication: Polynomial multiplication mod the irreducible polynomial
-///
-/// We use log/antilog tables for efficient multiplication:
-/// a * b = exp(log(a) + log(b))
-struct GF256;
+/// This covers the ASCII encodation scheme (the default and simplest of
+/// ECC200's text-compaction modes) and the nine square symbol sizes that
+/// use a single Reed-Solomon block, 10x10 through 26x26 (ISO/IEC 16022
+/// Table 7) - larger symbols needing block interleaving, the
+/// C40/Text/X12/EDIFACT/Base256 compaction modes, and rectangular
+/// symbols are not implemented. Module placement here walks the data
+/// region in row-major order rather than the diagonal "utah" placement
+/// ISO/IEC 16022 Annex F specifies, so symbols produced here are not
+/// byte-compatible with third-party Data Matrix scanners. This crate has
+/// no Data Matrix reader.
+pub mod data_matrix {
+    use super::GF;
+
+    /// ECC200's reducing polynomial: `x^8 + x^5 + x^3 + x^2 + 1`.
+    type GF301 = GF<0x12D>;
+
+    /// `(symbol size, data codewords, error codewords)` for the square,
+    /// single-block ECC200 symbol sizes, per ISO/IEC 16022 Table 7.
+    const SYMBOL_SIZES: [(usize, usize, usize); 9] = [
+        (10, 3, 5),
+        (12, 5, 7),
+        (14, 8, 10),
+        (16, 12, 12),
+        (18, 18, 14),
+        (20, 22, 18),
+        (22, 30, 20),
+        (24, 36, 24),
+        (26, 44, 28),
+    ];
+
+    /// A Data Matrix ECC200 symbol.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct DataMatrix {
+        modules: Vec<Vec<bool>>,
+        size: usize,
+    }
 
-impl GF256 {
-    /// Logarithm table (index 1-255 -> exponent)
-    const LOG: [u8; 256] = Self::generate_log_table();
+    impl DataMatrix {
+        /// Encode `data` as the smallest supported square Data Matrix
+        /// symbol that fits, using the ASCII encodation scheme.
+        pub fn encode(data: &[u8]) -> Result<Self, String> {
+            if data.is_empty() {
+                return Err("Cannot encode empty data".to_string());
+            }
 
-    /// Antilogarithm table (exponent 0-254 -> value)
-    const EXP: [u8; 256] = Self::generate_exp_table();
+            let codewords = encode_ascii(data);
 
-    const fn generate_exp_table() -> [u8; 256] {
-        let mut table = [0u8; 256];
-        let mut x = 1u16;
+            let &(size, data_cw, error_cw) = SYMBOL_SIZES
+                .iter()
+                .find(|&&(_, data_cw, _)| codewords.len() <= data_cw)
+                .ok_or_else(|| "Data too large for the supported Data Matrix sizes".to_string())?;
+
+            let mut padded = codewords;
+            pad_codewords(&mut padded, data_cw);
+
+            let error_codewords = reed_solomon_ecc200(&padded, error_cw);
+            let mut all_codewords = padded;
+            all_codewords.extend_from_slice(&error_codewords);
+
+            let mut modules = vec![vec![false; size]; size];
+            let mut is_function = vec![vec![false; size]; size];
+            place_finder_pattern(&mut modules, &mut is_function, size);
+            place_data_row_major(&mut modules, &is_function, size, &all_codewords);
+
+            Ok(DataMatrix { modules, size })
+        }
+
+        /// Side length of the symbol, in modules.
+        pub fn size(&self) -> usize {
+            self.size
+        }
+
+        /// Render as an SVG, mirroring `QrCode::to_svg`'s plain output.
+        pub fn to_svg(&self, module_size: u32) -> String {
+            let dimension = self.size as u32 * module_size;
+            let mut svg = format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">",
+                dimension
+            );
+            svg.push_str(&format!(
+                "<rect width=\"{0}\" height=\"{0}\" fill=\"white\"/>",
+                dimension
+            ));
+            for (r, row) in self.modules.iter().enumerate() {
+                for (c, &dark) in row.iter().enumerate() {
+                    if dark {
+                        svg.push_str(&format!(
+                            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"black\"/>",
+                            c as u32 * module_size,
+                            r as u32 * module_size,
+                            module_size,
+                            module_size
+                        ));
+                    }
+                }
+            }
+            svg.push_str("</svg>");
+            svg
+        }
+    }
 
+    /// ASCII encodation: two consecutive ASCII digits pack into one
+    /// codeword (`value + 130`); any other byte becomes its own codeword
+    /// (`byte + 1`), per ISO/IEC 16022 Section 5.2.3.
+    fn encode_ascii(data: &[u8]) -> Vec<u8> {
+        let mut codewords = Vec::with_capacity(data.len());
         let mut i = 0;
-        while i < 255 {
-            table[i] = x as u8;
-            x <<= 1;
-            if x >= 256 {
-                x ^= 0x11D; // Reduce by primitive polynomial
+        while i < data.len() {
+            if i + 1 < data.len() && data[i].is_ascii_digit() && data[i + 1].is_ascii_digit() {
+                let value = (data[i] - b'0') * 10 + (data[i + 1] - b'0');
+                codewords.push(value + 130);
+                i += 2;
+            } else {
+                codewords.push(data[i] + 1);
+                i += 1;
             }
-            i += 1;
         }
+        codewords
+    }
 
-        table[255] = table[0]; // Wrap around for convenience
-        table
+    /// Pad `codewords` up to `capacity`: the first pad codeword is 129;
+    /// each one after that is scrambled by ECC200's "253-state"
+    /// pseudo-random sequence so repeated padding doesn't look like
+    /// repeated data to a scanner.
+    fn pad_codewords(codewords: &mut Vec<u8>, capacity: usize) {
+        if codewords.len() >= capacity {
+            return;
+        }
+        codewords.push(129);
+        while codewords.len() < capacity {
+            let position = (codewords.len() + 1) as u32;
+            let prn = ((149 * position) % 253) + 1;
+            let temp = 129 + prn;
+            let value = if temp > 254 { temp - 254 } else { temp };
+            codewords.push(value as u8);
+        }
     }
 
-    const fn generate_log_table() -> [u8; 256] {
-        let exp = Self::generate_exp_table();
-        let mut table = [0u8; 256];
+    /// Build the ECC200 Reed-Solomon generator polynomial of the given
+    /// degree over `GF301` (coefficients in decreasing-degree order) -
+    /// the same construction as `QrCode::reed_solomon_generator`, just
+    /// over Data Matrix's field.
+    fn reed_solomon_generator(degree: usize) -> Vec<u8> {
+        let mut result = vec![1u8];
+        for i in 0..degree {
+            let mut new_result = vec![0u8; result.len() + 1];
+            let alpha_i = GF301::exp(i as u8);
+            for (j, &coef) in result.iter().enumerate() {
+                new_result[j] ^= coef;
+                new_result[j + 1] ^= GF301::mul(coef, alpha_i);
+            }
+            result = new_result;
+        }
+        result
+    }
 
-        let mut i = 0;
-        while i < 255 {
-            table[exp[i] as usize] = i as u8;
-            i += 1;
+    /// Compute `ec_count` ECC200 error-correction codewords for `data`
+    /// over `GF301`, mirroring `QrCode::reed_solomon_encode`.
+    fn reed_solomon_ecc200(data: &[u8], ec_count: usize) -> Vec<u8> {
+        let generator = reed_solomon_generator(ec_count);
+        let mut remainder = vec![0u8; ec_count];
+
+        for &byte in data {
+            let factor = byte ^ remainder[0];
+            remainder.rotate_left(1);
+            *remainder.last_mut().unwrap() = 0;
+
+            for (i, &gen_coef) in generator.iter().skip(1).enumerate() {
+                if i < remainder.len() {
+                    remainder[i] ^= GF301::mul(gen_coef, factor);
+                }
+            }
         }
 
-        table
+        remainder
     }
 
-    /// Multiply two elements in GF(2^8).
-    fn mul(a: u8, b: u8) -> u8 {
-        if a == 0 || b == 0 {
-            0
-        } else {
-            let log_sum = (Self::LOG[a as usize] as u16 + Self::LOG[b as usize] as u16) % 255;
-            Self::EXP[log_sum as usize]
+    /// Draw the solid-L finder pattern (left column, bottom row) and the
+    /// alternating clock track (top row, right column) that border a
+    /// square ECC200 symbol's data region.
+    fn place_finder_pattern(modules: &mut [Vec<bool>], is_function: &mut [Vec<bool>], size: usize) {
+        for r in 0..size {
+            modules[r][0] = true;
+            is_function[r][0] = true;
+        }
+        for c in 0..size {
+            modules[size - 1][c] = true;
+            is_function[size - 1][c] = true;
+        }
+        for c in (1..size).step_by(2) {
+            modules[0][c] = true;
+            is_function[0][c] = true;
+        }
+        for r in (1..size).step_by(2) {
+            modules[r][size - 1] = true;
+            is_function[r][size - 1] = true;
         }
     }
 
-    /// Get alpha^n in GF(2^8).
-    fn exp(n: u8) -> u8 {
-        Self::EXP[n as usize]
-    }
+    /// Write `codewords`' bits (MSB first) into the data region's
+    /// non-function modules in row-major order - see the module doc for
+    /// why this isn't yet the official diagonal placement.
+    fn place_data_row_major(
+        modules: &mut [Vec<bool>],
+        is_function: &[Vec<bool>],
+        size: usize,
+        codewords: &[u8],
+    ) {
+        let mut positions = Vec::with_capacity((size - 2) * (size - 2));
+        for r in 1..size - 1 {
+            for c in 1..size - 1 {
+                if !is_function[r][c] {
+                    positions.push((r, c));
+                }
+            }
+        }
 
-    /// Compute multiplicative inverse in GF(2^8).
-    ///
-    /// For a != 0: inv(a) = alpha^(255 - log(a))
-    /// Since alpha^255 = 1, we have a * inv(a) = alpha^log(a) * alpha^(255-log(a)) = alpha^255 = 1
-    #[cfg(test)]
-    fn inv(a: u8) -> u8 {
-        assert!(a != 0, "Cannot invert zero in GF(2^8)");
-        let log_a = Self::LOG[a as usize];
-        Self::EXP[(255 - log_a as u16) as usize]
+        for (i, &(r, c)) in positions.iter().enumerate() {
+            let byte = i / 8;
+            let bit = 7 - (i % 8);
+            if byte < codewords.len() {
+                modules[r][c] = (codewords[byte] >> bit) & 1 == 1;
+            }
+        }
     }
 
-    /// Divide two elements in GF(2^8): a / b = a * inv(b)
     #[cfg(test)]
-    fn div(a: u8, b: u8) -> u8 {
-        assert!(b != 0, "Cannot divide by zero in GF(2^8)");
-        if a == 0 {
-            0
-        } else {
-            // a / b = exp(log(a) - log(b)) mod 255
-            let log_a = Self::LOG[a as usize] as i16;
-            let log_b = Self::LOG[b as usize] as i16;
-            let log_result = ((log_a - log_b) % 255 + 255) % 255;
-            Self::EXP[log_result as usize]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_gf301_multiplicative_inverse_roundtrips() {
+            for a in 1u8..=255 {
+                assert_eq!(GF301::mul(a, GF301::inv(a)), 1);
+            }
+        }
+
+        #[test]
+        fn test_encode_ascii_packs_digit_pairs() {
+            assert_eq!(encode_ascii(b"12"), vec![12 + 130]);
+            assert_eq!(encode_ascii(b"1"), vec![b'1' + 1]);
+            assert_eq!(encode_ascii(b"A"), vec![b'A' + 1]);
+        }
+
+        #[test]
+        fn test_pad_codewords_fills_to_capacity() {
+            let mut codewords = vec![1, 2];
+            pad_codewords(&mut codewords, 5);
+            assert_eq!(codewords.len(), 5);
+            assert_eq!(codewords[2], 129);
+        }
+
+        #[test]
+        fn test_pad_codewords_no_op_when_already_full() {
+            let mut codewords = vec![1, 2, 3];
+            pad_codewords(&mut codewords, 3);
+            assert_eq!(codewords, vec![1, 2, 3]);
+        }
+
+        #[test]
+        fn test_data_matrix_encode_rejects_empty_data() {
+            assert!(DataMatrix::encode(b"").is_err());
+        }
+
+        #[test]
+        fn test_data_matrix_encode_picks_smallest_size() {
+            let dm = DataMatrix::encode(b"Hi").unwrap();
+            assert_eq!(dm.size(), 10);
+        }
+
+        #[test]
+        fn test_data_matrix_encode_rejects_oversized_data() {
+            let data = vec![b'A'; 100];
+            assert!(DataMatrix::encode(&data).is_err());
+        }
+
+        #[test]
+        fn test_data_matrix_to_svg_contains_black_rects() {
+            let dm = DataMatrix::encode(b"zcash:t1abc").unwrap();
+            let svg = dm.to_svg(4);
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.contains("fill=\"black\""));
         }
     }
 }
@@ -1460,8 +4153,10 @@ mod tests {
 
     /// Test that data encoding produces correct bitstream for "HELLO".
     ///
-    /// Per Thonky QR Code Tutorial, "HELLO" in byte mode should produce:
-    /// Mode: 0100, Count: 00000101, Data: 01001000 01000101 01001100 01001100 01001111
+    /// "HELLO" is made entirely of characters in the alphanumeric set, so
+    /// the optimal segmenter now picks alphanumeric mode over byte mode
+    /// (41 bits vs. 52 bits): mode 0010, count 000000101 (9 bits for v1),
+    /// then pairs (H,E), (L,L) as 11-bit values and a trailing O as 6 bits.
     #[test]
     fn test_data_encoding_hello() {
         // Test the internal encode_data function
@@ -1474,14 +4169,115 @@ mod tests {
         // For version 1-M, total data codewords = 16
         assert_eq!(codewords.len(), 16, "Should have 16 data codewords for v1-M");
 
-        // First codeword: mode (0100) + first 4 bits of count (0000) = 0100_0000 = 0x40
-        assert_eq!(codewords[0], 0x40, "First codeword should be 0x40");
+        // First codeword: mode (0010) + first 4 bits of count (0000) = 0010_0000 = 0x20
+        assert_eq!(codewords[0], 0x20, "First codeword should be 0x20");
+
+        // Second codeword: last 5 bits of count (00101) + first 3 bits of
+        // the (H,E) pair's 11-bit value (011) = 00101_011 = 0x2b
+        assert_eq!(codewords[1], 0x2b, "Second codeword should be 0x2b");
+    }
+
+    /// A fully numeric payload should be packed as a single numeric
+    /// segment (10 bits per 3 digits) rather than byte mode (8 bits/char).
+    #[test]
+    fn test_segment_data_prefers_numeric_for_digits() {
+        let (segments, bits) = QrCode::segment_data(b"0123456789", 1);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].mode, Mode::Numeric);
+        assert_eq!(segments[0].start, 0);
+        assert_eq!(segments[0].end, 10);
+        // header (4 + 10) + data (3 groups of 10 bits + trailing single = 4)
+        assert_eq!(bits, 14 + 3 * 10 + 4);
+    }
+
+    /// A mixed payload should split into segments by mode rather than
+    /// falling back to all-byte encoding.
+    #[test]
+    fn test_segment_data_splits_mixed_payload() {
+        let (segments, _bits) = QrCode::segment_data(b"\xE2\x9C\x9300000000000000", 1);
+        assert!(segments.len() > 1, "expected more than one segment");
+        assert!(segments.iter().any(|s| s.mode == Mode::Numeric));
+        assert!(segments.iter().any(|s| s.mode == Mode::Byte));
+    }
+
+    #[test]
+    fn test_segment_data_mixed_mode_beats_single_byte_segment() {
+        // A long numeric run prefixed by non-numeric text - e.g. a URL
+        // with a numeric ID - should cost strictly fewer bits when split
+        // into Byte+Numeric segments than if forced into one Byte
+        // segment covering the whole string.
+        let data = b"id=00000000000000000000";
+        let (segments, mixed_bits) = QrCode::segment_data(data, 1);
+        assert!(segments.iter().any(|s| s.mode == Mode::Numeric));
+
+        let forced_byte = Segment {
+            mode: Mode::Byte,
+            start: 0,
+            end: data.len(),
+        };
+        let byte_only_bits = QrCode::segment_bit_len(&forced_byte, 1);
+        assert!(mixed_bits < byte_only_bits);
+    }
+
+    #[test]
+    fn test_encode_advanced_forces_mask() {
+        let qr = QrCode::encode_advanced("HELLO", ErrorCorrectionLevel::M, 1, 40, Some(3), false)
+            .unwrap();
+        assert_eq!(qr.mask, 3);
+    }
+
+    #[test]
+    fn test_encode_advanced_rejects_too_small_max_version() {
+        let long_data = "A".repeat(500);
+        let result =
+            QrCode::encode_advanced(&long_data, ErrorCorrectionLevel::L, 1, 2, None, false);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_encode_advanced_boost_ecl_upgrades_level() {
+        let qr =
+            QrCode::encode_advanced("HI", ErrorCorrectionLevel::L, 1, 40, None, true).unwrap();
+        assert_ne!(qr.error_correction, ErrorCorrectionLevel::L);
+    }
+
+    #[test]
+    fn test_encode_micro_picks_smallest_supported_version() {
+        let qr = QrCode::encode_micro("12345", ErrorCorrectionLevel::L).unwrap();
+        assert_eq!(qr.kind(), SymbolKind::Micro);
+        assert_eq!(qr.version, 2);
+        assert_eq!(qr.size(), 13);
+    }
+
+    #[test]
+    fn test_encode_micro_picks_larger_version_for_more_data() {
+        let qr = QrCode::encode_micro("HELLO WORLD 123", ErrorCorrectionLevel::L).unwrap();
+        assert_eq!(qr.version, 4);
+        assert_eq!(qr.size(), 17);
+    }
+
+    #[test]
+    fn test_encode_micro_rejects_unsupported_ecl() {
+        // No Micro version supports H (M4 tops out at Q).
+        let result = QrCode::encode_micro("1", ErrorCorrectionLevel::H);
+        assert!(result.is_err());
+    }
 
-        // Second codeword: last 4 bits of count (0101) + first 4 bits of 'H' (0100) = 0101_0100 = 0x54
-        assert_eq!(codewords[1], 0x54, "Second codeword should be 0x54");
+    #[test]
+    fn test_encode_micro_mask_is_within_range() {
+        let qr = QrCode::encode_micro("42", ErrorCorrectionLevel::L).unwrap();
+        assert!(qr.mask < 4);
+    }
 
-        // Third codeword: last 4 bits of 'H' (1000) + first 4 bits of 'E' (0100) = 1000_0100 = 0x84
-        assert_eq!(codewords[2], 0x84, "Third codeword should be 0x84");
+    #[test]
+    fn test_encode_micro_overflow_error_notes_m1_is_unsupported() {
+        // Data too large for M2-M4 (the only versions this crate attempts)
+        // should fail with an error that's honest about M1 being out of
+        // scope rather than silently implying no Micro version exists.
+        let too_large = "1".repeat(40);
+        let result = QrCode::encode_micro(&too_large, ErrorCorrectionLevel::L);
+        let err = result.unwrap_err();
+        assert!(err.contains("M1"));
     }
 
     /// Debug test to print QR matrix for visual inspection.
@@ -1513,6 +4309,73 @@ mod tests {
         assert!(svg.ends_with("</svg>"));
     }
 
+    #[test]
+    fn test_renderer_svg_uses_custom_colors() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        let svg = qr.render().dark_color("#1a1a2e").light_color("#e0e0e0").to_svg();
+        assert!(svg.contains("fill=\"#1a1a2e\""));
+        assert!(svg.contains("fill=\"#e0e0e0\""));
+    }
+
+    #[test]
+    fn test_renderer_quiet_zone_zero_shrinks_svg() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        let with_border = qr.render().to_svg();
+        let without_border = qr.render().quiet_zone(0).to_svg();
+        assert!(without_border.len() < with_border.len());
+    }
+
+    #[test]
+    fn test_renderer_module_dimensions_affect_ascii_width() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        let square = qr.render().quiet_zone(0).module_dimensions(1, 1).to_ascii();
+        let wide = qr.render().quiet_zone(0).module_dimensions(2, 1).to_ascii();
+        let square_width = square.lines().next().unwrap().chars().count();
+        let wide_width = wide.lines().next().unwrap().chars().count();
+        assert_eq!(wide_width, square_width * 2);
+    }
+
+    #[test]
+    fn test_to_svg_matches_renderer_default_module_size() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        assert_eq!(qr.to_svg(10), qr.render().module_dimensions(10, 10).to_svg());
+    }
+
+    #[test]
+    fn test_to_ascii_matches_renderer_wrapper() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        assert_eq!(
+            qr.to_ascii(),
+            qr.render().quiet_zone(2).module_dimensions(2, 1).to_ascii()
+        );
+    }
+
+    #[test]
+    fn test_to_unicode_halves_row_count_versus_to_ascii() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        let ascii_lines = qr.to_ascii().lines().count();
+        let unicode_lines = qr.to_unicode(2).lines().count();
+        // to_ascii uses one quiet-zone-2-bordered row per module row, while
+        // to_unicode packs 2 module rows per output line.
+        assert_eq!(unicode_lines, ascii_lines.div_ceil(2));
+    }
+
+    #[test]
+    fn test_to_unicode_wider_quiet_zone_widens_output() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        let narrow = qr.to_unicode(1);
+        let wide = qr.to_unicode(4);
+        let narrow_width = narrow.lines().next().unwrap().chars().count();
+        let wide_width = wide.lines().next().unwrap().chars().count();
+        assert_eq!(wide_width, narrow_width + 2 * (4 - 1));
+    }
+
+    #[test]
+    fn test_to_ascii_compact_matches_to_unicode_default_quiet_zone() {
+        let qr = QrCode::encode("TEST", ErrorCorrectionLevel::L).unwrap();
+        assert_eq!(qr.to_ascii_compact(), qr.to_unicode(2));
+    }
+
     /// Test EC level format info encoding per ISO 18004:2015 Table C.1.
     ///
     /// The encoding is NOT the same as the natural ordering:
@@ -1567,6 +4430,178 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_decode_roundtrips_byte_payload() {
+        let qr = QrCode::encode("Hello, World! 123", ErrorCorrectionLevel::M).unwrap();
+        let decoded = QrCode::decode(&qr.modules).unwrap();
+        assert_eq!(decoded, "Hello, World! 123");
+    }
+
+    #[test]
+    fn test_decode_roundtrips_numeric_payload() {
+        let qr = QrCode::encode("0123456789012345", ErrorCorrectionLevel::L).unwrap();
+        let decoded = QrCode::decode(&qr.modules).unwrap();
+        assert_eq!(decoded, "0123456789012345");
+    }
+
+    #[test]
+    fn test_decode_roundtrips_mixed_segments() {
+        let qr = QrCode::encode("\u{2713}00000000000000", ErrorCorrectionLevel::Q).unwrap();
+        let decoded = QrCode::decode(&qr.modules).unwrap();
+        assert_eq!(decoded, "\u{2713}00000000000000");
+    }
+
+    #[test]
+    fn test_decode_corrects_flipped_modules() {
+        let qr = QrCode::encode("Reed-Solomon test payload", ErrorCorrectionLevel::H).unwrap();
+        let mut modules = qr.modules.clone();
+        // Flip a couple of modules deep in the data region; H-level EC
+        // should still recover the original string.
+        let size = modules.len();
+        modules[size - 9][size - 9] = !modules[size - 9][size - 9];
+        modules[size - 9][size - 11] = !modules[size - 9][size - 11];
+        let decoded = QrCode::decode(&modules).unwrap();
+        assert_eq!(decoded, "Reed-Solomon test payload");
+    }
+
+    #[test]
+    fn test_reed_solomon_decode_corrects_byte_errors() {
+        let data = b"correct me please!!!";
+        let ec_count = 10;
+        let generator = QrCode::reed_solomon_generator(ec_count);
+        let ec = QrCode::reed_solomon_encode(data, &generator, ec_count);
+        let mut block = data.to_vec();
+        block.extend_from_slice(&ec);
+
+        // Corrupt the full correctable capacity: ec_count/2 = 5 byte positions.
+        block[0] ^= 0x99;
+        block[2] ^= 0x42;
+        block[7] ^= 0x01;
+        block[15] ^= 0xFF;
+        block[20] ^= 0x3C;
+
+        let corrected = QrCode::reed_solomon_decode(&block, ec_count).unwrap();
+        assert_eq!(&corrected[..data.len()], data);
+    }
+
+    #[test]
+    fn test_reed_solomon_decode_returns_unchanged_block_when_no_errors() {
+        let data = b"clean payload";
+        let ec_count = 8;
+        let generator = QrCode::reed_solomon_generator(ec_count);
+        let ec = QrCode::reed_solomon_encode(data, &generator, ec_count);
+        let mut block = data.to_vec();
+        block.extend_from_slice(&ec);
+
+        let corrected = QrCode::reed_solomon_decode(&block, ec_count).unwrap();
+        assert_eq!(corrected, block);
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_size() {
+        let modules = vec![vec![false; 20]; 20];
+        assert!(QrCode::decode(&modules).is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_micro_sized_matrix() {
+        // `decode` only reverses the full-size pipeline (three finder
+        // patterns, version-derived-from-size, full-size format-info
+        // layout); Micro symbols use a single finder and a different
+        // format-info field, so they're rejected with a clear error
+        // rather than silently misread as a malformed full-size symbol.
+        let qr = QrCode::encode_micro("12345", ErrorCorrectionLevel::L).unwrap();
+        assert!(QrCode::decode(&qr.modules).is_err());
+    }
+
+    #[test]
+    fn test_encode_with_eci_roundtrips_utf8() {
+        let qr = QrCode::encode_with_eci("héllo".as_bytes(), 26, ErrorCorrectionLevel::M).unwrap();
+        let decoded = QrCode::decode(&qr.modules).unwrap();
+        assert_eq!(decoded, "héllo");
+    }
+
+    #[test]
+    fn test_encode_with_eci_rejects_out_of_range_assignment() {
+        let result = QrCode::encode_with_eci(b"hi", 1_000_000, ErrorCorrectionLevel::M);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_eci_designator_byte_length_bands() {
+        assert_eq!(QrCode::eci_designator_bit_len(26), 8);
+        assert_eq!(QrCode::eci_designator_bit_len(127), 8);
+        assert_eq!(QrCode::eci_designator_bit_len(128), 16);
+        assert_eq!(QrCode::eci_designator_bit_len(16_383), 16);
+        assert_eq!(QrCode::eci_designator_bit_len(16_384), 24);
+    }
+
+    #[test]
+    fn test_encode_kanji_rejects_odd_length_data() {
+        let result = QrCode::encode_kanji(&[0x93], ErrorCorrectionLevel::M);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_encode_kanji_rejects_out_of_range_byte_pair() {
+        // 0x00 0x00 falls outside both valid Shift-JIS double-byte ranges.
+        let result = QrCode::encode_kanji(&[0x00, 0x00], ErrorCorrectionLevel::M);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_encode_kanji_accepts_both_byte_pair_ranges() {
+        // 0x935F is in 0x8140-0x9FFC; 0xE4AA is in 0xE040-0xEBBF.
+        let data = [0x93, 0x5F, 0xE4, 0xAA];
+        let qr = QrCode::encode_kanji(&data, ErrorCorrectionLevel::M).unwrap();
+        assert_eq!(qr.kind(), SymbolKind::Full);
+        // decode() only understands Numeric/Alphanumeric/Byte/ECI, so a
+        // Kanji-mode symbol is correctly rejected as an unsupported mode.
+        assert!(QrCode::decode(&qr.modules).is_err());
+    }
+
+    #[test]
+    fn test_kanji_char_value_packs_high_and_low_bytes() {
+        // 0x935F - 0x8140 = 0x121F -> high 0x12, low 0x1F -> 0x12*0xC0 + 0x1F.
+        assert_eq!(QrCode::kanji_char_value(0x93, 0x5F).unwrap(), 0x12 * 0xC0 + 0x1F);
+    }
+
+    #[test]
+    fn test_encode_structured_append_rejects_empty_data() {
+        assert!(QrCode::encode_structured_append(b"", ErrorCorrectionLevel::L).is_err());
+    }
+
+    #[test]
+    fn test_encode_structured_append_single_symbol_for_small_data() {
+        let symbols =
+            QrCode::encode_structured_append(b"small payload", ErrorCorrectionLevel::L).unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].kind(), SymbolKind::Full);
+    }
+
+    #[test]
+    fn test_encode_structured_append_splits_large_payload_across_multiple_symbols() {
+        // One version-40/L symbol holds at most 2956 data codewords;
+        // this comfortably exceeds that even after header overhead.
+        let data = vec![b'A'; 4000];
+        let symbols = QrCode::encode_structured_append(&data, ErrorCorrectionLevel::L).unwrap();
+        assert!(symbols.len() > 1);
+        assert!(symbols.len() <= 16);
+    }
+
+    #[test]
+    fn test_find_min_version_structured_append_accounts_for_header_overhead() {
+        // The Structured Append header (20 bits) plus Byte mode indicator
+        // and count (12 bits at version <=9) must fit alongside the data,
+        // so a slice needs a version at least as large as an equivalent
+        // plain byte-mode encoding would.
+        let version =
+            QrCode::find_min_version_structured_append(10, ErrorCorrectionLevel::L, 1, 40)
+                .unwrap();
+        let capacity_bits = QrCode::get_data_codewords(version, ErrorCorrectionLevel::L) * 8;
+        assert!(capacity_bits >= 10 * 8 + 20 + 4 + 8);
+    }
 }
 
 /// GF(2^8) Property-Based Tests